@@ -2,8 +2,25 @@
 
 extern crate alloc;
 
-use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
-use dev_util::Bucket;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use dev_util::{Bucket, BucketGen};
+#[cfg(all(
+    target_arch = "aarch64",
+    target_feature = "neon",
+    any(feature = "bench", not(feature = "nightly"))
+))]
+use simd_cesu8::implementation::aarch64;
+use simd_cesu8::implementation::fallback;
+#[cfg(feature = "nightly")]
+use simd_cesu8::implementation::simd;
+#[cfg(any(feature = "bench", not(feature = "nightly")))]
+use simd_cesu8::implementation::word;
+#[cfg(all(
+    feature = "std",
+    target_arch = "x86_64",
+    any(feature = "bench", not(feature = "nightly"))
+))]
+use simd_cesu8::implementation::x86;
 
 macro_rules! bench_function {
     ($group:ident, $function:expr, $data:ident $(,)?) => {
@@ -29,6 +46,9 @@ fn bench(c: &mut Criterion) {
     let utf8_clamped_3_strings = Bucket::utf8_clamped_width(3);
     let utf8_clamped_4_strings = Bucket::utf8_clamped_width(4);
     let interspersed_strings = Bucket::interspersed();
+    let emoji_strings = Bucket::emoji();
+    let ascii_sparse_nulls_one_percent_strings = Bucket::ascii_sparse_nulls(0.01);
+    let ascii_sparse_nulls_ten_percent_strings = Bucket::ascii_sparse_nulls(0.1);
 
     let null_bytes = null_strings.clone().into_bytes();
     let ascii_non_null_bytes = ascii_non_null_strings.clone().into_bytes();
@@ -42,6 +62,8 @@ fn bench(c: &mut Criterion) {
     let mutf8_null_bytes = Bucket::mutf8_null_bytes();
     let interspersed_cesu8_bytes = Bucket::interspersed_cesu8();
     let interspersed_mutf8_bytes = Bucket::interspersed_mutf8();
+    let mutf8_sparse_nulls_one_percent_bytes = Bucket::mutf8_sparse_nulls(0.01);
+    let mutf8_sparse_nulls_ten_percent_bytes = Bucket::mutf8_sparse_nulls(0.1);
 
     ////////////////////////////////////////////////////////////////////////////
 
@@ -57,6 +79,12 @@ fn bench(c: &mut Criterion) {
             bench_function!(group, $function, utf8_clamped_3_strings);
             bench_function!(group, $function, utf8_clamped_4_strings);
             bench_function!(group, $function, interspersed_strings);
+            bench_function!(group, $function, emoji_strings);
+
+            if ($group_name).contains("mutf8") {
+                bench_function!(group, $function, ascii_sparse_nulls_one_percent_strings);
+                bench_function!(group, $function, ascii_sparse_nulls_ten_percent_strings);
+            }
 
             group.finish();
         };
@@ -86,6 +114,8 @@ fn bench(c: &mut Criterion) {
             if ($group_name).contains("mutf8") {
                 bench_function!(group, $function, mutf8_null_bytes);
                 bench_function!(group, $function, interspersed_mutf8_bytes);
+                bench_function!(group, $function, mutf8_sparse_nulls_one_percent_bytes);
+                bench_function!(group, $function, mutf8_sparse_nulls_ten_percent_bytes);
             }
 
             group.finish();
@@ -96,5 +126,242 @@ fn bench(c: &mut Criterion) {
     decode_group!("decode_mutf8", simd_cesu8::mutf8::decode);
 }
 
-criterion_group!(benches, bench);
+fn bench_offsets(c: &mut Criterion) {
+    let ascii_non_null_strings = Bucket::ascii_non_null();
+    let utf8_clamped_3_strings = Bucket::utf8_clamped_width(3);
+    let interspersed_strings = Bucket::interspersed();
+
+    let ascii_non_null_bytes = ascii_non_null_strings.clone().into_bytes();
+    let utf8_clamped_3_bytes = utf8_clamped_3_strings.clone().into_bytes();
+    let interspersed_cesu8_bytes = Bucket::interspersed_cesu8();
+    let interspersed_mutf8_bytes = Bucket::interspersed_mutf8();
+
+    // NOTE: Every other bucket starts aligned, so these measure whether
+    // misaligned input, like a sub-slice of a larger buffer, has a throughput
+    // cliff. An offset of 0 is included as a reference.
+    macro_rules! offset_group {
+        ($group_name:literal, $function:expr, $throughput:expr, [$($data:ident),+ $(,)?] $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+            group.throughput($throughput);
+
+            for offset in OFFSETS {
+                $(
+                    let data = $data.offset(offset);
+                    bench_function!(group, $function, data);
+                )+
+            }
+
+            group.finish();
+        };
+    }
+
+    offset_group!(
+        "encode_cesu8_offset",
+        simd_cesu8::encode,
+        Bucket::<String>::THROUGHPUT,
+        [
+            ascii_non_null_strings,
+            utf8_clamped_3_strings,
+            interspersed_strings
+        ],
+    );
+    offset_group!(
+        "encode_mutf8_offset",
+        simd_cesu8::mutf8::encode,
+        Bucket::<String>::THROUGHPUT,
+        [
+            ascii_non_null_strings,
+            utf8_clamped_3_strings,
+            interspersed_strings
+        ],
+    );
+    offset_group!(
+        "decode_cesu8_offset",
+        simd_cesu8::decode,
+        Bucket::<Vec<u8>>::THROUGHPUT,
+        [
+            ascii_non_null_bytes,
+            utf8_clamped_3_bytes,
+            interspersed_cesu8_bytes
+        ],
+    );
+    offset_group!(
+        "decode_mutf8_offset",
+        simd_cesu8::mutf8::decode,
+        Bucket::<Vec<u8>>::THROUGHPUT,
+        [
+            ascii_non_null_bytes,
+            utf8_clamped_3_bytes,
+            interspersed_mutf8_bytes
+        ],
+    );
+}
+
+/// The offsets the misaligned benchmarks start their values at.
+const OFFSETS: [usize; 4] = [0, 1, 3, 7];
+
+fn bench_lossy(c: &mut Criterion) {
+    let interspersed_cesu8_bytes = Bucket::interspersed_cesu8();
+    let corrupted_one_percent_bytes = Bucket::corrupted(0.01);
+    let corrupted_ten_percent_bytes = Bucket::corrupted(0.1);
+
+    // NOTE: Valid input is included as a reference, since it never reaches the
+    // code that replaces invalid sequences.
+    macro_rules! lossy_group {
+        ($group_name:literal, $function:expr $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+            group.throughput(Bucket::<Vec<u8>>::THROUGHPUT);
+
+            bench_function!(group, $function, interspersed_cesu8_bytes);
+            bench_function!(group, $function, corrupted_one_percent_bytes);
+            bench_function!(group, $function, corrupted_ten_percent_bytes);
+
+            group.finish();
+        };
+    }
+
+    lossy_group!("decode_lossy_cesu8", simd_cesu8::decode_lossy);
+    lossy_group!("decode_lossy_mutf8", simd_cesu8::mutf8::decode_lossy);
+}
+
+fn bench_value_sizes(c: &mut Criterion) {
+    // NOTE: The same groups are benchmarked with values from a few dozen bytes
+    // up to far larger than the L2 cache, to show how the throughput scales
+    // with the length of the input.
+    macro_rules! value_size_group {
+        ($group_name:literal, $function:expr, [$($bucket:ident),+ $(,)?] $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+
+            for value_size in VALUE_SIZES {
+                let gen = BucketGen::new(value_size);
+                group.throughput(Throughput::Bytes(value_size as u64));
+
+                $(
+                    let data = gen.$bucket();
+                    bench_function!(group, $function, data);
+                )+
+            }
+
+            group.finish();
+        };
+    }
+
+    value_size_group!("encode_cesu8_value_size", simd_cesu8::encode, [
+        ascii_non_null,
+        ascii_single_emoji,
+        interspersed
+    ],);
+    value_size_group!("encode_mutf8_value_size", simd_cesu8::mutf8::encode, [
+        ascii_non_null,
+        ascii_single_emoji,
+        interspersed
+    ],);
+    value_size_group!("encode_exact_cesu8_value_size", simd_cesu8::encode_exact, [
+        ascii_single_emoji,
+        interspersed
+    ],);
+    value_size_group!(
+        "encode_exact_mutf8_value_size",
+        simd_cesu8::mutf8::encode_exact,
+        [ascii_single_emoji, interspersed],
+    );
+    value_size_group!("decode_cesu8_value_size", simd_cesu8::decode, [
+        surrogate_pairs,
+        interspersed_cesu8
+    ],);
+    value_size_group!("decode_mutf8_value_size", simd_cesu8::mutf8::decode, [
+        surrogate_pairs,
+        interspersed_cesu8
+    ],);
+}
+
+/// The value sizes the scaling benchmarks sweep over. Each one is just short of
+/// a power of two, and a multiple of 60 so that every bucket can be generated
+/// at that size.
+const VALUE_SIZES: [usize; 4] = [60, 1020, 16_380, 262_140];
+
+fn bench_scanners(c: &mut Criterion) {
+    let null_bytes = Bucket::null().into_bytes();
+    let ascii_non_null_bytes = Bucket::ascii_non_null().into_bytes();
+    let ascii_null_alternating_bytes = Bucket::ascii_null_alternating().into_bytes();
+    let utf8_clamped_2_bytes = Bucket::utf8_clamped_width(2).into_bytes();
+    let utf8_clamped_3_bytes = Bucket::utf8_clamped_width(3).into_bytes();
+    let utf8_clamped_4_bytes = Bucket::utf8_clamped_width(4).into_bytes();
+    let interspersed_bytes = Bucket::interspersed().into_bytes();
+
+    let surrogate_pair_bytes = Bucket::surrogate_pairs();
+    let interspersed_cesu8_bytes = Bucket::interspersed_cesu8();
+    let mutf8_sparse_nulls_one_percent_bytes = Bucket::mutf8_sparse_nulls(0.01);
+
+    // NOTE: These are what decide whether the input can be borrowed as-is, so
+    // they're timed on their own, for every implementation that's compiled in.
+    // Each one stops at the first byte it's looking for, so the buckets without
+    // one time a full scan, and the rest show how early it bails out.
+    macro_rules! scanner_group {
+        ($group_name:expr, $function:expr $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+            group.throughput(Bucket::<Vec<u8>>::THROUGHPUT);
+
+            bench_function!(group, $function, null_bytes);
+            bench_function!(group, $function, ascii_non_null_bytes);
+            bench_function!(group, $function, ascii_null_alternating_bytes);
+            bench_function!(group, $function, utf8_clamped_2_bytes);
+            bench_function!(group, $function, utf8_clamped_3_bytes);
+            bench_function!(group, $function, utf8_clamped_4_bytes);
+            bench_function!(group, $function, interspersed_bytes);
+
+            bench_function!(group, $function, surrogate_pair_bytes);
+            bench_function!(group, $function, interspersed_cesu8_bytes);
+            bench_function!(group, $function, mutf8_sparse_nulls_one_percent_bytes);
+
+            group.finish();
+        };
+    }
+
+    macro_rules! scanner_groups {
+        ($implementation:ident) => {
+            scanner_group!(
+                concat!(
+                    "contains_utf8_4_byte_char_header_",
+                    stringify!($implementation)
+                ),
+                $implementation::contains_utf8_4_byte_char_header,
+            );
+            scanner_group!(
+                concat!(
+                    "contains_null_or_utf8_4_byte_char_header_",
+                    stringify!($implementation)
+                ),
+                $implementation::contains_null_or_utf8_4_byte_char_header,
+            );
+        };
+    }
+
+    scanner_groups!(fallback);
+    #[cfg(any(feature = "bench", not(feature = "nightly")))]
+    scanner_groups!(word);
+    #[cfg(feature = "nightly")]
+    scanner_groups!(simd);
+    #[cfg(all(
+        feature = "std",
+        target_arch = "x86_64",
+        any(feature = "bench", not(feature = "nightly"))
+    ))]
+    scanner_groups!(x86);
+    #[cfg(all(
+        target_arch = "aarch64",
+        target_feature = "neon",
+        any(feature = "bench", not(feature = "nightly"))
+    ))]
+    scanner_groups!(aarch64);
+}
+
+criterion_group!(
+    benches,
+    bench,
+    bench_offsets,
+    bench_lossy,
+    bench_value_sizes,
+    bench_scanners
+);
 criterion_main!(benches);
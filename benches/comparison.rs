@@ -0,0 +1,143 @@
+#![allow(missing_docs)]
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use dev_util::Bucket;
+
+// NOTE: Unlike the analysis benchmarks, every bucket here is benchmarked once
+// with `simd_cesu8` and once with `cesu8`, under the same group, so that the
+// two can be compared directly by `critcmp` or criterion's own reports.
+macro_rules! compare_function {
+    ($group:ident, $simd_function:expr, $cesu8_function:expr, $data:ident $(,)?) => {
+        let mut iter = $data.iter();
+        $group.bench_function(BenchmarkId::new("simd_cesu8", $data.name()), |b| {
+            b.iter_batched(
+                || iter.next().unwrap(),
+                |i| {
+                    let output = $simd_function(i);
+                    let _ = black_box(output);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+
+        let mut iter = $data.iter();
+        $group.bench_function(BenchmarkId::new("cesu8", $data.name()), |b| {
+            b.iter_batched(
+                || iter.next().unwrap(),
+                |i| {
+                    let output = $cesu8_function(i);
+                    let _ = black_box(output);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    };
+}
+
+fn bench(c: &mut Criterion) {
+    let ascii_non_null_strings = Bucket::ascii_non_null();
+    let utf8_clamped_2_strings = Bucket::utf8_clamped_width(2);
+    let utf8_clamped_3_strings = Bucket::utf8_clamped_width(3);
+    let utf8_clamped_4_strings = Bucket::utf8_clamped_width(4);
+    let interspersed_strings = Bucket::interspersed();
+    let emoji_strings = Bucket::emoji();
+
+    let ascii_non_null_bytes = ascii_non_null_strings.clone().into_bytes();
+    let utf8_clamped_2_bytes = utf8_clamped_2_strings.clone().into_bytes();
+    let utf8_clamped_3_bytes = utf8_clamped_3_strings.clone().into_bytes();
+    let surrogate_pair_bytes = Bucket::surrogate_pairs();
+    let interspersed_cesu8_bytes = Bucket::interspersed_cesu8();
+    let interspersed_mutf8_bytes = Bucket::interspersed_mutf8();
+    let mutf8_null_bytes = Bucket::mutf8_null_bytes();
+
+    ////////////////////////////////////////////////////////////////////////////
+
+    macro_rules! encode_group {
+        ($group_name:literal, $simd_function:expr, $cesu8_function:expr $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+            group.throughput(Bucket::<String>::THROUGHPUT);
+
+            compare_function!(
+                group,
+                $simd_function,
+                $cesu8_function,
+                ascii_non_null_strings
+            );
+            compare_function!(
+                group,
+                $simd_function,
+                $cesu8_function,
+                utf8_clamped_2_strings
+            );
+            compare_function!(
+                group,
+                $simd_function,
+                $cesu8_function,
+                utf8_clamped_3_strings
+            );
+            compare_function!(
+                group,
+                $simd_function,
+                $cesu8_function,
+                utf8_clamped_4_strings
+            );
+            compare_function!(group, $simd_function, $cesu8_function, interspersed_strings);
+            compare_function!(group, $simd_function, $cesu8_function, emoji_strings);
+
+            group.finish();
+        };
+    }
+
+    encode_group!("compare_encode_cesu8", simd_cesu8::encode, cesu8::to_cesu8,);
+    encode_group!(
+        "compare_encode_mutf8",
+        simd_cesu8::mutf8::encode,
+        cesu8::to_java_cesu8,
+    );
+
+    ////////////////////////////////////////////////////////////////////////////
+
+    macro_rules! decode_group {
+        ($group_name:literal, $simd_function:expr, $cesu8_function:expr $(,)?) => {
+            let mut group = c.benchmark_group($group_name);
+            group.throughput(Bucket::<Vec<u8>>::THROUGHPUT);
+
+            compare_function!(group, $simd_function, $cesu8_function, ascii_non_null_bytes);
+            compare_function!(group, $simd_function, $cesu8_function, utf8_clamped_2_bytes);
+            compare_function!(group, $simd_function, $cesu8_function, utf8_clamped_3_bytes);
+            compare_function!(group, $simd_function, $cesu8_function, surrogate_pair_bytes);
+            compare_function!(
+                group,
+                $simd_function,
+                $cesu8_function,
+                interspersed_cesu8_bytes
+            );
+
+            if ($group_name).contains("mutf8") {
+                compare_function!(group, $simd_function, $cesu8_function, mutf8_null_bytes);
+                compare_function!(
+                    group,
+                    $simd_function,
+                    $cesu8_function,
+                    interspersed_mutf8_bytes
+                );
+            }
+
+            group.finish();
+        };
+    }
+
+    decode_group!(
+        "compare_decode_cesu8",
+        simd_cesu8::decode,
+        cesu8::from_cesu8,
+    );
+    decode_group!(
+        "compare_decode_mutf8",
+        simd_cesu8::mutf8::decode,
+        cesu8::from_java_cesu8,
+    );
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
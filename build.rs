@@ -1,10 +1,23 @@
 //! A build script to detect if the current compiler is nightly. If it is, we
 //! automatically enable the `nightly` feature.
+//!
+//! It also detects whether `core::error::Error` is stable, so that `no_std`
+//! builds can implement it without the `nightly` feature.
 
 use rustc_version::{version_meta, Channel};
 
 fn main() {
-    if version_meta().unwrap().channel == Channel::Nightly {
+    let version = version_meta().unwrap();
+
+    if version.channel == Channel::Nightly {
         println!("cargo:rustc-cfg=feature=\"nightly\"");
     }
+
+    // NOTE: `core::error::Error` was stabilized in Rust 1.81. Comparing only
+    // the major and minor versions also counts the nightlies leading up to it.
+    println!("cargo:rustc-check-cfg=cfg(core_error)");
+
+    if (version.semver.major, version.semver.minor) >= (1, 81) {
+        println!("cargo:rustc-cfg=core_error");
+    }
 }
@@ -9,13 +9,15 @@
 extern crate alloc;
 
 use alloc::borrow::Cow;
-use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::Write;
 use core::iter;
+use core::ops::Deref;
 
 use criterion::Throughput;
-use rand::distributions::Uniform;
+use rand::distributions::{Uniform, WeightedIndex};
 use rand::prelude::*;
 use rand_xoshiro::Xoshiro256StarStar;
 
@@ -23,6 +25,7 @@ use rand_xoshiro::Xoshiro256StarStar;
 #[derive(Debug, Clone)]
 pub struct Bucket<T> {
     name: Cow<'static, str>,
+    value_size: usize,
     values: Vec<T>,
 }
 
@@ -34,17 +37,50 @@ impl<T> Bucket<T> {
     /// want to use a number that is divisible by 3 because we want to generate
     /// UTF-8 strings that contain only characters of a certain width, and one
     /// of those widths is 3 bytes.
+    ///
+    /// This is the size of every bucket, unless it's generated by a
+    /// [`BucketGen`] with a different size.
     pub const VALUE_SIZE: usize = 16_380;
 
-    /// The throughput of the bucket in bytes.
+    /// The throughput of a bucket with values of [`Self::VALUE_SIZE`] bytes.
     pub const THROUGHPUT: Throughput = Throughput::Bytes(Self::VALUE_SIZE as u64);
 
+    /// Returns the number of values a bucket holds when each of them is
+    /// `value_size` bytes long.
+    ///
+    /// Buckets of small values hold [`Self::SIZE`] values, but buckets of
+    /// larger values hold fewer, so that no bucket takes up much more memory
+    /// than one of the default size.
+    const fn len_for(value_size: usize) -> usize {
+        let len = Self::SIZE * Self::VALUE_SIZE / value_size;
+
+        if len == 0 {
+            1
+        } else if len > Self::SIZE {
+            Self::SIZE
+        } else {
+            len
+        }
+    }
+
     /// Returns the name of the bucket.
     #[must_use]
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns the size of the values in the bucket in bytes.
+    #[must_use]
+    pub fn value_size(&self) -> usize {
+        self.value_size
+    }
+
+    /// Returns the throughput of the bucket in bytes.
+    #[must_use]
+    pub fn throughput(&self) -> Throughput {
+        Throughput::Bytes(self.value_size as u64)
+    }
+
     /// Creates a new bucket of data.
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
@@ -56,7 +92,7 @@ impl<T> Bucket<T> {
 
 impl Bucket<String> {
     #[must_use]
-    fn new_string<T>(name: T, values: Vec<String>) -> Self
+    fn new_string<T>(name: T, value_size: usize, values: Vec<String>) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
@@ -67,113 +103,565 @@ impl Bucket<String> {
             "{name:?} does not end with \"_strings\""
         );
 
-        assert_eq!(values.len(), Self::SIZE, "{name:?} has an incorrect size");
+        assert_eq!(
+            values.len(),
+            Self::len_for(value_size),
+            "{name:?} has an incorrect size"
+        );
 
         for value in &values {
             assert_eq!(
                 value.len(),
-                Self::VALUE_SIZE,
+                value_size,
                 "{name:?} has an element of incorrect size"
             );
         }
 
         eprintln!("initialized {name:?} bucket");
 
-        Self { name, values }
+        Self {
+            name,
+            value_size,
+            values,
+        }
+    }
+
+    /// Creates a bucket from arbitrary strings, like the lines of a text file,
+    /// so that benchmarks can run against real-world corpora instead of
+    /// generated data.
+    ///
+    /// Unlike the generated buckets, the values can be of any length. Since a
+    /// benchmark takes every value in turn, the
+    /// [`value_size`](Self::value_size) of the bucket, and so its
+    /// [`throughput`](Self::throughput), is their mean length, rounded
+    /// down.
+    ///
+    /// `name` must end with `"_strings"`, and `values` must not be empty.
+    #[must_use]
+    pub fn from_strings<T>(name: T, values: Vec<String>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let name = name.into();
+
+        assert!(
+            name.ends_with("_strings"),
+            "{name:?} does not end with \"_strings\""
+        );
+        assert!(!values.is_empty(), "{name:?} has no values");
+
+        let value_size = values.iter().map(String::len).sum::<usize>() / values.len();
+
+        eprintln!("initialized {name:?} bucket");
+
+        Self {
+            name,
+            value_size,
+            values,
+        }
     }
 
     /// Generates a bucket of strings purely composed of null characters.
     #[must_use]
     pub fn null() -> Self {
-        let value = "\0".repeat(Self::VALUE_SIZE);
-        let values = iter::repeat_n(value, Self::SIZE).collect::<Vec<_>>();
-        Self::new_string("null_strings", values)
+        BucketGen::default().null()
     }
 
     /// Generates a bucket of random ASCII strings that don't contain the null
     /// byte.
     #[must_use]
     pub fn ascii_non_null() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        BucketGen::default().ascii_non_null()
+    }
+
+    /// Generates a bucket of ASCII strings where every other byte is a null
+    /// byte.
+    #[must_use]
+    pub fn ascii_null_alternating() -> Self {
+        BucketGen::default().ascii_null_alternating()
+    }
+
+    /// Generates a bucket of random ASCII strings where each byte is a null
+    /// byte with a probability of `density`, and a non-null ASCII character
+    /// otherwise.
+    ///
+    /// This models binary blobs with realistic, sparse null bytes, as opposed
+    /// to [`Self::null`] and [`Self::ascii_null_alternating`].
+    #[must_use]
+    pub fn ascii_sparse_nulls(density: f64) -> Self {
+        BucketGen::default().ascii_sparse_nulls(density)
+    }
+
+    /// Generates a bucket of UTF-8 strings that only contain characters that
+    /// are of a certain width.
+    #[must_use]
+    pub fn utf8_clamped_width(width: usize) -> Self {
+        BucketGen::default().utf8_clamped_width(width)
+    }
+
+    /// Generates a bucket of UTF-8 strings that contain a uniform spread of
+    /// characters of different widths.
+    ///
+    /// The distribution of characters is as follows:
+    ///
+    /// 1. A non-null ASCII character (1 byte)
+    /// 2. A 2-byte character
+    /// 3. A 3-byte character
+    /// 4. A 4-byte character
+    /// 5. A null byte
+    /// 6. A 2-byte character
+    /// 7. A 3-byte character
+    /// 8. A 4-byte character
+    ///
+    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    #[must_use]
+    pub fn interspersed() -> Self {
+        BucketGen::default().interspersed()
+    }
+
+    /// Generates a bucket of UTF-8 strings purely composed of emoji that are
+    /// outside of the Basic Multilingual Plane, so every character is encoded
+    /// in CESU-8 as a surrogate pair.
+    ///
+    /// Unlike [`Self::utf8_clamped_width`], the emoji aren't uniformly random.
+    /// They're drawn from a table of the most commonly used emoji, following
+    /// Zipf's law, so the first emoji in the table is the most common, like in
+    /// real messages.
+    #[must_use]
+    pub fn emoji() -> Self {
+        BucketGen::default().emoji()
+    }
+
+    /// Generates a bucket of random ASCII strings that don't contain the null
+    /// byte, except for a single emoji outside of the Basic Multilingual Plane
+    /// at a random position.
+    ///
+    /// This is the worst case for encoding with a speculative capacity, since
+    /// the output is barely longer than the input.
+    #[must_use]
+    pub fn ascii_single_emoji() -> Self {
+        BucketGen::default().ascii_single_emoji()
+    }
+
+    /// Converts the bucket of strings into a bucket of bytes.
+    #[must_use]
+    pub fn into_bytes(self) -> Bucket<Vec<u8>> {
+        let values = self.values.into_iter().map(String::into_bytes).collect();
+        let name = self.name.into_owned().replace("_strings", "_bytes");
+
+        eprintln!("initialized {name:?} bucket");
+
+        Bucket {
+            name: name.into(),
+            value_size: self.value_size,
+            values,
+        }
+    }
+
+    /// Copies the bucket so that every value starts `offset` bytes into its
+    /// allocation. See [`Offset`] for details.
+    #[must_use]
+    pub fn offset(&self, offset: usize) -> Bucket<Offset<String>> {
+        let values = self
+            .values
+            .iter()
+            .map(|value| {
+                let mut buffer = "a".repeat(offset);
+                buffer.push_str(value);
+                Offset { buffer, offset }
+            })
+            .collect();
+        let name = self
+            .name
+            .replace("_strings", &format!("_offset_{offset}_strings"));
+
+        eprintln!("initialized {name:?} bucket");
+
+        Bucket {
+            name: name.into(),
+            value_size: self.value_size,
+            values,
+        }
+    }
+}
+
+impl Bucket<Vec<u8>> {
+    #[must_use]
+    fn new_bytes<T>(name: T, value_size: usize, values: Vec<Vec<u8>>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let name = name.into();
+
+        assert!(
+            name.ends_with("_bytes"),
+            "{name:?} does not end with \"_bytes\""
+        );
+        assert_eq!(
+            values.len(),
+            Self::len_for(value_size),
+            "{name:?} has an incorrect size"
+        );
+
+        for value in &values {
+            assert_eq!(
+                value.len(),
+                value_size,
+                "{name:?} has an element of incorrect size"
+            );
+        }
+
+        eprintln!("initialized {name:?} bucket");
+
+        Self {
+            name,
+            value_size,
+            values,
+        }
+    }
+
+    /// Creates a bucket from arbitrary bytes, like the records of a binary
+    /// file, so that benchmarks can run against real-world corpora instead
+    /// of generated data.
+    ///
+    /// Unlike the generated buckets, the values can be of any length. Since a
+    /// benchmark takes every value in turn, the
+    /// [`value_size`](Self::value_size) of the bucket, and so its
+    /// [`throughput`](Self::throughput), is their mean length, rounded
+    /// down.
+    ///
+    /// `name` must end with `"_bytes"`, and `values` must not be empty.
+    #[must_use]
+    pub fn from_bytes<T>(name: T, values: Vec<Vec<u8>>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let name = name.into();
+
+        assert!(
+            name.ends_with("_bytes"),
+            "{name:?} does not end with \"_bytes\""
+        );
+        assert!(!values.is_empty(), "{name:?} has no values");
+
+        let value_size = values.iter().map(Vec::len).sum::<usize>() / values.len();
+
+        eprintln!("initialized {name:?} bucket");
+
+        Self {
+            name,
+            value_size,
+            values,
+        }
+    }
+
+    /// Generates a bucket of bytes that are random UTF-8 characters with a
+    /// width of 4 bytes encoded in CESU-8 as surrogate pairs.
+    #[must_use]
+    pub fn surrogate_pairs() -> Self {
+        BucketGen::default().surrogate_pairs()
+    }
+
+    /// A bucket of bytes that contain only the null byte encoded in MUTF-8.
+    #[must_use]
+    pub fn mutf8_null_bytes() -> Self {
+        BucketGen::default().mutf8_null_bytes()
+    }
+
+    /// Generates a bucket of MUTF-8 bytes that are mostly non-null ASCII
+    /// characters, where each character is a null byte encoded in MUTF-8 (2
+    /// bytes) with a probability of `density`.
+    ///
+    /// If a null byte would be generated, but only one byte is left to fill the
+    /// value, a non-null ASCII character is used instead so that every value is
+    /// exactly [`Self::VALUE_SIZE`] bytes long.
+    #[must_use]
+    pub fn mutf8_sparse_nulls(density: f64) -> Self {
+        BucketGen::default().mutf8_sparse_nulls(density)
+    }
+
+    /// Generates a bucket of CESU-8 bytes that contain a uniform spread of
+    /// characters of different widths.
+    ///
+    /// The distribution of characters is as follows:
+    ///
+    /// 1. An ASCII character (1 byte)
+    /// 2. A 2-byte character
+    /// 3. A 3-byte character
+    /// 4. A 4-byte character encoded in CESU-8 (6 bytes)
+    ///
+    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    #[must_use]
+    pub fn interspersed_cesu8() -> Self {
+        BucketGen::default().interspersed_cesu8()
+    }
+
+    /// Generates a bucket of MUTF-8 bytes that contain a uniform spread of
+    /// characters of different widths.
+    ///
+    /// The distribution of characters is as follows:
+    ///
+    /// 1. A non-null ASCII character (1 byte)
+    /// 2. A non-null ASCII character (1 byte)
+    /// 2. A 2-byte character
+    /// 3. A 3-byte character
+    /// 4. A 4-byte character encoded in CESU-8 (6 bytes)
+    /// 5. A null byte encoded in MUTF-8 (2 bytes)
+    /// 6. A 2-byte character
+    /// 7. A 3-byte character
+    /// 8. A 4-byte character encoded in CESU-8 (6 bytes)
+    ///
+    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    #[must_use]
+    pub fn interspersed_mutf8() -> Self {
+        BucketGen::default().interspersed_mutf8()
+    }
+
+    /// Generates a bucket of bytes that are valid CESU-8 and MUTF-8, except
+    /// that each character is replaced by an invalid sequence with a
+    /// probability of `density`.
+    ///
+    /// The valid characters are a non-null ASCII character half of the time,
+    /// and a random non-null character of any width otherwise. The invalid
+    /// sequences are truncated characters, bad continuation bytes, stray
+    /// continuation bytes, bytes that never appear in either encoding, and
+    /// unpaired surrogates, so benchmarks exercise every way the lossy decoders
+    /// recover from invalid input.
+    ///
+    /// If a character would not fit in the bytes left to fill the value, a
+    /// non-null ASCII character is used instead so that every value is exactly
+    /// [`Self::VALUE_SIZE`] bytes long.
+    #[must_use]
+    pub fn corrupted(density: f64) -> Self {
+        BucketGen::default().corrupted(density)
+    }
+}
+
+/// Generates the same buckets as the constructors on [`Bucket`], but with
+/// values of any size, so benchmarks can measure how the throughput scales with
+/// the length of the input, and from any seed, so they can check that their
+/// results aren't an artifact of one particular set of values.
+///
+/// Some buckets are made of a repeating pattern of characters, and can only be
+/// generated if the value size is a multiple of the pattern's length. Those
+/// generators panic otherwise. Every such length divides 60, so any multiple of
+/// 60 works for every bucket but [`Self::interspersed_mutf8`].
+#[derive(Debug, Clone)]
+pub struct BucketGen {
+    value_size: usize,
+    seed: u64,
+}
+
+impl Default for BucketGen {
+    fn default() -> Self {
+        Self::new(Bucket::<String>::VALUE_SIZE)
+    }
+}
+
+impl BucketGen {
+    /// The seed of the random number generator behind every bucket, unless
+    /// another one is given with [`Self::seed`].
+    pub const DEFAULT_SEED: u64 = 1;
+
+    /// Creates a generator of buckets with values of `value_size` bytes.
+    #[must_use]
+    pub fn new(value_size: usize) -> Self {
+        assert_ne!(value_size, 0, "the value size must not be zero");
+        Self {
+            value_size,
+            seed: Self::DEFAULT_SEED,
+        }
+    }
+
+    /// Seeds the random number generator behind every bucket with `seed`, so
+    /// that it generates an independent sample of values. The same seed always
+    /// generates the same values.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn rng(&self) -> Xoshiro256StarStar {
+        Xoshiro256StarStar::seed_from_u64(self.seed)
+    }
+
+    /// Returns the name of a bucket, mentioning the value size and the seed
+    /// unless they're the defaults, so that the names of the default buckets
+    /// never change.
+    fn name(&self, name: &str) -> Cow<'static, str> {
+        let (base, suffix) = name.rsplit_once('_').unwrap();
+        let mut name = String::from(base);
+
+        if self.value_size != Bucket::<String>::VALUE_SIZE {
+            write!(name, "_{}_byte", self.value_size).unwrap();
+        }
+
+        if self.seed != Self::DEFAULT_SEED {
+            write!(name, "_seed_{}", self.seed).unwrap();
+        }
+
+        Cow::Owned(format!("{name}_{suffix}"))
+    }
+
+    /// Returns the number of values in every bucket this generates.
+    fn len(&self) -> usize {
+        Bucket::<String>::len_for(self.value_size)
+    }
+
+    fn assert_divisible_by(&self, name: &str, pattern_len: usize) {
+        assert_eq!(
+            self.value_size % pattern_len,
+            0,
+            "{name:?} needs a value size divisible by {pattern_len}"
+        );
+    }
+
+    /// Generates [`Bucket::null`].
+    #[must_use]
+    pub fn null(&self) -> Bucket<String> {
+        let value = "\0".repeat(self.value_size);
+        let values = iter::repeat_n(value, self.len()).collect::<Vec<_>>();
+        Bucket::new_string(self.name("null_strings"), self.value_size, values)
+    }
+
+    /// Generates [`Bucket::ascii_non_null`].
+    #[must_use]
+    pub fn ascii_non_null(&self) -> Bucket<String> {
+        let mut rng = self.rng();
         let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
 
-        let values = (0..Self::SIZE)
+        let values = (0..self.len())
             .map(|_| {
                 let bytes = (&mut rng)
                     .sample_iter(&ascii_dist)
-                    .take(Self::VALUE_SIZE)
+                    .take(self.value_size)
                     .collect::<Vec<u8>>();
                 String::from_utf8(bytes).unwrap()
             })
             .collect::<Vec<_>>();
 
-        Self::new_string("ascii_non_null_strings", values)
+        Bucket::new_string(self.name("ascii_non_null_strings"), self.value_size, values)
     }
 
-    /// Generates a bucket of ASCII strings where every other byte is a null
-    /// byte.
+    /// Generates [`Bucket::ascii_null_alternating`].
     #[must_use]
-    pub fn ascii_null_alternating() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+    pub fn ascii_null_alternating(&self) -> Bucket<String> {
+        let mut rng = self.rng();
         let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
 
-        let values = (0..Self::SIZE)
+        let values = (0..self.len())
             .map(|_| {
                 let bytes = (&mut rng)
                     .sample_iter(&ascii_dist)
                     .intersperse(0x00)
-                    .take(Self::VALUE_SIZE)
+                    .take(self.value_size)
                     .collect::<Vec<u8>>();
                 String::from_utf8(bytes).unwrap()
             })
             .collect::<Vec<_>>();
 
-        Self::new_string("ascii_null_alternating_strings", values)
+        Bucket::new_string(
+            self.name("ascii_null_alternating_strings"),
+            self.value_size,
+            values,
+        )
     }
 
-    /// Generates a bucket of UTF-8 strings that only contain characters that
-    /// are of a certain width.
+    /// Generates [`Bucket::ascii_sparse_nulls`].
     #[must_use]
-    pub fn utf8_clamped_width(width: usize) -> Self {
+    pub fn ascii_sparse_nulls(&self, density: f64) -> Bucket<String> {
+        assert!((0.0..=1.0).contains(&density));
+        let mut rng = self.rng();
+        let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
+
+        let values = (0..self.len())
+            .map(|_| {
+                let bytes = (0..self.value_size)
+                    .map(|_| {
+                        if rng.gen_bool(density) {
+                            0x00
+                        } else {
+                            rng.sample(ascii_dist)
+                        }
+                    })
+                    .collect::<Vec<u8>>();
+                String::from_utf8(bytes).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let percent = density * 100.0;
+        Bucket::new_string(
+            self.name(&format!("ascii_sparse_nulls_{percent}_percent_strings")),
+            self.value_size,
+            values,
+        )
+    }
+
+    /// Generates [`Bucket::ascii_single_emoji`].
+    ///
+    /// The value size must be at least 4.
+    #[must_use]
+    pub fn ascii_single_emoji(&self) -> Bucket<String> {
+        assert!(self.value_size >= 4, "the value size must be at least 4");
+        let mut rng = self.rng();
+        let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
+
+        let values = (0..self.len())
+            .map(|_| {
+                let mut bytes = (&mut rng)
+                    .sample_iter(&ascii_dist)
+                    .take(self.value_size - 4)
+                    .collect::<Vec<u8>>();
+                let index = rng.gen_range(0..=bytes.len());
+                bytes.splice(index..index, "💖".bytes());
+                String::from_utf8(bytes).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        Bucket::new_string(
+            self.name("ascii_single_emoji_strings"),
+            self.value_size,
+            values,
+        )
+    }
+
+    /// Generates [`Bucket::utf8_clamped_width`].
+    ///
+    /// The value size must be a multiple of `width`.
+    #[must_use]
+    pub fn utf8_clamped_width(&self, width: usize) -> Bucket<String> {
         assert!(width != 0 && width <= 4);
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let name = self.name(&format!("utf8_clamped_width_{width}_strings"));
+        self.assert_divisible_by(&name, width);
+        let mut rng = self.rng();
 
-        let values = (0..Self::SIZE)
+        let values = (0..self.len())
             .map(|_| {
                 Utf8ClampedGen::new(&mut rng, width)
-                    .take(Self::VALUE_SIZE / width)
+                    .take(self.value_size / width)
                     .collect::<String>()
             })
             .collect::<Vec<_>>();
 
-        Self::new_string(format!("utf8_clamped_width_{width}_strings"), values)
+        Bucket::new_string(name, self.value_size, values)
     }
 
-    /// Generates a bucket of UTF-8 strings that contain a uniform spread of
-    /// characters of different widths.
-    ///
-    /// The distribution of characters is as follows:
+    /// Generates [`Bucket::interspersed`].
     ///
-    /// 1. A non-null ASCII character (1 byte)
-    /// 2. A 2-byte character
-    /// 3. A 3-byte character
-    /// 4. A 4-byte character
-    /// 5. A null byte
-    /// 6. A 2-byte character
-    /// 7. A 3-byte character
-    /// 8. A 4-byte character
-    ///
-    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    /// The value size must be a multiple of 10.
     #[must_use]
-    pub fn interspersed() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+    pub fn interspersed(&self) -> Bucket<String> {
+        let name = self.name("interspersed_strings");
+        self.assert_divisible_by(&name, 4 + 3 + 2 + 1);
+        let mut rng = self.rng();
         let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
 
-        let passes = Self::VALUE_SIZE / (4 + 3 + 2 + 1);
+        let passes = self.value_size / (4 + 3 + 2 + 1);
 
-        let mut values = Vec::with_capacity(Self::SIZE);
+        let mut values = Vec::with_capacity(self.len());
 
-        for _ in 0..Self::SIZE {
+        for _ in 0..self.len() {
             let clamped_1 = (0..passes)
                 .map(|i| {
                     if i % 2 == 0 {
@@ -199,7 +687,7 @@ impl Bucket<String> {
             let mut clamped_3_chars = clamped_3.chars();
             let mut clamped_4_chars = clamped_4.chars();
 
-            let mut string = String::with_capacity(Self::VALUE_SIZE);
+            let mut string = String::with_capacity(self.value_size);
 
             for _ in 0..passes {
                 string.push(clamped_1_chars.next().unwrap());
@@ -208,59 +696,62 @@ impl Bucket<String> {
                 string.push(clamped_4_chars.next().unwrap());
             }
 
-            assert_eq!(string.len(), Self::VALUE_SIZE);
+            assert_eq!(string.len(), self.value_size);
             values.push(string);
         }
 
-        Self::new_string("interspersed_strings", values)
-    }
-
-    /// Converts the bucket of strings into a bucket of bytes.
-    #[must_use]
-    pub fn into_bytes(self) -> Bucket<Vec<u8>> {
-        let values = self.values.into_iter().map(String::into_bytes).collect();
-        let name = self.name.into_owned().replace("_strings", "_bytes");
-        Bucket::new_bytes(name, values)
+        Bucket::new_string(name, self.value_size, values)
     }
-}
 
-impl Bucket<Vec<u8>> {
+    /// Generates [`Bucket::emoji`].
+    ///
+    /// The value size must be a multiple of 4.
     #[must_use]
-    fn new_bytes<T>(name: T, values: Vec<Vec<u8>>) -> Self
-    where
-        T: Into<Cow<'static, str>>,
-    {
-        let name = name.into();
-
-        assert!(
-            name.ends_with("_bytes"),
-            "{name:?} does not end with \"_bytes\""
-        );
-        assert_eq!(values.len(), Self::SIZE, "{name:?} has an incorrect size");
-
-        for value in &values {
-            assert_eq!(
-                value.len(),
-                Self::VALUE_SIZE,
-                "{name:?} has an element of incorrect size"
-            );
-        }
-
-        eprintln!("initialized {name:?} bucket");
+    pub fn emoji(&self) -> Bucket<String> {
+        /// Commonly used emoji outside of the Basic Multilingual Plane, from
+        /// the most to the least common.
+        const EMOJI: [char; 40] = [
+            '😂', '🤣', '👍', '😭', '🙏', '😘', '🥰', '😍', '😊', '🎉', '😁', '💕', '🥺', '😅',
+            '🔥', '🤦', '🤷', '🙄', '😆', '🤗', '😉', '🎂', '🤔', '👏', '🙂', '😳', '🥳', '😎',
+            '👌', '💜', '😔', '💪', '💖', '👀', '😋', '😏', '😢', '👉', '💗', '💯',
+        ];
+
+        let name = self.name("emoji_strings");
+        self.assert_divisible_by(&name, 4);
+        let mut rng = self.rng();
+        // NOTE: Following Zipf's law, the n-th most common emoji is used 1/n
+        // times as often as the most common one.
+        let weights = (1_u32..)
+            .take(EMOJI.len())
+            .map(|rank| 1.0 / f64::from(rank));
+        let emoji_dist = WeightedIndex::new(weights).unwrap();
+
+        let values = (0..self.len())
+            .map(|_| {
+                (&mut rng)
+                    .sample_iter(&emoji_dist)
+                    .map(|index| EMOJI[index])
+                    .take(self.value_size / 4)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
 
-        Self { name, values }
+        Bucket::new_string(name, self.value_size, values)
     }
 
-    /// Generates a bucket of bytes that are random UTF-8 characters with a
-    /// width of 4 bytes encoded in CESU-8 as surrogate pairs.
+    /// Generates [`Bucket::surrogate_pairs`].
+    ///
+    /// The value size must be a multiple of 6.
     #[must_use]
-    pub fn surrogate_pairs() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+    pub fn surrogate_pairs(&self) -> Bucket<Vec<u8>> {
+        let name = self.name("surrogate_pairs_bytes");
+        self.assert_divisible_by(&name, 6);
+        let mut rng = self.rng();
         let mut gen = Utf8ClampedGen::new(&mut rng, 4);
 
-        let passes = Self::VALUE_SIZE / 6;
+        let passes = self.value_size / 6;
 
-        let values = (0..Self::SIZE)
+        let values = (0..self.len())
             .map(|_| {
                 let input = gen.take(passes).collect::<String>();
                 let encoded = cesu8::to_cesu8(&input);
@@ -268,39 +759,68 @@ impl Bucket<Vec<u8>> {
             })
             .collect::<Vec<_>>();
 
-        Self::new_bytes("surrogate_pairs_bytes", values)
+        Bucket::new_bytes(name, self.value_size, values)
     }
 
-    /// A bucket of bytes that contain only the null byte encoded in MUTF-8.
+    /// Generates [`Bucket::mutf8_null_bytes`].
+    ///
+    /// The value size must be a multiple of 2.
     #[must_use]
-    pub fn mutf8_null_bytes() -> Self {
-        let value = [0xc0, 0x80].repeat(Self::VALUE_SIZE / 2);
-        let values = iter::repeat_n(value, Self::SIZE).collect::<Vec<_>>();
-        Self::new_bytes("mutf8_null_bytes", values)
+    pub fn mutf8_null_bytes(&self) -> Bucket<Vec<u8>> {
+        let name = self.name("mutf8_null_bytes");
+        self.assert_divisible_by(&name, 2);
+        let value = [0xc0, 0x80].repeat(self.value_size / 2);
+        let values = iter::repeat_n(value, self.len()).collect::<Vec<_>>();
+        Bucket::new_bytes(name, self.value_size, values)
     }
 
-    /// Generates a bucket of CESU-8 bytes that contain a uniform spread of
-    /// characters of different widths.
-    ///
-    /// The distribution of characters is as follows:
-    ///
-    /// 1. An ASCII character (1 byte)
-    /// 2. A 2-byte character
-    /// 3. A 3-byte character
-    /// 4. A 4-byte character encoded in CESU-8 (6 bytes)
+    /// Generates [`Bucket::mutf8_sparse_nulls`].
+    #[must_use]
+    pub fn mutf8_sparse_nulls(&self, density: f64) -> Bucket<Vec<u8>> {
+        assert!((0.0..=1.0).contains(&density));
+        let mut rng = self.rng();
+        let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
+
+        let values = (0..self.len())
+            .map(|_| {
+                let mut vec = Vec::with_capacity(self.value_size);
+
+                while vec.len() < self.value_size {
+                    if rng.gen_bool(density) && vec.len() + 2 <= self.value_size {
+                        vec.extend_from_slice(&[0xc0, 0x80]);
+                    } else {
+                        vec.push(rng.sample(ascii_dist));
+                    }
+                }
+
+                vec
+            })
+            .collect::<Vec<_>>();
+
+        let percent = density * 100.0;
+        Bucket::new_bytes(
+            self.name(&format!("mutf8_sparse_nulls_{percent}_percent_bytes")),
+            self.value_size,
+            values,
+        )
+    }
+
+    /// Generates [`Bucket::interspersed_cesu8`].
     ///
-    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    /// The value size must be a multiple of 12.
     #[must_use]
-    pub fn interspersed_cesu8() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+    pub fn interspersed_cesu8(&self) -> Bucket<Vec<u8>> {
+        let name = self.name("interspersed_cesu8_bytes");
+        self.assert_divisible_by(&name, 6 + 3 + 2 + 1);
+        let mut rng = self.rng();
         let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
 
-        let passes = Self::VALUE_SIZE / (6 + 3 + 2 + 1);
+        let passes = self.value_size / (6 + 3 + 2 + 1);
 
-        let mut values = Vec::with_capacity(Self::SIZE);
+        let mut values = Vec::with_capacity(self.len());
 
-        for _ in 0..Self::SIZE {
-            let mut vec = Vec::with_capacity(Self::VALUE_SIZE);
+        for _ in 0..self.len() {
+            let mut vec = Vec::with_capacity(self.value_size);
 
             let clamped_1 = (0..passes)
                 .map(|_| {
@@ -335,40 +855,31 @@ impl Bucket<Vec<u8>> {
                 vec.extend_from_slice(&encoded);
             }
 
-            assert_eq!(vec.len(), Self::VALUE_SIZE);
+            assert_eq!(vec.len(), self.value_size);
             values.push(vec);
         }
 
-        Self::new_bytes("interspersed_cesu8_bytes", values)
+        Bucket::new_bytes(name, self.value_size, values)
     }
 
-    /// Generates a bucket of MUTF-8 bytes that contain a uniform spread of
-    /// characters of different widths.
-    ///
-    /// The distribution of characters is as follows:
+    /// Generates [`Bucket::interspersed_mutf8`].
     ///
-    /// 1. A non-null ASCII character (1 byte)
-    /// 2. A non-null ASCII character (1 byte)
-    /// 2. A 2-byte character
-    /// 3. A 3-byte character
-    /// 4. A 4-byte character encoded in CESU-8 (6 bytes)
-    /// 5. A null byte encoded in MUTF-8 (2 bytes)
-    /// 6. A 2-byte character
-    /// 7. A 3-byte character
-    /// 8. A 4-byte character encoded in CESU-8 (6 bytes)
-    ///
-    /// This pattern repeats until the string is filled ([`Self::VALUE_SIZE`]).
+    /// The value size must be a multiple of 26.
     #[must_use]
-    pub fn interspersed_mutf8() -> Self {
-        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+    pub fn interspersed_mutf8(&self) -> Bucket<Vec<u8>> {
+        const PATTERN_LEN: usize = 1 + 1 + 2 + 3 + 6 + 2 + 2 + 3 + 6;
+
+        let name = self.name("interspersed_mutf8_bytes");
+        self.assert_divisible_by(&name, PATTERN_LEN);
+        let mut rng = self.rng();
         let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
 
-        let passes = Self::VALUE_SIZE / (1 + 1 + 2 + 3 + 6 + 2 + 2 + 3 + 6);
+        let passes = self.value_size / PATTERN_LEN;
 
-        let mut values = Vec::with_capacity(Self::SIZE);
+        let mut values = Vec::with_capacity(self.len());
 
-        for _ in 0..Self::SIZE {
-            let mut vec = Vec::with_capacity(Self::VALUE_SIZE);
+        for _ in 0..self.len() {
+            let mut vec = Vec::with_capacity(self.value_size);
 
             let clamped_1 = (0..(passes * 2))
                 .map(|_| {
@@ -412,7 +923,130 @@ impl Bucket<Vec<u8>> {
             values.push(vec);
         }
 
-        Self::new_bytes("interspersed_mutf8_bytes", values)
+        Bucket::new_bytes(name, self.value_size, values)
+    }
+
+    /// Generates [`Bucket::corrupted`].
+    #[must_use]
+    pub fn corrupted(&self, density: f64) -> Bucket<Vec<u8>> {
+        /// Sequences that are invalid in both CESU-8 and MUTF-8. Each one ends
+        /// with ASCII, or a byte that can't start a character, so that it can
+        /// never be completed by the character after it.
+        const INVALID: [&[u8]; 6] = [
+            // A truncated 3-byte character.
+            &[0xe2, 0x82, b'a'],
+            // A 3-byte character with a bad continuation byte.
+            &[0xe2, 0x28, 0xa1],
+            // A stray continuation byte.
+            &[0x80],
+            // A byte that never appears in either encoding.
+            &[0xff],
+            // An unpaired high surrogate.
+            &[0xed, 0xa0, 0xbd, b'a'],
+            // An unpaired low surrogate.
+            &[0xed, 0xb2, 0x96],
+        ];
+
+        assert!((0.0..=1.0).contains(&density));
+        let mut rng = self.rng();
+        let ascii_dist = Uniform::new_inclusive(0x01, 0x7f);
+
+        let values = (0..self.len())
+            .map(|_| {
+                let mut vec = Vec::with_capacity(self.value_size);
+
+                while vec.len() < self.value_size {
+                    let encoded: Cow<'_, [u8]> = if rng.gen_bool(density) {
+                        Cow::Borrowed(INVALID[rng.gen_range(0..INVALID.len())])
+                    } else if rng.gen_bool(0.5) {
+                        Cow::Owned(vec![rng.sample(ascii_dist)])
+                    } else {
+                        let mut buf = [0; 4];
+                        let value = loop {
+                            let random_char = rng.gen::<char>();
+
+                            if random_char != '\0' {
+                                break random_char;
+                            }
+                        };
+                        Cow::Owned(cesu8::to_cesu8(value.encode_utf8(&mut buf)).into_owned())
+                    };
+
+                    if vec.len() + encoded.len() <= self.value_size {
+                        vec.extend_from_slice(&encoded);
+                    } else {
+                        vec.push(rng.sample(ascii_dist));
+                    }
+                }
+
+                vec
+            })
+            .collect::<Vec<_>>();
+
+        let percent = density * 100.0;
+        Bucket::new_bytes(
+            self.name(&format!("corrupted_{percent}_percent_bytes")),
+            self.value_size,
+            values,
+        )
+    }
+}
+
+impl Bucket<Vec<u8>> {
+    /// Copies the bucket so that every value starts `offset` bytes into its
+    /// allocation. See [`Offset`] for details.
+    #[must_use]
+    pub fn offset(&self, offset: usize) -> Bucket<Offset<Vec<u8>>> {
+        let values = self
+            .values
+            .iter()
+            .map(|value| {
+                let mut buffer = b"a".repeat(offset);
+                buffer.extend_from_slice(value);
+                Offset { buffer, offset }
+            })
+            .collect();
+        let name = self
+            .name
+            .replace("_bytes", &format!("_offset_{offset}_bytes"));
+
+        eprintln!("initialized {name:?} bucket");
+
+        Bucket {
+            name: name.into(),
+            value_size: self.value_size,
+            values,
+        }
+    }
+}
+
+/// A value that starts `offset` bytes into its allocation, after `offset`
+/// bytes of ASCII padding.
+///
+/// Fresh allocations are always at least word-aligned, so the values in every
+/// other bucket start aligned. An `offset` that isn't a multiple of the word
+/// size gives a value that starts unaligned, like a sub-slice of a larger
+/// buffer would, which exercises the unaligned head handling of the word and
+/// SIMD implementations. The value dereferences to the part after the padding.
+#[derive(Debug, Clone)]
+pub struct Offset<T> {
+    buffer: T,
+    offset: usize,
+}
+
+impl Deref for Offset<String> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer[self.offset..]
+    }
+}
+
+impl Deref for Offset<Vec<u8>> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer[self.offset..]
     }
 }
 
@@ -427,6 +1061,10 @@ impl<'a, T> IntoIterator for &'a Bucket<T> {
 
 /// An iterator that repeatedly takes values from a slice, resetting the index
 /// when it reaches the end of the slice.
+///
+/// Every constructor of [`Bucket`] makes sure it has at least one value, so
+/// this never ends in practice. If the slice were empty, though, this would
+/// return `None` right away rather than read out of bounds.
 #[derive(Debug, Clone)]
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 pub struct Iter<'a, T> {
@@ -442,9 +1080,9 @@ impl<'a, T> Iterator for Iter<'a, T> {
             self.index = 0;
         }
 
-        // SAFETY: We know that `self.index` is always in bounds because we
-        // reset it to 0 when it reaches the end of the slice.
-        let value = unsafe { self.values.get_unchecked(self.index) };
+        // NOTE: The index is always in bounds after it's reset, unless the
+        // slice is empty, which is what ends the iterator.
+        let value = self.values.get(self.index)?;
         self.index += 1;
 
         Some(value)
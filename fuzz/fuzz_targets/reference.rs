@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    simd_cesu8_fuzz::reference_bytes(data);
+
+    if let Ok(value) = core::str::from_utf8(data) {
+        simd_cesu8_fuzz::reference(value);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    simd_cesu8_fuzz::lossy(data);
+    simd_cesu8_fuzz::reencode(data);
+
+    if let Ok(value) = core::str::from_utf8(data) {
+        simd_cesu8_fuzz::roundtrip(value);
+    }
+});
@@ -0,0 +1,116 @@
+//! Harness functions for the `simd_cesu8` fuzz targets.
+//!
+//! Each function panics if one of the core guarantees of `simd_cesu8` is
+//! violated for the given input, which is exactly what `libfuzzer` looks for.
+
+/// Asserts that encoding a string and decoding it again yields the original
+/// string, for both CESU-8 and MUTF-8, using both the regular and the strict
+/// decoders.
+///
+/// # Panics
+///
+/// Panics if any round trip doesn't produce the original string.
+pub fn roundtrip(value: &str) {
+    let encoded = simd_cesu8::encode(value);
+    assert_eq!(simd_cesu8::decode(&encoded).unwrap(), value);
+    assert_eq!(simd_cesu8::decode_strict(&encoded).unwrap(), value);
+
+    let encoded = simd_cesu8::mutf8::encode(value);
+    assert_eq!(simd_cesu8::mutf8::decode(&encoded).unwrap(), value);
+    assert_eq!(simd_cesu8::mutf8::decode_strict(&encoded).unwrap(), value);
+}
+
+/// Asserts that the lossy decoders accept arbitrary bytes without panicking
+/// and always produce valid UTF-8.
+///
+/// # Panics
+///
+/// Panics if any of the lossy decoders panic, or if they return a string
+/// that isn't valid UTF-8.
+pub fn lossy(bytes: &[u8]) {
+    let decoded = [
+        simd_cesu8::decode_lossy(bytes),
+        simd_cesu8::decode_lossy_strict(bytes),
+        simd_cesu8::mutf8::decode_lossy(bytes),
+        simd_cesu8::mutf8::decode_lossy_strict(bytes),
+    ];
+
+    for string in &decoded {
+        // NOTE: The decoders build their output with `from_utf8_unchecked`, so
+        // we can't take the `str` type as proof of validity here.
+        assert!(core::str::from_utf8(string.as_bytes()).is_ok());
+        // NOTE: Whatever was replaced, the output is an ordinary string, so it
+        // has to survive a round trip like any other.
+        roundtrip(string);
+    }
+
+    if let Ok(string) = simd_cesu8::decode(bytes) {
+        assert_eq!(decoded[0], string);
+    }
+
+    if let Ok(string) = simd_cesu8::mutf8::decode(bytes) {
+        assert_eq!(decoded[2], string);
+    }
+}
+
+/// Asserts that input the strict decoders accept is re-encoded to the exact
+/// same bytes, since CESU-8 and MUTF-8 only have one way to encode each
+/// string.
+///
+/// # Panics
+///
+/// Panics if decoding and re-encoding valid input changes any of its bytes.
+pub fn reencode(bytes: &[u8]) {
+    if let Ok(string) = simd_cesu8::decode_strict(bytes) {
+        assert_eq!(*simd_cesu8::encode(&string), *bytes);
+    }
+
+    if let Ok(string) = simd_cesu8::mutf8::decode_strict(bytes) {
+        assert_eq!(*simd_cesu8::mutf8::encode(&string), *bytes);
+    }
+}
+
+/// Asserts that encoding a string, and decoding the result, gives the same
+/// output as the `cesu8` crate, for both CESU-8 and MUTF-8.
+///
+/// # Panics
+///
+/// Panics if `simd_cesu8` and `cesu8` disagree on any of the outputs.
+pub fn reference(value: &str) {
+    let encoded = simd_cesu8::encode(value);
+    assert_eq!(encoded, cesu8::to_cesu8(value));
+    assert_eq!(
+        simd_cesu8::decode(&encoded).unwrap(),
+        cesu8::from_cesu8(&encoded).unwrap()
+    );
+
+    let encoded = simd_cesu8::mutf8::encode(value);
+    assert_eq!(encoded, cesu8::to_java_cesu8(value));
+    assert_eq!(
+        simd_cesu8::mutf8::decode(&encoded).unwrap(),
+        cesu8::from_java_cesu8(&encoded).unwrap()
+    );
+}
+
+/// Asserts that whenever both `simd_cesu8` and the `cesu8` crate decode the
+/// same arbitrary bytes, they decode them to the same string.
+///
+/// NOTE: Which input is rejected isn't compared, since the two crates are
+/// allowed to differ on input that's neither valid UTF-8 nor canonical CESU-8,
+/// such as overlong encodings.
+///
+/// # Panics
+///
+/// Panics if both crates decode `bytes`, but to different strings.
+pub fn reference_bytes(bytes: &[u8]) {
+    if let (Ok(simd), Ok(reference)) = (simd_cesu8::decode(bytes), cesu8::from_cesu8(bytes)) {
+        assert_eq!(simd, reference);
+    }
+
+    if let (Ok(simd), Ok(reference)) = (
+        simd_cesu8::mutf8::decode(bytes),
+        cesu8::from_java_cesu8(bytes),
+    ) {
+        assert_eq!(simd, reference);
+    }
+}
@@ -0,0 +1,293 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simdutf8::basic::from_utf8;
+
+use crate::error::DecodingError;
+use crate::internal::{DecodeOptions, Flavor};
+use crate::{internal, metrics, mutf8};
+
+/// A reusable scratch buffer for decoding many strings one after another.
+///
+/// See [`Cesu8EncodeArena`] for the encoding counterpart.
+///
+/// Each call to [`decode_next`] or [`decode_next_mutf8`] overwrites the
+/// previous result and returns a borrow of the scratch buffer that is valid
+/// until the next call. Because the buffer only ever grows, decoding amortizes
+/// to zero allocations once it has reached the size of the largest input.
+///
+/// [`decode_next`]: Cesu8Arena::decode_next
+/// [`decode_next_mutf8`]: Cesu8Arena::decode_next_mutf8
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8Arena;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let table: [&[u8]; 3] = [b"Hello", &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96], b"world"];
+/// let mut arena = Cesu8Arena::new();
+/// let mut total = 0;
+///
+/// for bytes in table {
+///     let decoded = arena.decode_next(bytes)?;
+///     total += decoded.chars().count();
+/// }
+///
+/// assert_eq!(total, 11);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Cesu8Arena {
+    buf: String,
+}
+
+impl Cesu8Arena {
+    /// Creates a new, empty arena. This does not allocate.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self { buf: String::new() }
+    }
+
+    /// Creates a new, empty arena with at least the given capacity in bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the capacity exceeds [`isize::MAX`] bytes.
+    #[must_use]
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: String::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the capacity of the scratch buffer in bytes.
+    #[must_use]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Decodes a slice of bytes as CESU-8 into the arena, returning a borrow of
+    /// the decoded string.
+    ///
+    /// This function accepts the same input as [`decode`](crate::decode), so
+    /// valid UTF-8 that is not valid CESU-8 is accepted as-is.
+    ///
+    /// # Errors
+    ///
+    /// If the input is not valid CESU-8 or UTF-8, this function will return a
+    /// [`DecodingError`], and the arena will be left empty.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8Arena;
+    ///
+    /// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+    /// let mut arena = Cesu8Arena::new();
+    ///
+    /// let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+    /// assert_eq!(arena.decode_next(&bytes)?, "💖");
+    ///
+    /// let bytes = b"Hello, world!";
+    /// assert_eq!(arena.decode_next(bytes)?, "Hello, world!");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn decode_next(&mut self, bytes: &[u8]) -> Result<&str, DecodingError> {
+        self.decode(bytes, Flavor::Cesu8)
+    }
+
+    /// Decodes a slice of bytes as MUTF-8 into the arena, returning a borrow of
+    /// the decoded string.
+    ///
+    /// This function accepts the same input as
+    /// [`mutf8::decode`](crate::mutf8::decode), so valid UTF-8 that is not
+    /// valid MUTF-8 is accepted as-is.
+    ///
+    /// # Errors
+    ///
+    /// If the input is not valid MUTF-8 or UTF-8, this function will return a
+    /// [`DecodingError`], and the arena will be left empty.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8Arena;
+    ///
+    /// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+    /// let mut arena = Cesu8Arena::new();
+    ///
+    /// let bytes = [b'a', 0xc0, 0x80, b'b'];
+    /// assert_eq!(arena.decode_next_mutf8(&bytes)?, "a\0b");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn decode_next_mutf8(&mut self, bytes: &[u8]) -> Result<&str, DecodingError> {
+        self.decode(bytes, Flavor::Mutf8)
+    }
+
+    #[inline]
+    fn decode(&mut self, bytes: &[u8], flavor: Flavor) -> Result<&str, DecodingError> {
+        self.buf.clear();
+
+        if let Ok(string) = from_utf8(bytes) {
+            self.buf.push_str(string);
+            metrics::record(bytes.len(), bytes.len(), false);
+            return Ok(&self.buf);
+        }
+
+        // SAFETY: `decode_to` only ever appends complete UTF-8 sequences, and
+        // we clear the buffer if it fails, so `buf` is always valid UTF-8 when
+        // this borrow ends.
+        let vec = unsafe { self.buf.as_mut_vec() };
+        vec.reserve(bytes.len());
+
+        let result = internal::decode_to(
+            bytes,
+            DecodeOptions {
+                flavor,
+                lossy: false,
+            },
+            vec,
+        );
+
+        if let Err(error) = result {
+            vec.clear();
+            return Err(error);
+        }
+
+        metrics::record(bytes.len(), vec.len(), true);
+        Ok(&self.buf)
+    }
+}
+
+/// A reusable scratch buffer for encoding many strings one after another.
+///
+/// This is the encoding counterpart of [`Cesu8Arena`]. Each call to
+/// [`encode_next`] or [`encode_next_mutf8`] overwrites the previous result and
+/// returns a borrow of the scratch buffer that is valid until the next call.
+/// Because the buffer only ever grows, encoding amortizes to zero allocations
+/// once it has reached the size of the largest output.
+///
+/// [`encode_next`]: Cesu8EncodeArena::encode_next
+/// [`encode_next_mutf8`]: Cesu8EncodeArena::encode_next_mutf8
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8EncodeArena;
+///
+/// let mut arena = Cesu8EncodeArena::new();
+/// let mut total = 0;
+///
+/// for value in ["Hello", "💖", "world"] {
+///     total += arena.encode_next(value).len();
+/// }
+///
+/// assert_eq!(total, 16);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Cesu8EncodeArena {
+    buf: Vec<u8>,
+}
+
+impl Cesu8EncodeArena {
+    /// Creates a new, empty arena. This does not allocate.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// Creates a new, empty arena with at least the given capacity in bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the capacity exceeds [`isize::MAX`] bytes.
+    #[must_use]
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the capacity of the scratch buffer in bytes.
+    #[must_use]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Encodes a string to CESU-8 into the arena, returning a borrow of the
+    /// encoded bytes.
+    ///
+    /// The bytes are exactly the ones that [`encode`](crate::encode) would
+    /// return.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to encode the input
+    /// exceeds [`isize::MAX`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8EncodeArena;
+    ///
+    /// let mut arena = Cesu8EncodeArena::new();
+    /// assert_eq!(arena.encode_next("💖"), [
+    ///     0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96
+    /// ]);
+    /// assert_eq!(arena.encode_next("Hello, world!"), b"Hello, world!");
+    /// ```
+    #[inline]
+    pub fn encode_next(&mut self, value: &str) -> &[u8] {
+        self.buf.clear();
+        crate::encode_into(value, &mut self.buf);
+        &self.buf
+    }
+
+    /// Encodes a string to MUTF-8 into the arena, returning a borrow of the
+    /// encoded bytes.
+    ///
+    /// The bytes are exactly the ones that
+    /// [`mutf8::encode`](crate::mutf8::encode) would return.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to encode the input
+    /// exceeds [`isize::MAX`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8EncodeArena;
+    ///
+    /// let mut arena = Cesu8EncodeArena::new();
+    /// assert_eq!(arena.encode_next_mutf8("a\0b"), [b'a', 0xc0, 0x80, b'b']);
+    /// ```
+    #[inline]
+    pub fn encode_next_mutf8(&mut self, value: &str) -> &[u8] {
+        self.buf.clear();
+        mutf8::encode_into(value, &mut self.buf);
+        &self.buf
+    }
+}
@@ -0,0 +1,107 @@
+use core::fmt;
+use core::ops::Deref;
+
+use bytes::Bytes;
+
+/// An immutable string that is backed by a reference-counted [`Bytes`].
+///
+/// This is returned by [`decode_bytes`](crate::decode_bytes) and
+/// [`mutf8::decode_bytes`](crate::mutf8::decode_bytes). When the input is
+/// already valid UTF-8, the string shares the input's allocation, so cloning
+/// and slicing it never copies the string.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let input = Bytes::from_static(b"Hello, world!");
+/// let decoded = simd_cesu8::decode_bytes(&input)?;
+///
+/// assert_eq!(&*decoded, "Hello, world!");
+/// assert_eq!(decoded.as_bytes().as_ptr(), input.as_ptr());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BytesStr(Bytes);
+
+impl BytesStr {
+    /// Wraps `bytes`, which must be valid UTF-8.
+    #[inline]
+    pub(crate) const fn from_utf8_unchecked(bytes: Bytes) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the string as a [`str`].
+    #[must_use]
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `BytesStr` only ever wraps valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Returns the UTF-8 bytes of the string.
+    #[must_use]
+    #[inline]
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.0
+    }
+
+    /// Returns the UTF-8 bytes of the string, without copying them.
+    #[must_use]
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for BytesStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for BytesStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq<str> for BytesStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for BytesStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<BytesStr> for Bytes {
+    #[inline]
+    fn from(value: BytesStr) -> Self {
+        value.0
+    }
+}
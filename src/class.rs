@@ -0,0 +1,16 @@
+/// What a slice of bytes is, as far as CESU-8 is concerned, as returned by
+/// [`classify_cesu8`](crate::classify_cesu8).
+///
+/// Each variant also tells you how the bytes can be stored and decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cesu8Class {
+    /// The bytes are valid CESU-8 and valid UTF-8, i.e. every character is in
+    /// the Basic Multilingual Plane. They can be borrowed as a [`str`] as-is.
+    AsciiOrBmp,
+    /// The bytes are valid CESU-8, but contain at least one surrogate pair, so
+    /// they aren't valid UTF-8 and have to be decoded.
+    ContainsSurrogatePairs,
+    /// The bytes are not valid CESU-8. This includes valid UTF-8 that contains
+    /// 4-byte characters.
+    Invalid,
+}
@@ -0,0 +1,133 @@
+//! Building blocks for encoding and decoding a single character at a time.
+//!
+//! The rest of this crate works with whole strings, which is what most people
+//! want. These functions are for implementing custom framing on top of CESU-8
+//! or MUTF-8, such as a protocol that interleaves characters with other data,
+//! without re-deriving the bit twiddling of surrogate pairs.
+//!
+//! # Examples
+//!
+//! ```
+//! use simd_cesu8::codec;
+//!
+//! # fn main() -> Result<(), simd_cesu8::DecodingError> {
+//! let mut buffer = [0; codec::MAX_CHAR_LEN];
+//! let encoded = codec::encode_char_to_cesu8('💖', &mut buffer);
+//! assert_eq!(encoded, [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+//!
+//! assert_eq!(codec::decode_one(encoded)?, ('💖', 6));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{DecodingError, DecodingErrorKind};
+use crate::internal::{self, Flavor};
+
+/// The most bytes that a single character takes up in CESU-8 or MUTF-8, which
+/// is the length of a surrogate pair.
+pub const MAX_CHAR_LEN: usize = 6;
+
+/// Encodes a character to CESU-8 into `buffer`, and returns the part of
+/// `buffer` that was written.
+///
+/// This is to CESU-8 what [`char::encode_utf8`] is to UTF-8. Characters
+/// outside the Basic Multilingual Plane take up all 6 bytes, as a surrogate
+/// pair, and every other character is encoded exactly like UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::codec;
+///
+/// let mut buffer = [0; codec::MAX_CHAR_LEN];
+/// assert_eq!(codec::encode_char_to_cesu8('a', &mut buffer), b"a");
+/// assert_eq!(codec::encode_char_to_cesu8('\0', &mut buffer), [0x00]);
+/// ```
+#[inline]
+pub fn encode_char_to_cesu8(char: char, buffer: &mut [u8; MAX_CHAR_LEN]) -> &mut [u8] {
+    internal::encode_char(char, Flavor::Cesu8, buffer)
+}
+
+/// Encodes a character to MUTF-8 into `buffer`, and returns the part of
+/// `buffer` that was written.
+///
+/// This is [`encode_char_to_cesu8`], except that null is encoded as
+/// `0xc0 0x80`.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::codec;
+///
+/// let mut buffer = [0; codec::MAX_CHAR_LEN];
+/// assert_eq!(codec::encode_char_to_mutf8('\0', &mut buffer), [0xc0, 0x80]);
+/// ```
+#[inline]
+pub fn encode_char_to_mutf8(char: char, buffer: &mut [u8; MAX_CHAR_LEN]) -> &mut [u8] {
+    internal::encode_char(char, Flavor::Mutf8, buffer)
+}
+
+/// Decodes the first character of a slice of CESU-8 bytes, and returns it
+/// along with the number of bytes it took up.
+///
+/// Only the bytes of the first character are looked at, so the rest of the
+/// input can be anything.
+///
+/// # Errors
+///
+/// If the input is empty, or doesn't start with a valid CESU-8 character, this
+/// function will return a [`DecodingError`]. Like
+/// [`decode_strict`](crate::decode_strict), this includes 4-byte UTF-8
+/// characters.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::codec;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0xc8, 0x85, 0xff];
+/// assert_eq!(codec::decode_one(&bytes)?, ('ȅ', 2));
+/// assert!(codec::decode_one(&bytes[2..]).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn decode_one(bytes: &[u8]) -> Result<(char, usize), DecodingError> {
+    decode_one_with(bytes, Flavor::Cesu8)
+}
+
+/// Decodes the first character of a slice of MUTF-8 bytes, and returns it
+/// along with the number of bytes it took up.
+///
+/// This is the MUTF-8 counterpart of [`decode_one`].
+///
+/// # Errors
+///
+/// If the input is empty, or doesn't start with a valid MUTF-8 character, this
+/// function will return a [`DecodingError`].
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::codec;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// assert_eq!(codec::decode_one_mutf8(&[0xc0, 0x80, b'a'])?, ('\0', 2));
+/// assert!(codec::decode_one_mutf8(&[0x00]).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn decode_one_mutf8(bytes: &[u8]) -> Result<(char, usize), DecodingError> {
+    decode_one_with(bytes, Flavor::Mutf8)
+}
+
+#[inline]
+fn decode_one_with(bytes: &[u8], flavor: Flavor) -> Result<(char, usize), DecodingError> {
+    if bytes.is_empty() {
+        return Err(DecodingError::new(DecodingErrorKind::UnexpectedEof));
+    }
+
+    internal::decode_char(bytes, flavor)
+}
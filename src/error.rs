@@ -1,19 +1,69 @@
-#[cfg(all(feature = "nightly", not(feature = "std")))]
+#[cfg(core_error)]
 use core::error;
 use core::fmt;
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", not(core_error)))]
 use std::error;
 
 /// A zero-sized type that represents an error that occurred while decoding.
 ///
 /// No information is provided where the error occurred or what the error was,
 /// only that an error *did* occur.
+///
+/// With the `detailed-errors` feature, this type is no longer zero-sized, and
+/// also carries the [`DecodingErrorKind`] of the error, which is returned by
+/// its `kind` method. Use [`decode_verbose`](crate::decode_verbose) if you
+/// also need to know where the error occurred.
 #[derive(Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::module_name_repetitions)]
-pub struct DecodingError(pub(crate) ());
+pub struct DecodingError {
+    #[cfg(feature = "detailed-errors")]
+    kind: DecodingErrorKind,
+}
+
+impl DecodingError {
+    /// Creates an error of the given kind.
+    ///
+    /// NOTE: Unless `detailed-errors` is enabled, the kind is thrown away, so
+    /// working it out costs nothing once this is inlined.
+    #[inline]
+    pub(crate) const fn new(kind: DecodingErrorKind) -> Self {
+        #[cfg(not(feature = "detailed-errors"))]
+        let _ = kind;
+
+        Self {
+            #[cfg(feature = "detailed-errors")]
+            kind,
+        }
+    }
+
+    /// Returns why the input failed to decode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::DecodingErrorKind;
+    ///
+    /// let error = simd_cesu8::decode(&[0xed, 0xa0, 0xbd]).unwrap_err();
+    /// assert_eq!(error.kind(), DecodingErrorKind::UnexpectedEof);
+    /// ```
+    #[cfg(feature = "detailed-errors")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "detailed-errors")))]
+    #[must_use]
+    #[inline]
+    pub const fn kind(&self) -> DecodingErrorKind {
+        self.kind
+    }
+}
 
 impl fmt::Debug for DecodingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "detailed-errors")]
+        return f
+            .debug_struct("DecodingError")
+            .field("kind", &self.kind)
+            .finish();
+
+        #[cfg(not(feature = "detailed-errors"))]
         f.write_str("DecodingError")
     }
 }
@@ -24,6 +74,331 @@ impl fmt::Display for DecodingError {
     }
 }
 
-#[cfg(any(feature = "nightly", feature = "std"))]
-#[cfg_attr(docsrs, doc(cfg(any(feature = "nightly", feature = "std"))))]
+#[cfg(any(core_error, feature = "std"))]
 impl error::Error for DecodingError {}
+
+// NOTE: Newer Clippy suggests `core::io::Error`, which is newer than our
+// minimum supported Rust version.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[allow(clippy::std_instead_of_core)]
+impl From<DecodingError> for std::io::Error {
+    /// Converts the error to an [`io::Error`](std::io::Error) of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData), so that decoding can
+    /// be used with `?` in functions that return an [`io::Result`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{self, ErrorKind};
+    ///
+    /// fn read_name(bytes: &[u8]) -> io::Result<String> {
+    ///     Ok(simd_cesu8::decode(bytes)?.into_owned())
+    /// }
+    ///
+    /// let error = read_name(&[0xff]).unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::InvalidData);
+    /// assert_eq!(error.to_string(), "invalid input");
+    /// ```
+    ///
+    /// [`io::Result`]: std::io::Result
+    #[cold]
+    fn from(error: DecodingError) -> Self {
+        Self::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// A zero-sized type that represents an error that occurred while encoding.
+///
+/// This is returned by [`try_encode`](crate::try_encode) and
+/// [`mutf8::try_encode`](crate::mutf8::try_encode) when the encoded output
+/// would exceed [`isize::MAX`] bytes, or couldn't be allocated, and by
+/// [`mutf8::encode_java_string`](crate::mutf8::encode_java_string) when it
+/// would exceed [`u16::MAX`] bytes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EncodeError(pub(crate) ());
+
+impl fmt::Debug for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncodeError")
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("encoded output is too large")
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for EncodeError {}
+
+/// A zero-sized type that represents an output buffer that was too small.
+///
+/// This is returned by [`encode_to_slice`](crate::encode_to_slice) and
+/// [`mutf8::encode_to_slice`](crate::mutf8::encode_to_slice) when the encoded
+/// output doesn't fit in the buffer they were given.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall(pub(crate) ());
+
+impl fmt::Debug for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BufferTooSmall")
+    }
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("output buffer is too small")
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for BufferTooSmall {}
+
+/// The reason a sequence of bytes failed to decode.
+///
+/// This is passed to the recovery policy of
+/// [`decode_selective_lossy`](crate::decode_selective_lossy), so that it can
+/// decide which errors to replace with the [U+FFFD REPLACEMENT CHARACTER] (�)
+/// and which to reject outright.
+///
+/// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[allow(clippy::module_name_repetitions)]
+pub enum DecodingErrorKind {
+    /// A continuation byte (`0x80..=0xbf`) appeared where the first byte of a
+    /// character was expected.
+    UnexpectedContinuation,
+    /// The first byte of a multibyte character wasn't followed by a
+    /// continuation byte.
+    ExpectedContinuation,
+    /// The input ended in the middle of a character or surrogate pair.
+    UnexpectedEof,
+    /// A surrogate wasn't part of a well-formed surrogate pair, such as a lone
+    /// high or low surrogate.
+    InvalidSurrogatePair,
+    /// A character was encoded with more bytes than necessary, such as `0xc0
+    /// 0x80` in CESU-8, or `0xe0 0x80 0x80`.
+    OverlongEncoding,
+    /// The input is valid UTF-8, but not valid in the encoding being decoded,
+    /// such as a 4-byte UTF-8 character, or a raw null byte in MUTF-8.
+    Utf8NotCesu8,
+    /// A byte that can never appear in CESU-8 or MUTF-8 (`0xf5..=0xff`), or
+    /// the start of a 4-byte UTF-8 character above U+10FFFF.
+    InvalidByte,
+}
+
+impl fmt::Display for DecodingErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnexpectedContinuation => "unexpected continuation byte",
+            Self::ExpectedContinuation => "expected continuation byte",
+            Self::UnexpectedEof => "unexpected end of input",
+            Self::InvalidSurrogatePair => "invalid surrogate pair",
+            Self::OverlongEncoding => "overlong encoding",
+            Self::Utf8NotCesu8 => "valid UTF-8 that is not valid CESU-8 or MUTF-8",
+            Self::InvalidByte => "invalid byte",
+        })
+    }
+}
+
+/// An error that occurred while decoding, along with where it occurred.
+///
+/// This is returned by [`decode_verbose`](crate::decode_verbose) and
+/// [`mutf8::decode_verbose`](crate::mutf8::decode_verbose). Unlike the
+/// zero-sized [`DecodingError`], it tells you the offset of the invalid
+/// sequence, the byte at that offset, and why the sequence is invalid, which
+/// is useful when debugging malformed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct DecodingErrorAt {
+    offset: usize,
+    byte: u8,
+    kind: DecodingErrorKind,
+}
+
+impl DecodingErrorAt {
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) const fn new(offset: usize, byte: u8, kind: DecodingErrorKind) -> Self {
+        Self { offset, byte, kind }
+    }
+
+    /// Returns the offset in the input where the invalid sequence begins.
+    #[must_use]
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the byte at [`offset`](DecodingErrorAt::offset), which is the
+    /// first byte of the invalid sequence.
+    #[must_use]
+    #[inline]
+    pub const fn byte(&self) -> u8 {
+        self.byte
+    }
+
+    /// Returns why the sequence is invalid.
+    #[must_use]
+    #[inline]
+    pub const fn kind(&self) -> DecodingErrorKind {
+        self.kind
+    }
+}
+
+impl From<DecodingErrorAt> for DecodingError {
+    #[inline]
+    fn from(error: DecodingErrorAt) -> Self {
+        Self::new(error.kind)
+    }
+}
+
+impl fmt::Display for DecodingErrorAt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {} (byte {:#04x})",
+            self.kind, self.offset, self.byte
+        )
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for DecodingErrorAt {}
+
+/// An error that occurred while reading a string with a varint length prefix.
+///
+/// Unlike [`DecodingError`], this tells you *which* part of the input was
+/// wrong, so that a malformed length prefix can be told apart from a string
+/// that failed to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum VarintStringError {
+    /// The length prefix is not a valid LEB128 varint, either because it's
+    /// longer than ten bytes, or because its value doesn't fit in a [`usize`].
+    InvalidLength,
+    /// The input ended before the end of the length prefix, or before the end
+    /// of the string it describes.
+    UnexpectedEnd,
+    /// The string described by the length prefix failed to decode.
+    Decoding(DecodingError),
+}
+
+impl From<DecodingError> for VarintStringError {
+    #[inline]
+    fn from(error: DecodingError) -> Self {
+        Self::Decoding(error)
+    }
+}
+
+impl fmt::Display for VarintStringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => f.write_str("invalid varint length prefix"),
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::Decoding(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for VarintStringError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Decoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An error that occurred while decoding with [`try_decode`](crate::try_decode)
+/// or [`mutf8::try_decode`](crate::mutf8::try_decode).
+///
+/// Unlike [`DecodingError`], this also covers running out of memory, so that
+/// no input can make decoding panic or abort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum TryDecodeError {
+    /// The decoded output couldn't be allocated.
+    Allocation,
+    /// The input failed to decode.
+    Decoding(DecodingError),
+}
+
+impl From<DecodingError> for TryDecodeError {
+    #[inline]
+    fn from(error: DecodingError) -> Self {
+        Self::Decoding(error)
+    }
+}
+
+impl fmt::Display for TryDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allocation => f.write_str("failed to allocate the decoded output"),
+            Self::Decoding(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for TryDecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Decoding(error) => Some(error),
+            Self::Allocation => None,
+        }
+    }
+}
+
+/// An error that occurred while decoding with
+/// [`decode_to_slice`](crate::decode_to_slice) or
+/// [`mutf8::decode_to_slice`](crate::mutf8::decode_to_slice).
+///
+/// Unlike [`DecodingError`], this also covers the decoded string not fitting
+/// in the output buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum DecodeToSliceError {
+    /// The decoded string doesn't fit in the output buffer.
+    BufferTooSmall(BufferTooSmall),
+    /// The input failed to decode.
+    Decoding(DecodingError),
+}
+
+impl From<BufferTooSmall> for DecodeToSliceError {
+    #[inline]
+    fn from(error: BufferTooSmall) -> Self {
+        Self::BufferTooSmall(error)
+    }
+}
+
+impl From<DecodingError> for DecodeToSliceError {
+    #[inline]
+    fn from(error: DecodingError) -> Self {
+        Self::Decoding(error)
+    }
+}
+
+impl fmt::Display for DecodeToSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall(error) => fmt::Display::fmt(error, f),
+            Self::Decoding(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+#[cfg(any(core_error, feature = "std"))]
+impl error::Error for DecodeToSliceError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::BufferTooSmall(error) => Some(error),
+            Self::Decoding(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// NOTE: The `from_*` methods decode `self`, so they can't follow the usual
+// convention of taking no `self`.
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::borrow::Cow;
+
+use crate::error::DecodingError;
+use crate::mutf8;
+
+/// Methods for encoding a [`str`] to CESU-8 or MUTF-8.
+///
+/// Each method delegates to the free function of the same flavor, so they
+/// behave exactly the same, and read more naturally in a chain of calls.
+///
+/// This trait is sealed, and is only implemented for [`str`].
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8StrExt;
+///
+/// assert_eq!(*"a\0".to_cesu8(), *b"a\0");
+/// assert_eq!(*"a\0".to_mutf8(), [b'a', 0xc0, 0x80]);
+/// ```
+pub trait Cesu8StrExt: sealed::Sealed {
+    /// Encodes `self` to CESU-8, like [`encode`](crate::encode).
+    #[must_use]
+    fn to_cesu8(&self) -> Cow<'_, [u8]>;
+
+    /// Encodes `self` to MUTF-8, like [`mutf8::encode`].
+    #[must_use]
+    fn to_mutf8(&self) -> Cow<'_, [u8]>;
+}
+
+impl Cesu8StrExt for str {
+    #[inline]
+    fn to_cesu8(&self) -> Cow<'_, [u8]> {
+        crate::encode(self)
+    }
+
+    #[inline]
+    fn to_mutf8(&self) -> Cow<'_, [u8]> {
+        mutf8::encode(self)
+    }
+}
+
+/// Methods for decoding a slice of bytes from CESU-8 or MUTF-8.
+///
+/// Each method delegates to the free function of the same flavor, so they
+/// behave exactly the same, and read more naturally in a chain of calls.
+///
+/// This trait is sealed, and is only implemented for `[u8]`.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8BytesExt;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [b'a', 0xc0, 0x80];
+/// assert_eq!(bytes.from_mutf8()?, "a\0");
+/// assert!(bytes.from_cesu8().is_err());
+/// assert_eq!(bytes.from_cesu8_lossy(), "a\u{fffd}\u{fffd}");
+/// # Ok(())
+/// # }
+/// ```
+pub trait Cesu8BytesExt: sealed::Sealed {
+    /// Decodes `self` from CESU-8, like [`decode`](crate::decode).
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error in the same cases as
+    /// [`decode`](crate::decode).
+    fn from_cesu8(&self) -> Result<Cow<'_, str>, DecodingError>;
+
+    /// Decodes `self` from CESU-8, like
+    /// [`decode_lossy`](crate::decode_lossy).
+    #[must_use]
+    fn from_cesu8_lossy(&self) -> Cow<'_, str>;
+
+    /// Decodes `self` from MUTF-8, like [`mutf8::decode`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error in the same cases as [`mutf8::decode`].
+    fn from_mutf8(&self) -> Result<Cow<'_, str>, DecodingError>;
+
+    /// Decodes `self` from MUTF-8, like [`mutf8::decode_lossy`].
+    #[must_use]
+    fn from_mutf8_lossy(&self) -> Cow<'_, str>;
+}
+
+impl Cesu8BytesExt for [u8] {
+    #[inline]
+    fn from_cesu8(&self) -> Result<Cow<'_, str>, DecodingError> {
+        crate::decode(self)
+    }
+
+    #[inline]
+    fn from_cesu8_lossy(&self) -> Cow<'_, str> {
+        crate::decode_lossy(self)
+    }
+
+    #[inline]
+    fn from_mutf8(&self) -> Result<Cow<'_, str>, DecodingError> {
+        mutf8::decode(self)
+    }
+
+    #[inline]
+    fn from_mutf8_lossy(&self) -> Cow<'_, str> {
+        mutf8::decode_lossy(self)
+    }
+}
+
+mod sealed {
+    /// Keeps the extension traits from being implemented outside this crate,
+    /// so that methods can be added to them without breaking anyone.
+    pub trait Sealed {}
+
+    impl Sealed for str {}
+
+    impl Sealed for [u8] {}
+}
@@ -0,0 +1,163 @@
+//! Scanners built on `core::arch::aarch64` NEON intrinsics, so that stable
+//! compilers get a SIMD path on aarch64 too.
+//!
+//! NEON is part of the aarch64 baseline, so unlike x86_64, there's nothing to
+//! detect at runtime. This is only compiled when `neon` is enabled at compile
+//! time, which it is for every aarch64 target that ships with Rust.
+
+#[must_use]
+#[inline]
+pub fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    // SAFETY: This module is only compiled when NEON is enabled.
+    unsafe { neon::contains_null_or_utf8_4_byte_char_header(value) }
+}
+
+#[must_use]
+#[inline]
+pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    // SAFETY: This module is only compiled when NEON is enabled.
+    unsafe { neon::contains_utf8_4_byte_char_header(value) }
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_ascii(value: &[u8]) -> usize {
+    // SAFETY: This module is only compiled when NEON is enabled.
+    unsafe { neon::count_leading_ascii(value) }
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+    // SAFETY: This module is only compiled when NEON is enabled.
+    unsafe { neon::count_leading_non_null_ascii(value) }
+}
+
+/// Scanners that use 16-byte NEON vectors.
+pub mod neon {
+    use core::arch::aarch64::{vandq_u8, vceqq_u8, vdupq_n_u8, vld1q_u8, vmaxvq_u8, vorrq_u8};
+
+    use crate::implementation::fallback;
+
+    const LEN: usize = 16;
+
+    /// The bits of a byte that identify the header of a 4-byte UTF-8
+    /// character.
+    const MASK: u8 = 0b1111_1000;
+
+    /// The header of a 4-byte UTF-8 character, once it's masked with [`MASK`].
+    const HEADER: u8 = 0b1111_0000;
+
+    /// # Safety
+    ///
+    /// The CPU must support NEON, which every aarch64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "neon")]
+    pub unsafe fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let zero = vdupq_n_u8(0x00);
+        let mask = vdupq_n_u8(MASK);
+        let header = vdupq_n_u8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `vld1q_u8` doesn't
+            // need the pointer to be aligned.
+            let bytes = unsafe { vld1q_u8(chunk.as_ptr()) };
+            let is_null = vceqq_u8(bytes, zero);
+            let is_header = vceqq_u8(vandq_u8(bytes, mask), header);
+
+            // NOTE: Each lane of a comparison is either `0x00` or `0xff`, so
+            // the maximum across the lanes is only nonzero if any lane matched.
+            if vmaxvq_u8(vorrq_u8(is_null, is_header)) != 0 {
+                return true;
+            }
+        }
+
+        fallback::contains_null_or_utf8_4_byte_char_header(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support NEON, which every aarch64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "neon")]
+    pub unsafe fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let mask = vdupq_n_u8(MASK);
+        let header = vdupq_n_u8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `vld1q_u8` doesn't
+            // need the pointer to be aligned.
+            let bytes = unsafe { vld1q_u8(chunk.as_ptr()) };
+            let is_header = vceqq_u8(vandq_u8(bytes, mask), header);
+
+            if vmaxvq_u8(is_header) != 0 {
+                return true;
+            }
+        }
+
+        fallback::contains_utf8_4_byte_char_header(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support NEON, which every aarch64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "neon")]
+    pub unsafe fn count_leading_ascii(value: &[u8]) -> usize {
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `vld1q_u8` doesn't
+            // need the pointer to be aligned.
+            let bytes = unsafe { vld1q_u8(chunk.as_ptr()) };
+
+            // NOTE: NEON has no equivalent of `movemask`, so once a chunk is
+            // known to contain a non-ASCII byte, the fallback finds it. This
+            // also keeps the answer independent of the byte order.
+            if vmaxvq_u8(bytes) >= 0x80 {
+                return count + fallback::count_leading_ascii(chunk);
+            }
+
+            count += LEN;
+        }
+
+        count + fallback::count_leading_ascii(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support NEON, which every aarch64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "neon")]
+    pub unsafe fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+        let zero = vdupq_n_u8(0x00);
+
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `vld1q_u8` doesn't
+            // need the pointer to be aligned.
+            let bytes = unsafe { vld1q_u8(chunk.as_ptr()) };
+
+            // NOTE: A null byte compares equal to zero as `0xff`, so both
+            // kinds of byte that stop the count end up with the high bit set.
+            if vmaxvq_u8(vorrq_u8(bytes, vceqq_u8(bytes, zero))) >= 0x80 {
+                return count + fallback::count_leading_non_null_ascii(chunk);
+            }
+
+            count += LEN;
+        }
+
+        count + fallback::count_leading_non_null_ascii(chunks.remainder())
+    }
+}
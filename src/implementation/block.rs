@@ -0,0 +1,100 @@
+//! Block-at-a-time primitives for building custom processing loops.
+//!
+//! These expose the same decision the whole-buffer functions make, which is
+//! whether a run of UTF-8 can be copied as-is, one fixed-size block at a time.
+//! Like the rest of [`implementation`](super), this module is semver exempt.
+
+use super::active;
+pub use crate::internal::Flavor;
+
+/// The number of bytes in a block passed to [`process_block`].
+pub const BLOCK_SIZE: usize = 64;
+
+/// What [`process_block`] found in a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockResult {
+    /// No byte in the block needs to be transformed, so it can be copied in
+    /// bulk.
+    TransformFree,
+    /// The byte at `index` is the first one in the block that needs to be
+    /// transformed.
+    Transform {
+        /// The index of the first byte that needs to be transformed.
+        index: usize,
+    },
+}
+
+/// Checks whether a block of UTF-8 can be copied as-is in the given flavor.
+///
+/// A byte needs to be transformed if it's the header of a 4-byte UTF-8
+/// character, or, in MUTF-8, if it's null. This is the same check that
+/// [`encode`](crate::encode) and [`mutf8::encode`](crate::mutf8::encode) use
+/// to decide whether their input can be borrowed.
+///
+/// **NOTE:** This function doesn't validate the block, and a block can start
+/// or end in the middle of a character. When decoding, a transform-free block
+/// still has to be validated as UTF-8, and the characters that straddle block
+/// boundaries are left for the caller to handle.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::implementation::block::{process_block, BlockResult, Flavor, BLOCK_SIZE};
+///
+/// let mut block = [b'a'; BLOCK_SIZE];
+/// assert_eq!(
+///     process_block(&block, Flavor::Cesu8),
+///     BlockResult::TransformFree
+/// );
+/// assert_eq!(
+///     process_block(&block, Flavor::Mutf8),
+///     BlockResult::TransformFree
+/// );
+///
+/// block[10] = 0x00;
+/// block[20..24].copy_from_slice("💖".as_bytes());
+/// assert_eq!(
+///     process_block(&block, Flavor::Cesu8),
+///     BlockResult::Transform { index: 20 }
+/// );
+/// assert_eq!(
+///     process_block(&block, Flavor::Mutf8),
+///     BlockResult::Transform { index: 10 }
+/// );
+/// ```
+#[must_use]
+#[inline]
+pub fn process_block(input: &[u8; BLOCK_SIZE], flavor: Flavor) -> BlockResult {
+    // PERF: Almost every block is transform-free, so we answer that with the
+    // active implementation first, and only look for the exact index when we
+    // know there is one.
+    let index = match flavor {
+        Flavor::Cesu8 => {
+            if !active::contains_utf8_4_byte_char_header(input) {
+                return BlockResult::TransformFree;
+            }
+
+            input.iter().position(|&byte| is_4_byte_char_header(byte))
+        }
+        Flavor::Mutf8 => {
+            if !active::contains_null_or_utf8_4_byte_char_header(input) {
+                return BlockResult::TransformFree;
+            }
+
+            input
+                .iter()
+                .position(|&byte| byte == 0x00 || is_4_byte_char_header(byte))
+        }
+    };
+
+    match index {
+        Some(index) => BlockResult::Transform { index },
+        None => BlockResult::TransformFree,
+    }
+}
+
+#[must_use]
+#[inline]
+fn is_4_byte_char_header(byte: u8) -> bool {
+    byte & 0b1111_1000 == 0b1111_0000
+}
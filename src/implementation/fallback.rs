@@ -20,3 +20,27 @@ pub fn contains_utf8_4_byte_char_header(bytes: &[u8]) -> bool {
 
     false
 }
+
+#[must_use]
+#[inline]
+pub fn count_leading_ascii(bytes: &[u8]) -> usize {
+    for (index, &byte) in bytes.iter().enumerate() {
+        if byte & 0b1000_0000 != 0 {
+            return index;
+        }
+    }
+
+    bytes.len()
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(bytes: &[u8]) -> usize {
+    for (index, &byte) in bytes.iter().enumerate() {
+        if byte == 0x00 || byte & 0b1000_0000 != 0 {
+            return index;
+        }
+    }
+
+    bytes.len()
+}
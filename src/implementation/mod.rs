@@ -3,13 +3,64 @@
 //!
 //! THIS MODULE IS NOT PART OF THE PUBLIC API AND IS SEMVER EXEMPT.
 
+#[cfg(all(
+    target_arch = "aarch64",
+    target_feature = "neon",
+    any(feature = "bench", not(feature = "nightly"))
+))]
+pub mod aarch64;
+pub mod block;
 pub mod fallback;
 #[cfg(feature = "nightly")]
 pub mod simd;
+#[cfg(all(
+    target_arch = "wasm32",
+    target_feature = "simd128",
+    any(feature = "bench", not(feature = "nightly"))
+))]
+pub mod wasm32;
 #[cfg(any(feature = "bench", not(feature = "nightly")))]
 pub mod word;
+#[cfg(all(
+    feature = "std",
+    target_arch = "x86_64",
+    any(feature = "bench", not(feature = "nightly"))
+))]
+pub mod x86;
 
-#[cfg(feature = "nightly")]
+// NOTE: On stable, the `core::arch` or `std::arch` implementation is used when
+// there's one for the target, and the word-at-a-time implementation is used
+// otherwise. The `force-fallback` feature overrides all of them.
+#[cfg(all(
+    not(feature = "force-fallback"),
+    not(feature = "nightly"),
+    target_arch = "aarch64",
+    target_feature = "neon"
+))]
+pub use self::aarch64 as active;
+#[cfg(feature = "force-fallback")]
+pub use self::fallback as active;
+#[cfg(all(not(feature = "force-fallback"), feature = "nightly"))]
 pub use self::simd as active;
-#[cfg(not(feature = "nightly"))]
+#[cfg(all(
+    not(feature = "force-fallback"),
+    not(feature = "nightly"),
+    target_arch = "wasm32",
+    target_feature = "simd128"
+))]
+pub use self::wasm32 as active;
+#[cfg(all(
+    not(feature = "force-fallback"),
+    not(feature = "nightly"),
+    not(all(feature = "std", target_arch = "x86_64")),
+    not(all(target_arch = "aarch64", target_feature = "neon")),
+    not(all(target_arch = "wasm32", target_feature = "simd128"))
+))]
 pub use self::word as active;
+#[cfg(all(
+    not(feature = "force-fallback"),
+    not(feature = "nightly"),
+    feature = "std",
+    target_arch = "x86_64"
+))]
+pub use self::x86 as active;
@@ -9,15 +9,15 @@ pub fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
 
     macro_rules! process {
         ($simd:ty) => {
-            let array_chunks = remainder.array_chunks::<{ <$simd>::LEN }>();
-            remainder = array_chunks.remainder();
+            let chunks = remainder.chunks_exact(<$simd>::LEN);
+            remainder = chunks.remainder();
 
             let zero = <$simd>::splat(0x00);
             let mask = <$simd>::splat(0b1111_1000);
             let header = <$simd>::splat(0b1111_0000);
 
-            for &array in array_chunks {
-                let simd = <$simd>::from_array(array);
+            for chunk in chunks {
+                let simd = <$simd>::from_slice(chunk);
 
                 if simd.simd_eq(zero).any() || (simd & mask).simd_eq(header).any() {
                     return true;
@@ -43,14 +43,14 @@ pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
 
     macro_rules! process {
         ($simd:ty) => {
-            let array_chunks = remainder.array_chunks::<{ <$simd>::LEN }>();
-            remainder = array_chunks.remainder();
+            let chunks = remainder.chunks_exact(<$simd>::LEN);
+            remainder = chunks.remainder();
 
             let mask = <$simd>::splat(0b1111_1000);
             let header = <$simd>::splat(0b1111_0000);
 
-            for &array in array_chunks {
-                let simd = <$simd>::from_array(array);
+            for chunk in chunks {
+                let simd = <$simd>::from_slice(chunk);
 
                 if (simd & mask).simd_eq(header).any() {
                     return true;
@@ -68,3 +68,70 @@ pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
 
     fallback::contains_utf8_4_byte_char_header(remainder)
 }
+
+#[must_use]
+#[inline]
+pub fn count_leading_ascii(value: &[u8]) -> usize {
+    let mut count = 0;
+
+    // NOTE: When a chunk contains a non-ASCII byte, we stop and let the next,
+    // narrower lane width retry from the same position. This narrows down the
+    // position of the byte until the fallback can find it exactly.
+    macro_rules! process {
+        ($simd:ty) => {
+            let non_ascii = <$simd>::splat(0x80);
+
+            while let Some(chunk) = value.get(count..count + <$simd>::LEN) {
+                let simd = <$simd>::from_slice(chunk);
+
+                if simd.simd_ge(non_ascii).any() {
+                    break;
+                }
+
+                count += <$simd>::LEN;
+            }
+        };
+    }
+
+    process!(u8x64);
+    process!(u8x32);
+    process!(u8x16);
+    process!(u8x8);
+    process!(u8x4);
+    process!(u8x2);
+
+    count + fallback::count_leading_ascii(&value[count..])
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+    let mut count = 0;
+
+    // NOTE: See `count_leading_ascii` for how the lane widths cooperate.
+    macro_rules! process {
+        ($simd:ty) => {
+            let zero = <$simd>::splat(0x00);
+            let non_ascii = <$simd>::splat(0x80);
+
+            while let Some(chunk) = value.get(count..count + <$simd>::LEN) {
+                let simd = <$simd>::from_slice(chunk);
+
+                if simd.simd_eq(zero).any() || simd.simd_ge(non_ascii).any() {
+                    break;
+                }
+
+                count += <$simd>::LEN;
+            }
+        };
+    }
+
+    process!(u8x64);
+    process!(u8x32);
+    process!(u8x16);
+    process!(u8x8);
+    process!(u8x4);
+    process!(u8x2);
+
+    count + fallback::count_leading_non_null_ascii(&value[count..])
+}
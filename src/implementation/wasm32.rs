@@ -0,0 +1,127 @@
+//! Scanners built on `core::arch::wasm32` SIMD128 intrinsics, so that stable
+//! compilers get a SIMD path on WebAssembly too.
+//!
+//! Unlike x86_64, WebAssembly has no runtime feature detection: a module that
+//! uses SIMD128 instructions fails to validate on an engine without them. So
+//! this is only compiled when `simd128` is enabled at compile time, e.g. with
+//! `-C target-feature=+simd128`, and every scanner can call the intrinsics
+//! directly.
+
+// NOTE: Every load is unaligned, so casting to the more-strictly-aligned vector
+// pointer is fine.
+#![allow(clippy::cast_ptr_alignment)]
+
+use core::arch::wasm32::{
+    u8x16_bitmask,
+    u8x16_eq,
+    u8x16_splat,
+    v128,
+    v128_and,
+    v128_any_true,
+    v128_load,
+    v128_or,
+};
+
+use super::fallback;
+
+const LEN: usize = 16;
+
+/// The bits of a byte that identify the header of a 4-byte UTF-8 character.
+const MASK: u8 = 0b1111_1000;
+
+/// The header of a 4-byte UTF-8 character, once it's masked with [`MASK`].
+const HEADER: u8 = 0b1111_0000;
+
+#[must_use]
+#[inline]
+pub fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    let zero = u8x16_splat(0x00);
+    let mask = u8x16_splat(MASK);
+    let header = u8x16_splat(HEADER);
+
+    let mut chunks = value.chunks_exact(LEN);
+
+    for chunk in &mut chunks {
+        // SAFETY: `chunk` is exactly `LEN` bytes, and `v128_load` doesn't need
+        // the pointer to be aligned.
+        let bytes = unsafe { v128_load(chunk.as_ptr().cast::<v128>()) };
+        let is_null = u8x16_eq(bytes, zero);
+        let is_header = u8x16_eq(v128_and(bytes, mask), header);
+
+        if v128_any_true(v128_or(is_null, is_header)) {
+            return true;
+        }
+    }
+
+    fallback::contains_null_or_utf8_4_byte_char_header(chunks.remainder())
+}
+
+#[must_use]
+#[inline]
+pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    let mask = u8x16_splat(MASK);
+    let header = u8x16_splat(HEADER);
+
+    let mut chunks = value.chunks_exact(LEN);
+
+    for chunk in &mut chunks {
+        // SAFETY: `chunk` is exactly `LEN` bytes, and `v128_load` doesn't need
+        // the pointer to be aligned.
+        let bytes = unsafe { v128_load(chunk.as_ptr().cast::<v128>()) };
+        let is_header = u8x16_eq(v128_and(bytes, mask), header);
+
+        if v128_any_true(is_header) {
+            return true;
+        }
+    }
+
+    fallback::contains_utf8_4_byte_char_header(chunks.remainder())
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_ascii(value: &[u8]) -> usize {
+    let mut chunks = value.chunks_exact(LEN);
+    let mut count = 0;
+
+    for chunk in &mut chunks {
+        // SAFETY: `chunk` is exactly `LEN` bytes, and `v128_load` doesn't need
+        // the pointer to be aligned.
+        let bytes = unsafe { v128_load(chunk.as_ptr().cast::<v128>()) };
+        // NOTE: The high bit of each byte is only set for non-ASCII bytes, so
+        // the mask of high bits is all we need.
+        let non_ascii = u8x16_bitmask(bytes);
+
+        if non_ascii != 0 {
+            return count + non_ascii.trailing_zeros() as usize;
+        }
+
+        count += LEN;
+    }
+
+    count + fallback::count_leading_ascii(chunks.remainder())
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+    let zero = u8x16_splat(0x00);
+
+    let mut chunks = value.chunks_exact(LEN);
+    let mut count = 0;
+
+    for chunk in &mut chunks {
+        // SAFETY: `chunk` is exactly `LEN` bytes, and `v128_load` doesn't need
+        // the pointer to be aligned.
+        let bytes = unsafe { v128_load(chunk.as_ptr().cast::<v128>()) };
+        let stop = u8x16_bitmask(v128_or(bytes, u8x16_eq(bytes, zero)));
+
+        if stop != 0 {
+            return count + stop.trailing_zeros() as usize;
+        }
+
+        count += LEN;
+    }
+
+    count + fallback::count_leading_non_null_ascii(chunks.remainder())
+}
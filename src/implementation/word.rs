@@ -12,9 +12,15 @@ pub fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
     )
 }
 
+/// Returns `true` if any byte of `word` is null or the header of a 4-byte UTF-8
+/// character.
+///
+/// Like every `word_*` function in this module, this only tests each byte of
+/// the word independently, so the answer doesn't depend on the byte order of
+/// the target.
 #[must_use]
 #[inline]
-fn word_contains_null_or_utf8_4_byte_char_header(word: usize) -> bool {
+pub fn word_contains_null_or_utf8_4_byte_char_header(word: usize) -> bool {
     word_contains_null_byte(word) || word_contains_utf8_4_byte_char_header(word)
 }
 
@@ -28,9 +34,11 @@ pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
     )
 }
 
+/// Returns `true` if any byte of `word` is the header of a 4-byte UTF-8
+/// character, i.e. matches `0b1111_0xxx`.
 #[must_use]
 #[inline]
-fn word_contains_utf8_4_byte_char_header(word: usize) -> bool {
+pub fn word_contains_utf8_4_byte_char_header(word: usize) -> bool {
     const MASK: usize = usize_repeat_u8(0b1111_1000);
     const HEADER: usize = usize_repeat_u8(0b1111_0000);
 
@@ -39,16 +47,58 @@ fn word_contains_utf8_4_byte_char_header(word: usize) -> bool {
 
 #[must_use]
 #[inline]
-fn word_contains_null_byte(word: usize) -> bool {
+pub fn count_leading_ascii(value: &[u8]) -> usize {
+    count_leading_words(value, fallback::count_leading_ascii, word_is_ascii)
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+    count_leading_words(
+        value,
+        fallback::count_leading_non_null_ascii,
+        word_is_non_null_ascii,
+    )
+}
+
+/// Returns `true` if every byte of `word` is ASCII.
+#[must_use]
+#[inline]
+pub fn word_is_ascii(word: usize) -> bool {
+    const HIGH_MASK: usize = usize_repeat_u8(0x80);
+
+    word & HIGH_MASK == 0
+}
+
+/// Returns `true` if every byte of `word` is ASCII, and none of them are null.
+#[must_use]
+#[inline]
+pub fn word_is_non_null_ascii(word: usize) -> bool {
+    word_is_ascii(word) && !word_contains_null_byte(word)
+}
+
+/// Returns `true` if any byte of `word` is null.
+///
+/// NOTE: The borrow from subtracting `0x01` out of a null byte can set the high
+/// bit of the byte *above* it, so this can't tell you *which* byte was null.
+/// Whether any byte was null is exact in either byte order, though, because
+/// the lowest null byte is always flagged correctly.
+#[must_use]
+#[inline]
+pub fn word_contains_null_byte(word: usize) -> bool {
     const LOW_MASK: usize = usize_repeat_u8(0x01);
     const HIGH_MASK: usize = usize_repeat_u8(0x80);
 
     (word.wrapping_sub(LOW_MASK) & !word & HIGH_MASK) != 0
 }
 
+/// Returns a `usize` where every byte is `byte`.
+///
+/// Because every byte is the same, the result is identical whether the bytes
+/// are read as big-endian or little-endian.
 #[must_use]
 #[inline]
-const fn usize_repeat_u8(byte: u8) -> usize {
+pub const fn usize_repeat_u8(byte: u8) -> usize {
     usize::from_ne_bytes([byte; USIZE_SIZE])
 }
 
@@ -87,13 +137,17 @@ where
         return fallback(value);
     }
 
+    // NOTE: The first word is always read unaligned, so when the slice is
+    // already aligned, the aligned words start right after it. Either way, the
+    // aligned words start within the first word, which we know is in bounds.
     let offset_to_aligned = if align_offset == 0 {
         USIZE_SIZE
     } else {
         align_offset
     };
+    debug_assert!(offset_to_aligned <= USIZE_SIZE && offset_to_aligned <= len);
 
-    // SAFETY: We have already checked that `len` is less than `USIZE_SIZE`
+    // SAFETY: We have already checked that `len` is at least `USIZE_SIZE`
     // above.
     let first_word = unsafe { start_ptr.cast::<usize>().read_unaligned() };
 
@@ -139,4 +193,44 @@ where
     test(last_word)
 }
 
+/// This counts the number of leading bytes of the slice that satisfy a test,
+/// using usize-at-a-time operations for the aligned middle of the slice.
+///
+/// `fallback` is a function that returns the number of leading bytes that pass
+/// the test, one byte at a time. It is used for the unaligned head and tail of
+/// the slice, and to find the exact byte within a word that failed `test`.
+///
+/// `test` is a function that will be called with each aligned usize read from
+/// the slice. It must return `true` only if every byte in the word passes.
+#[must_use]
+#[inline]
+fn count_leading_words<F, T>(value: &[u8], fallback: F, test: T) -> usize
+where
+    F: Fn(&[u8]) -> usize,
+    T: Fn(usize) -> bool,
+{
+    // SAFETY: Every bit pattern is a valid `usize`, so reinterpreting the
+    // aligned middle of the slice is sound.
+    let (head, words, _) = unsafe { value.align_to::<usize>() };
+
+    let mut count = fallback(head);
+
+    if count < head.len() {
+        return count;
+    }
+
+    for &word in words {
+        if !test(word) {
+            break;
+        }
+
+        count += USIZE_SIZE;
+    }
+
+    // SAFETY: `count` never exceeds `head.len() + words.len() * USIZE_SIZE`,
+    // which is at most `value.len()`.
+    let rest = unsafe { value.get_unchecked(count..) };
+    count + fallback(rest)
+}
+
 const USIZE_SIZE: usize = mem::size_of::<usize>();
@@ -0,0 +1,357 @@
+//! Scanners built on `core::arch::x86_64` intrinsics, so that stable
+//! compilers get a SIMD path too.
+//!
+//! The widest instruction set the CPU supports is detected the first time a
+//! scanner is called, and cached for every call after that.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[must_use]
+#[inline]
+pub fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    match level() {
+        // SAFETY: `level` only returns `AVX2` when the CPU supports AVX2.
+        AVX2 => unsafe { avx2::contains_null_or_utf8_4_byte_char_header(value) },
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        _ => unsafe { sse2::contains_null_or_utf8_4_byte_char_header(value) },
+    }
+}
+
+#[must_use]
+#[inline]
+pub fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+    match level() {
+        // SAFETY: `level` only returns `AVX2` when the CPU supports AVX2.
+        AVX2 => unsafe { avx2::contains_utf8_4_byte_char_header(value) },
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        _ => unsafe { sse2::contains_utf8_4_byte_char_header(value) },
+    }
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_ascii(value: &[u8]) -> usize {
+    match level() {
+        // SAFETY: `level` only returns `AVX2` when the CPU supports AVX2.
+        AVX2 => unsafe { avx2::count_leading_ascii(value) },
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        _ => unsafe { sse2::count_leading_ascii(value) },
+    }
+}
+
+#[must_use]
+#[inline]
+pub fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+    match level() {
+        // SAFETY: `level` only returns `AVX2` when the CPU supports AVX2.
+        AVX2 => unsafe { avx2::count_leading_non_null_ascii(value) },
+        // SAFETY: SSE2 is part of the x86_64 baseline.
+        _ => unsafe { sse2::count_leading_non_null_ascii(value) },
+    }
+}
+
+/// Returns the widest instruction set the CPU supports, detecting it if this
+/// is the first call.
+#[must_use]
+#[inline]
+fn level() -> u8 {
+    let level = LEVEL.load(Ordering::Relaxed);
+
+    if level != UNDETECTED {
+        return level;
+    }
+
+    let level = if std::is_x86_feature_detected!("avx2") {
+        AVX2
+    } else {
+        SSE2
+    };
+
+    // NOTE: Several threads may race to store the level, but they all store
+    // the same value, so it doesn't matter which one wins.
+    LEVEL.store(level, Ordering::Relaxed);
+    level
+}
+
+/// The widest instruction set the CPU supports, or [`UNDETECTED`].
+static LEVEL: AtomicU8 = AtomicU8::new(UNDETECTED);
+
+const UNDETECTED: u8 = 0;
+const SSE2: u8 = 1;
+const AVX2: u8 = 2;
+
+/// The bits of a byte that identify the header of a 4-byte UTF-8 character.
+// NOTE: The intrinsics take signed bytes, but only the bit pattern matters.
+#[allow(clippy::cast_possible_wrap)]
+const MASK: i8 = 0b1111_1000_u8 as i8;
+
+/// The header of a 4-byte UTF-8 character, once it's masked with [`MASK`].
+#[allow(clippy::cast_possible_wrap)]
+const HEADER: i8 = 0b1111_0000_u8 as i8;
+
+/// Scanners that use 16-byte SSE2 vectors.
+pub mod sse2 {
+    // NOTE: Every load is unaligned, so casting to the more-strictly-aligned
+    // vector pointer is fine.
+    #![allow(clippy::cast_ptr_alignment)]
+
+    use core::arch::x86_64::{
+        __m128i,
+        _mm_and_si128,
+        _mm_cmpeq_epi8,
+        _mm_loadu_si128,
+        _mm_movemask_epi8,
+        _mm_or_si128,
+        _mm_set1_epi8,
+        _mm_setzero_si128,
+    };
+
+    use super::{HEADER, MASK};
+    use crate::implementation::fallback;
+
+    const LEN: usize = 16;
+
+    /// # Safety
+    ///
+    /// The CPU must support SSE2, which every x86_64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let zero = _mm_setzero_si128();
+        let mask = _mm_set1_epi8(MASK);
+        let header = _mm_set1_epi8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm_loadu_si128`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>()) };
+            let is_null = _mm_cmpeq_epi8(bytes, zero);
+            let is_header = _mm_cmpeq_epi8(_mm_and_si128(bytes, mask), header);
+
+            if _mm_movemask_epi8(_mm_or_si128(is_null, is_header)) != 0 {
+                return true;
+            }
+        }
+
+        fallback::contains_null_or_utf8_4_byte_char_header(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support SSE2, which every x86_64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let mask = _mm_set1_epi8(MASK);
+        let header = _mm_set1_epi8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm_loadu_si128`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>()) };
+            let is_header = _mm_cmpeq_epi8(_mm_and_si128(bytes, mask), header);
+
+            if _mm_movemask_epi8(is_header) != 0 {
+                return true;
+            }
+        }
+
+        fallback::contains_utf8_4_byte_char_header(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support SSE2, which every x86_64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn count_leading_ascii(value: &[u8]) -> usize {
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm_loadu_si128`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>()) };
+            // NOTE: The high bit of each byte is only set for non-ASCII bytes,
+            // so the mask of high bits is all we need.
+            let non_ascii = _mm_movemask_epi8(bytes);
+
+            if non_ascii != 0 {
+                return count + non_ascii.trailing_zeros() as usize;
+            }
+
+            count += LEN;
+        }
+
+        count + fallback::count_leading_ascii(chunks.remainder())
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support SSE2, which every x86_64 CPU does.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+        let zero = _mm_setzero_si128();
+
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm_loadu_si128`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>()) };
+            let stop = _mm_movemask_epi8(_mm_or_si128(bytes, _mm_cmpeq_epi8(bytes, zero)));
+
+            if stop != 0 {
+                return count + stop.trailing_zeros() as usize;
+            }
+
+            count += LEN;
+        }
+
+        count + fallback::count_leading_non_null_ascii(chunks.remainder())
+    }
+}
+
+/// Scanners that use 32-byte AVX2 vectors.
+pub mod avx2 {
+    // NOTE: Every load is unaligned, so casting to the more-strictly-aligned
+    // vector pointer is fine.
+    #![allow(clippy::cast_ptr_alignment)]
+
+    use core::arch::x86_64::{
+        __m256i,
+        _mm256_and_si256,
+        _mm256_cmpeq_epi8,
+        _mm256_loadu_si256,
+        _mm256_movemask_epi8,
+        _mm256_or_si256,
+        _mm256_set1_epi8,
+        _mm256_setzero_si256,
+    };
+
+    use super::{sse2, HEADER, MASK};
+
+    const LEN: usize = 32;
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn contains_null_or_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let zero = _mm256_setzero_si256();
+        let mask = _mm256_set1_epi8(MASK);
+        let header = _mm256_set1_epi8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm256_loadu_si256`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>()) };
+            let is_null = _mm256_cmpeq_epi8(bytes, zero);
+            let is_header = _mm256_cmpeq_epi8(_mm256_and_si256(bytes, mask), header);
+
+            if _mm256_movemask_epi8(_mm256_or_si256(is_null, is_header)) != 0 {
+                return true;
+            }
+        }
+
+        // SAFETY: Every CPU that supports AVX2 supports SSE2.
+        unsafe { sse2::contains_null_or_utf8_4_byte_char_header(chunks.remainder()) }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn contains_utf8_4_byte_char_header(value: &[u8]) -> bool {
+        let mask = _mm256_set1_epi8(MASK);
+        let header = _mm256_set1_epi8(HEADER);
+
+        let mut chunks = value.chunks_exact(LEN);
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm256_loadu_si256`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>()) };
+            let is_header = _mm256_cmpeq_epi8(_mm256_and_si256(bytes, mask), header);
+
+            if _mm256_movemask_epi8(is_header) != 0 {
+                return true;
+            }
+        }
+
+        // SAFETY: Every CPU that supports AVX2 supports SSE2.
+        unsafe { sse2::contains_utf8_4_byte_char_header(chunks.remainder()) }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn count_leading_ascii(value: &[u8]) -> usize {
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm256_loadu_si256`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>()) };
+            let non_ascii = _mm256_movemask_epi8(bytes);
+
+            if non_ascii != 0 {
+                return count + non_ascii.trailing_zeros() as usize;
+            }
+
+            count += LEN;
+        }
+
+        // SAFETY: Every CPU that supports AVX2 supports SSE2.
+        count + unsafe { sse2::count_leading_ascii(chunks.remainder()) }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[must_use]
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn count_leading_non_null_ascii(value: &[u8]) -> usize {
+        let zero = _mm256_setzero_si256();
+
+        let mut chunks = value.chunks_exact(LEN);
+        let mut count = 0;
+
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly `LEN` bytes, and `_mm256_loadu_si256`
+            // doesn't need the pointer to be aligned.
+            let bytes = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast::<__m256i>()) };
+            let stop = _mm256_movemask_epi8(_mm256_or_si256(bytes, _mm256_cmpeq_epi8(bytes, zero)));
+
+            if stop != 0 {
+                return count + stop.trailing_zeros() as usize;
+            }
+
+            count += LEN;
+        }
+
+        // SAFETY: Every CPU that supports AVX2 supports SSE2.
+        count + unsafe { sse2::count_leading_non_null_ascii(chunks.remainder()) }
+    }
+}
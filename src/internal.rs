@@ -1,20 +1,580 @@
+#[cfg(feature = "alloc")]
 use alloc::string::String;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
 use core::hint;
 
 use simdutf8::basic::from_utf8;
 
-use crate::error::DecodingError;
+use crate::error::{BufferTooSmall, DecodeToSliceError, DecodingError, DecodingErrorKind};
+#[cfg(feature = "alloc")]
+use crate::error::{DecodingErrorAt, EncodeError, TryDecodeError};
+use crate::implementation::active::{
+    contains_null_or_utf8_4_byte_char_header,
+    contains_utf8_4_byte_char_header,
+    count_leading_ascii,
+    count_leading_non_null_ascii,
+};
+#[cfg(feature = "alloc")]
+use crate::metrics;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Flavor {
+/// The flavor of CESU-8 to work with.
+///
+/// The functions at the root of this crate work with CESU-8, and the ones in
+/// [`mutf8`](mod@crate::mutf8) work with MUTF-8. When the flavor is only known
+/// at runtime, pass one of these to [`encode_with`](crate::encode_with) or
+/// [`decode_with`](crate::decode_with) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flavor {
+    /// CESU-8, where only 4-byte characters differ from UTF-8.
     Cesu8,
+    /// MUTF-8, which is CESU-8 where null is also encoded as `0xc0 0x80`.
     Mutf8,
 }
 
+/// Every knob of the decoder, for [`decode_opts`](crate::decode_opts).
+///
+/// The presets, like [`decode`](crate::decode) and
+/// [`decode_strict`](crate::decode_strict), each fix these to one combination.
+/// Build one of these instead to pick them independently, starting from
+/// [`DecodeConfig::new`] for strict decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeConfig {
+    /// The flavor of CESU-8 to decode.
+    pub flavor: Flavor,
+    /// Whether 4-byte UTF-8 characters are accepted as-is, next to the
+    /// surrogate pairs that encode the same characters in CESU-8.
+    pub allow_utf8_4_byte: bool,
+    /// Whether invalid sequences are replaced with U+FFFD instead of returning
+    /// an error.
+    pub lossy: bool,
+    /// Whether a byte order mark at the start of the input is skipped, instead
+    /// of being decoded as U+FEFF. See [`has_bom`](crate::has_bom).
+    pub strip_bom: bool,
+}
+
+impl DecodeConfig {
+    /// Returns the config for strictly decoding the given flavor, i.e. the one
+    /// that matches [`decode_strict`](crate::decode_strict) or
+    /// [`mutf8::decode_strict`](crate::mutf8::decode_strict).
+    #[must_use]
+    #[inline]
+    pub const fn new(flavor: Flavor) -> Self {
+        Self {
+            flavor,
+            allow_utf8_4_byte: false,
+            lossy: false,
+            strip_bom: false,
+        }
+    }
+}
+
+/// A growable byte buffer that the decoder can write UTF-8 into.
+///
+/// This exists so that the decoding state machine can be shared between
+/// buffers that use the global allocator and buffers that use a custom one.
+pub(crate) trait Buffer {
+    fn push(&mut self, byte: u8);
+
+    fn extend_from_slice(&mut self, slice: &[u8]);
+}
+
+#[cfg(all(feature = "alloc", not(feature = "allocator_api")))]
+impl Buffer for Vec<u8> {
+    #[inline]
+    fn push(&mut self, byte: u8) {
+        Vec::push(self, byte);
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, slice: &[u8]) {
+        Vec::extend_from_slice(self, slice);
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator> Buffer for Vec<u8, A> {
+    #[inline]
+    fn push(&mut self, byte: u8) {
+        Vec::push(self, byte);
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, slice: &[u8]) {
+        Vec::extend_from_slice(self, slice);
+    }
+}
+
+/// A buffer that throws away everything pushed to it. Decoding into it only
+/// validates the input.
+pub(crate) struct Discard;
+
+impl Buffer for Discard {
+    #[inline]
+    fn push(&mut self, _byte: u8) {}
+
+    #[inline]
+    fn extend_from_slice(&mut self, _slice: &[u8]) {}
+}
+
+/// A buffer of UTF-16 code units that the decoder's UTF-8 is converted into as
+/// it's written.
+///
+/// NOTE: [`decode_to`] only ever pushes whole characters, so every slice is
+/// valid UTF-8 on its own.
+#[cfg(feature = "alloc")]
+impl Buffer for Vec<u16> {
+    #[inline]
+    fn push(&mut self, byte: u8) {
+        debug_assert!(byte.is_ascii());
+        Vec::push(self, u16::from(byte));
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, slice: &[u8]) {
+        debug_assert!(from_utf8(slice).is_ok());
+        // SAFETY: Every slice holds whole characters, as explained above.
+        let value = unsafe { core::str::from_utf8_unchecked(slice) };
+        self.extend(value.encode_utf16());
+    }
+}
+
+/// A buffer that collects decoded UTF-8 on the stack and passes it to a sink
+/// in runs, so decoding into a sink doesn't allocate.
+///
+/// NOTE: [`decode_to`] only ever pushes whole characters, so every run that
+/// reaches the sink is valid UTF-8 on its own.
+pub(crate) struct SinkBuffer<F: FnMut(&str)> {
+    buffer: [u8; SINK_BUFFER_SIZE],
+    len: usize,
+    sink: F,
+}
+
+impl<F: FnMut(&str)> SinkBuffer<F> {
+    #[inline]
+    pub(crate) fn new(sink: F) -> Self {
+        Self {
+            buffer: [0; SINK_BUFFER_SIZE],
+            len: 0,
+            sink,
+        }
+    }
+
+    /// Passes everything collected so far to the sink.
+    #[inline]
+    pub(crate) fn flush(&mut self) {
+        if self.len != 0 {
+            // SAFETY: `len` is never greater than `SINK_BUFFER_SIZE`.
+            let run = unsafe { self.buffer.get_unchecked(..self.len) };
+            emit(&mut self.sink, run);
+            self.len = 0;
+        }
+    }
+}
+
+impl<F: FnMut(&str)> Buffer for SinkBuffer<F> {
+    #[inline]
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, slice: &[u8]) {
+        if slice.len() > SINK_BUFFER_SIZE - self.len {
+            self.flush();
+
+            if slice.len() > SINK_BUFFER_SIZE {
+                // PERF: Runs that don't fit, like a long ASCII prefix, are
+                // passed to the sink directly instead of being copied.
+                emit(&mut self.sink, slice);
+                return;
+            }
+        }
+
+        // SAFETY: We made sure there's room for `slice` above.
+        let target = unsafe {
+            self.buffer
+                .get_unchecked_mut(self.len..self.len + slice.len())
+        };
+        target.copy_from_slice(slice);
+        self.len += slice.len();
+    }
+}
+
+#[inline]
+fn emit<F: FnMut(&str)>(sink: &mut F, run: &[u8]) {
+    debug_assert!(from_utf8(run).is_ok());
+    // SAFETY: `decode_to` only pushes whole characters, so every run is valid
+    // UTF-8.
+    sink(unsafe { core::str::from_utf8_unchecked(run) });
+}
+
+/// A buffer that writes into a slice owned by the caller, so decoding into it
+/// never allocates.
+///
+/// NOTE: Once a write doesn't fit, it and every write after it are dropped, so
+/// the slice only ever holds whole characters.
+struct SliceBuffer<'a> {
+    slice: &'a mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'a> SliceBuffer<'a> {
+    #[inline]
+    fn new(slice: &'a mut [u8]) -> Self {
+        Self {
+            slice,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Returns the bytes written so far, or `None` if any write didn't fit.
+    #[inline]
+    fn into_written(self) -> Option<&'a mut [u8]> {
+        if self.overflowed {
+            None
+        } else {
+            self.slice.get_mut(..self.len)
+        }
+    }
+}
+
+impl Buffer for SliceBuffer<'_> {
+    #[inline]
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, slice: &[u8]) {
+        if self.overflowed {
+            return;
+        }
+
+        match self.slice.get_mut(self.len..self.len + slice.len()) {
+            Some(target) => {
+                target.copy_from_slice(slice);
+                self.len += slice.len();
+            }
+            None => self.overflowed = true,
+        }
+    }
+}
+
+/// Returns the error for `bytes`, which must not be strictly valid in the given
+/// flavor.
+///
+/// NOTE: Working out why the input is invalid means decoding it again, so it's
+/// only done when `detailed-errors` is enabled. Otherwise, this is free.
+#[cold]
+pub(crate) fn invalid(bytes: &[u8], flavor: Flavor) -> DecodingError {
+    if cfg!(feature = "detailed-errors") {
+        if let Err(error) = decode_to_with(bytes, flavor, &mut Discard, |_, _| false) {
+            return error;
+        }
+    }
+
+    DecodingError::new(DecodingErrorKind::InvalidByte)
+}
+
+/// Returns `true` if `bytes` is strictly valid in the given flavor, i.e. if
+/// the matching `decode_strict` would succeed.
+#[must_use]
+#[inline]
+pub(crate) fn validate(bytes: &[u8], flavor: Flavor) -> bool {
+    first_invalid_index(bytes, flavor).is_none()
+}
+
+/// Returns the closest index at or before `index` where `bytes` can be split
+/// without cutting a character, or a surrogate pair, in two. An `index` past
+/// the end is clamped to `bytes.len()`.
+///
+/// If `bytes` is valid, this backs up over the continuation bytes of the
+/// character that `index` falls in, and then over the high surrogate if that
+/// character is a low surrogate. If `bytes` is invalid, the split can be
+/// anywhere, since decoding fails either way.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+pub(crate) fn floor_char_boundary(bytes: &[u8], index: usize) -> usize {
+    if index >= bytes.len() {
+        return bytes.len();
+    }
+
+    let mut index = index;
+
+    // NOTE: Every character in CESU-8 and MUTF-8 is at most 3 bytes long, not
+    // counting surrogate pairs, so there are at most 2 continuation bytes. We
+    // back up over a third anyway, so that 4-byte UTF-8 isn't cut either.
+    for _ in 0..3 {
+        if index == 0 || bytes[index] & 0b1100_0000 != 0b1000_0000 {
+            break;
+        }
+
+        index -= 1;
+    }
+
+    let is_low_surrogate = matches!(bytes[index..], [0xed, 0xb0..=0xbf, ..]);
+
+    if is_low_surrogate && index >= 3 && matches!(bytes[index - 3..index], [0xed, 0xa0..=0xaf, _]) {
+        index -= 3;
+    }
+
+    index
+}
+
+/// Returns the offset of the first invalid sequence in `bytes` in the given
+/// flavor, or `None` if [`validate`] would return `true`.
+#[must_use]
+#[inline]
+pub(crate) fn first_invalid_index(bytes: &[u8], flavor: Flavor) -> Option<usize> {
+    let needs_decoded = match flavor {
+        Flavor::Cesu8 => contains_utf8_4_byte_char_header(bytes),
+        Flavor::Mutf8 => contains_null_or_utf8_4_byte_char_header(bytes),
+    };
+
+    if !needs_decoded && from_utf8(bytes).is_ok() {
+        return None;
+    }
+
+    let mut index = None;
+
+    // NOTE: `recover` is always called before an error is returned, so the
+    // result itself carries nothing that `index` doesn't.
+    let _ = decode_to_with(bytes, flavor, &mut Discard, |_, offset| {
+        index = Some(offset);
+        false
+    });
+
+    index
+}
+
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn decode(bytes: &[u8], options: DecodeOptions) -> Result<String, DecodingError> {
-    let capacity = if options.lossy {
+    decode_with(bytes, options, |_, _| options.lossy)
+}
+
+/// Decodes `bytes` like [`decode`], but asks `recover` whether to replace each
+/// invalid sequence with U+FFFD or to return an error. `options.lossy` should
+/// be `true` if `recover` might ever return `true`, so that enough capacity is
+/// reserved up front.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_with<R: FnMut(DecodingErrorKind, usize) -> bool>(
+    bytes: &[u8],
+    options: DecodeOptions,
+    recover: R,
+) -> Result<String, DecodingError> {
+    decode_counted_with(bytes, options, recover).map(|(decoded, _)| decoded)
+}
+
+/// Decodes `bytes` like [`decode_with`], but also returns the number of U+FFFD
+/// that were emitted.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_counted_with<R: FnMut(DecodingErrorKind, usize) -> bool>(
+    bytes: &[u8],
+    options: DecodeOptions,
+    recover: R,
+) -> Result<(String, usize), DecodingError> {
+    let mut decoded = Vec::<u8>::with_capacity(decode_capacity(bytes, options));
+    let replacements = decode_to_with(bytes, options.flavor, &mut decoded, recover)?;
+    metrics::record_replacements(replacements);
+
+    // NOTE: We do a sanity check that the decoded string is valid UTF-8. We
+    // have to do this because `String::from_utf8_unchecked` doesn't have a
+    // sanity check in debug mode.
+    debug_assert!(from_utf8(&decoded).is_ok());
+    // SAFETY: We know that `decoded` is a valid UTF-8 string because we only
+    // ever push valid UTF-8 bytes to it.
+    let decoded = unsafe { String::from_utf8_unchecked(decoded) };
+    Ok((decoded, replacements))
+}
+
+/// Decodes `bytes` with every knob of `config`. Unlike [`decode`], this can
+/// accept 4-byte UTF-8 characters anywhere in the input, not only when the
+/// whole input is valid UTF-8.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_config(bytes: &[u8], config: DecodeConfig) -> Result<String, DecodingError> {
+    let options = DecodeOptions {
+        flavor: config.flavor,
+        lossy: config.lossy,
+    };
+    let mut decoded = Vec::<u8>::with_capacity(decode_capacity(bytes, options));
+    let replacements = decode_utf8_to_with(
+        bytes,
+        config.flavor,
+        config.allow_utf8_4_byte,
+        &mut decoded,
+        |_, _| config.lossy,
+    )?;
+    metrics::record_replacements(replacements);
+
+    debug_assert!(from_utf8(&decoded).is_ok());
+    // SAFETY: We know that `decoded` is a valid UTF-8 string because we only
+    // ever push valid UTF-8 bytes to it.
+    Ok(unsafe { String::from_utf8_unchecked(decoded) })
+}
+
+/// Decodes `bytes` strictly like [`decode`], but on error, reports where the
+/// invalid sequence begins and why it's invalid.
+///
+/// `valid_up_to` is the length of the prefix of `bytes` that is already known
+/// to be valid UTF-8, which `simdutf8::compat` reports for free. When that
+/// prefix is also valid in `flavor`, it's copied as-is, and the state machine
+/// only starts where the UTF-8 validation stopped.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_verbose(
+    bytes: &[u8],
+    flavor: Flavor,
+    valid_up_to: usize,
+) -> Result<String, DecodingErrorAt> {
+    let prefix = &bytes[..valid_up_to];
+    let needs_decoded = match flavor {
+        Flavor::Cesu8 => contains_utf8_4_byte_char_header(prefix),
+        Flavor::Mutf8 => contains_null_or_utf8_4_byte_char_header(prefix),
+    };
+    // NOTE: UTF-8 stops being valid at the start of a character, and a
+    // surrogate pair is never valid UTF-8, so the state machine can't tell
+    // that it started partway through the input.
+    let start = if needs_decoded { 0 } else { valid_up_to };
+
+    let mut decoded = Vec::<u8>::with_capacity(bytes.len());
+    decoded.extend_from_slice(&bytes[..start]);
+
+    let mut error = None;
+    let result = decode_to_with(&bytes[start..], flavor, &mut decoded, |kind, offset| {
+        error = Some((kind, start + offset));
+        false
+    });
+
+    if result.is_err() {
+        let Some((kind, offset)) = error else {
+            unreachable!("`recover` is always called before an error is returned");
+        };
+
+        // NOTE: Errors are only ever reported while there are bytes left to
+        // process, so `offset` is always in bounds.
+        return Err(DecodingErrorAt::new(offset, bytes[offset], kind));
+    }
+
+    debug_assert!(from_utf8(&decoded).is_ok());
+    // SAFETY: We know that `decoded` is a valid UTF-8 string because the
+    // prefix was validated as UTF-8, and the rest only ever pushes valid UTF-8
+    // bytes to it.
+    Ok(unsafe { String::from_utf8_unchecked(decoded) })
+}
+
+/// Decodes `bytes`, appending the result to `decoded`. On success, this returns
+/// the number of replacement characters that were emitted, just like
+/// [`decode_to`].
+///
+/// If an error is returned, `decoded` may contain everything before the
+/// invalid sequence, which is still valid UTF-8.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_into(
+    bytes: &[u8],
+    options: DecodeOptions,
+    decoded: &mut String,
+) -> Result<usize, DecodingError> {
+    // SAFETY: `decode_to` only ever pushes whole characters, so the string is
+    // valid UTF-8 even if it returns an error partway through.
+    let vec = unsafe { decoded.as_mut_vec() };
+    vec.reserve(decode_capacity(bytes, options));
+    decode_to(bytes, options, vec)
+}
+
+/// Decodes `bytes` strictly in place, returning the decoded prefix of `bytes`.
+///
+/// The input is validated first, so on error, `bytes` is left untouched.
+#[inline]
+pub(crate) fn decode_in_place(bytes: &mut [u8], flavor: Flavor) -> Result<&mut str, DecodingError> {
+    let options = DecodeOptions {
+        flavor,
+        lossy: false,
+    };
+    decode_to(bytes, options, &mut Discard)?;
+
+    // NOTE: Every sequence decodes to at most as many bytes as it takes up, so
+    // `write` never gets ahead of `read`, and we never overwrite a byte before
+    // reading it.
+    let mut read = 0;
+    let mut write = 0;
+
+    while read < bytes.len() {
+        let first = bytes[read];
+
+        let (width, decoded_width) = match first {
+            0xc0 if flavor == Flavor::Mutf8 => {
+                bytes[write] = 0x00;
+                (2, 1)
+            }
+            0xed if bytes[read + 1] >= 0xa0 => {
+                let c = decode_surrogate_pair(
+                    bytes[read + 1],
+                    bytes[read + 2],
+                    bytes[read + 4],
+                    bytes[read + 5],
+                );
+                bytes[write..write + 4].copy_from_slice(&c);
+                (6, 4)
+            }
+            _ => {
+                // NOTE: The input is valid, so this is a 1-, 2-, or 3-byte
+                // character that decodes to itself.
+                let width = match first {
+                    0x00..=0x7f => 1,
+                    0xc0..=0xdf => 2,
+                    _ => 3,
+                };
+
+                if read != write {
+                    bytes.copy_within(read..read + width, write);
+                }
+
+                (width, width)
+            }
+        };
+
+        read += width;
+        write += decoded_width;
+    }
+
+    let decoded = &mut bytes[..write];
+    debug_assert!(from_utf8(decoded).is_ok());
+    // SAFETY: We only ever write whole UTF-8 characters to the prefix.
+    Ok(unsafe { core::str::from_utf8_unchecked_mut(decoded) })
+}
+
+/// Decodes `bytes` into a vector that uses the given allocator. The returned
+/// vector is always valid UTF-8.
+#[cfg(feature = "allocator_api")]
+#[inline]
+pub(crate) fn decode_in<A: Allocator>(
+    bytes: &[u8],
+    options: DecodeOptions,
+    alloc: A,
+) -> Result<Vec<u8, A>, DecodingError> {
+    let mut decoded = Vec::with_capacity_in(decode_capacity(bytes, options), alloc);
+    decode_to(bytes, options, &mut decoded)?;
+
+    debug_assert!(from_utf8(&decoded).is_ok());
+    Ok(decoded)
+}
+
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+fn decode_capacity(bytes: &[u8], options: DecodeOptions) -> usize {
+    if options.lossy {
         // NOTE: This is the worst-case scenario where *every* byte is invalid,
         // and we have to replace it with the "U+FFFD REPLACEMENT CHARACTER".
         bytes.len().checked_mul(3).unwrap_or(ISIZE_MAX_USIZE)
@@ -23,38 +583,244 @@ pub(crate) fn decode(bytes: &[u8], options: DecodeOptions) -> Result<String, Dec
         // their UTF-8 counterparts, so we can use the length of the input
         // bytes as a speculative capacity.
         bytes.len()
+    }
+}
+
+/// Decodes `bytes` as CESU-8 or MUTF-8, appending the UTF-8 result to
+/// `decoded`. On success, this returns the number of replacement characters
+/// that were emitted, which is always zero unless `options.lossy` is `true`.
+///
+/// If an error is returned, `decoded` may contain the UTF-8 of everything
+/// before the invalid sequence.
+#[inline]
+pub(crate) fn decode_to<B: Buffer>(
+    bytes: &[u8],
+    options: DecodeOptions,
+    decoded: &mut B,
+) -> Result<usize, DecodingError> {
+    decode_to_with(bytes, options.flavor, decoded, |_, _| options.lossy)
+}
+
+/// Decodes `bytes` using the given flavor of encoding to UTF-16 code units.
+///
+/// Just like [`decode_to`] with `lossy` set to `false`, valid UTF-8 that isn't
+/// valid in the given flavor is rejected.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_to_utf16(bytes: &[u8], flavor: Flavor) -> Result<Vec<u16>, DecodingError> {
+    // NOTE: Every code unit comes from at least one byte.
+    let mut decoded = Vec::with_capacity(bytes.len());
+    decode_to_with(bytes, flavor, &mut decoded, |_, _| false)?;
+    Ok(decoded)
+}
+
+/// Decodes `bytes` using the given flavor of encoding to UTF-16 code units,
+/// keeping each lone surrogate as its own code unit instead of rejecting it.
+///
+/// NOTE: This reuses the state machine as-is: whenever it stops at a
+/// well-formed 3-byte surrogate, that surrogate can't have been part of a
+/// pair, so it's pushed on its own and decoding starts again right after it.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn decode_to_utf16_lossless(
+    bytes: &[u8],
+    flavor: Flavor,
+) -> Result<Vec<u16>, DecodingError> {
+    // NOTE: Every code unit comes from at least one byte.
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+
+    loop {
+        let mut start = 0;
+        let Err(error) = decode_to_with(rest, flavor, &mut decoded, |_, processed| {
+            start = processed;
+            false
+        }) else {
+            return Ok(decoded);
+        };
+
+        // SAFETY: The state machine only ever fails at an index within `rest`.
+        let invalid = unsafe { rest.get_unchecked(start..) };
+
+        let &[0xed, second @ 0xa0..=0xbf, third @ 0x80..=0xbf, ..] = invalid else {
+            return Err(error);
+        };
+
+        // NOTE: A surrogate always fits in a code unit.
+        #[allow(clippy::cast_possible_truncation)]
+        decoded.push(decode_surrogate(second, third) as u16);
+        // SAFETY: The surrogate is 3 bytes long, and all of them are in `rest`.
+        rest = unsafe { invalid.get_unchecked(3..) };
+    }
+}
+
+/// Decodes `bytes` using the given flavor of encoding into `out`, and returns
+/// the decoded string, which borrows the start of `out`.
+///
+/// Just like [`decode_to`] with `lossy` set to `false`, valid UTF-8 that isn't
+/// valid in the given flavor is rejected. If the input is invalid, that error
+/// is returned even if the output wouldn't fit either.
+#[inline]
+pub(crate) fn decode_to_slice<'a>(
+    bytes: &[u8],
+    flavor: Flavor,
+    out: &'a mut [u8],
+) -> Result<&'a mut str, DecodeToSliceError> {
+    let mut buffer = SliceBuffer::new(out);
+    decode_to_with(bytes, flavor, &mut buffer, |_, _| false)?;
+    let decoded = buffer.into_written().ok_or(BufferTooSmall(()))?;
+
+    debug_assert!(from_utf8(decoded).is_ok());
+    // SAFETY: `decode_to` only ever pushes valid UTF-8.
+    Ok(unsafe { core::str::from_utf8_unchecked_mut(decoded) })
+}
+
+/// Decodes the first character of `bytes`, which must not be empty, using the
+/// given flavor of encoding, and returns it along with the number of bytes it
+/// took up.
+///
+/// Just like [`decode_to`] with `lossy` set to `false`, valid UTF-8 that isn't
+/// valid in the given flavor is rejected.
+#[inline]
+pub(crate) fn decode_char(bytes: &[u8], flavor: Flavor) -> Result<(char, usize), DecodingError> {
+    debug_assert!(!bytes.is_empty());
+
+    // NOTE: This is only how long the sequence would be if it were valid. The
+    // state machine below decides whether it actually is.
+    let width = match *bytes {
+        [0xed, 0xa0..=0xaf, ..] => 6,
+        [0xc0..=0xdf, ..] => 2,
+        [0xe0..=0xef, ..] => 3,
+        _ => 1,
+    };
+    let sequence = bytes.get(..width).unwrap_or(bytes);
+
+    let mut decoded = [0; 4];
+    let mut buffer = SliceBuffer::new(&mut decoded);
+    decode_to_with(sequence, flavor, &mut buffer, |_, _| false)?;
+    let decoded = buffer.into_written().unwrap_or_default();
+
+    debug_assert!(from_utf8(decoded).is_ok());
+    // SAFETY: `decode_to` only ever pushes valid UTF-8.
+    let decoded = unsafe { core::str::from_utf8_unchecked(decoded) };
+    debug_assert_eq!(decoded.chars().count(), 1);
+
+    // NOTE: A valid sequence always decodes to exactly one character, so this
+    // only fails if `bytes` is empty.
+    let first = decoded.chars().next();
+    first
+        .map(|char| (char, sequence.len()))
+        .ok_or(DecodingError::new(DecodingErrorKind::UnexpectedEof))
+}
+
+/// Encodes `char` using the given flavor of encoding into `buffer`, and
+/// returns the part of `buffer` that was written.
+#[inline]
+pub(crate) fn encode_char(char: char, flavor: Flavor, buffer: &mut [u8; 6]) -> &mut [u8] {
+    let len = match char.len_utf8() {
+        1 if char == '\0' && flavor == Flavor::Mutf8 => {
+            buffer[..2].copy_from_slice(&[0xc0, 0x80]);
+            2
+        }
+        4 => {
+            let [high, low] = to_surrogate_pair(u32::from(char));
+            buffer[..3].copy_from_slice(&encode_surrogate(high));
+            buffer[3..].copy_from_slice(&encode_surrogate(low));
+            6
+        }
+        _ => char.encode_utf8(buffer).len(),
     };
 
-    let mut decoded = Vec::<u8>::with_capacity(capacity);
+    &mut buffer[..len]
+}
+
+/// Like [`decode`] with `lossy` set to `false`, but returns an error instead of
+/// aborting when the output can't be allocated.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn try_decode(bytes: &[u8], flavor: Flavor) -> Result<String, TryDecodeError> {
+    let mut decoded = Vec::new();
+    // NOTE: Strictly decoding never makes the output longer than the input, so
+    // this is the only allocation.
+    decoded
+        .try_reserve_exact(bytes.len())
+        .map_err(|_| TryDecodeError::Allocation)?;
+    decode_to_with(bytes, flavor, &mut decoded, |_, _| false)?;
+
+    debug_assert!(from_utf8(&decoded).is_ok());
+    // SAFETY: `decode_to` only ever pushes valid UTF-8.
+    Ok(unsafe { String::from_utf8_unchecked(decoded) })
+}
+
+/// The state machine behind [`decode_to`]. Whenever an invalid sequence is
+/// found, `recover` is called with the kind of error and the offset in `bytes`
+/// where the invalid sequence starts. If it returns `true`, a U+FFFD is
+/// emitted and decoding resumes at the next byte, otherwise the error is
+/// returned.
+///
+/// NOTE: Working out the kind of error is cheap, and it's only done on the
+/// error path. When `recover` ignores its arguments, LLVM removes them
+/// entirely.
+#[inline]
+pub(crate) fn decode_to_with<B: Buffer, R: FnMut(DecodingErrorKind, usize) -> bool>(
+    bytes: &[u8],
+    flavor: Flavor,
+    decoded: &mut B,
+    recover: R,
+) -> Result<usize, DecodingError> {
+    decode_utf8_to_with(bytes, flavor, false, decoded, recover)
+}
+
+/// Decodes `bytes` like [`decode_to_with`], but if `allow_utf8_4_byte` is
+/// `true`, valid 4-byte UTF-8 characters are copied as-is instead of being
+/// rejected.
+///
+/// NOTE: This is one long `match` on purpose, so that the macros can `continue`
+/// the loop and LLVM sees the whole state machine at once.
+#[allow(clippy::too_many_lines)]
+#[inline]
+fn decode_utf8_to_with<B: Buffer, R: FnMut(DecodingErrorKind, usize) -> bool>(
+    bytes: &[u8],
+    flavor: Flavor,
+    allow_utf8_4_byte: bool,
+    decoded: &mut B,
+    mut recover: R,
+) -> Result<usize, DecodingError> {
     let mut index = 0;
-    let mut processed = 0;
+    let mut processed;
+    let mut replacements = 0;
+    // NOTE: Everything before this has already been found to need decoding
+    // byte-at-a-time, so it isn't scanned again.
+    #[cfg(feature = "memchr")]
+    let mut plain_until = 0;
 
     macro_rules! err {
-        () => {{
-            if options.lossy {
+        ($kind:expr) => {{
+            if recover($kind, processed) {
                 // NOTE: This is the "U+FFFD REPLACEMENT CHARACTER" in UTF-8.
                 // Because CESU-8 and MUTF-8 only differ in how they encode
                 // 4-byte characters, and null, this UTF-8 is valid as-is in
                 // both encodings.
                 decoded.extend_from_slice(&[0xef, 0xbf, 0xbd]);
-                // SAFETY: We know that `processed` will only ever be less than
-                // or equal to `bytes.len()`, so this is safe. We increment
-                // `processed` here to ensure that we don't get stuck in an
-                // infinite loop.
-                processed = unsafe { processed.unchecked_add(1) };
+                replacements += 1;
+                // SAFETY: `invalid_len` never reaches past the end of `bytes`,
+                // so `processed` will only ever be less than or equal to
+                // `bytes.len()`, and it's always at least 1, so we never get
+                // stuck in an infinite loop.
+                processed = unsafe { processed.unchecked_add(invalid_len(bytes, processed)) };
                 // NOTE: We unwind `index` to the new start.
                 index = processed;
                 continue;
             }
 
-            return Err(DecodingError(()));
+            return Err(DecodingError::new($kind));
         }};
     }
 
     macro_rules! next {
         () => {{
             if index >= bytes.len() {
-                err!();
+                err!(DecodingErrorKind::UnexpectedEof);
             }
 
             // SAFETY: We know that `index` is less than `bytes.len()`.
@@ -72,101 +838,343 @@ pub(crate) fn decode(bytes: &[u8], options: DecodeOptions) -> Result<String, Dec
             let byte = next!();
 
             if byte & 0b1100_0000 != 0b1000_0000 {
-                err!();
+                err!(DecodingErrorKind::ExpectedContinuation);
             }
 
             byte
         }};
     }
 
-    while processed < bytes.len() {
-        // NOTE: `processed` should be equal to `index` at the start of each
-        // iteration.
-        debug_assert!(index == processed);
-        // SAFETY: We know that `index` is less than `bytes.len()` due to the
-        // loop condition.
-        let first = unsafe { *bytes.get_unchecked(processed) };
-        // SAFETY: We know that `index` is less than `bytes.len()`, so at most,
-        // `index + 1` will be equal to `isize::MAX + 1`, which will never
-        // overflow a `usize`.
-        index = unsafe { index.unchecked_add(1) };
-
-        match first {
-            0x00 if options.flavor == Flavor::Mutf8 => err!(),
-            0x00..=0x7f => {
-                decoded.push(first);
-            }
-            0xc0 if options.flavor == Flavor::Mutf8 => {
-                if next!() != 0x80 {
-                    err!();
-                }
+    // PERF: Real-world input tends to be made of runs of ASCII, which decode to
+    // themselves, such as the start of the input, or the text between the
+    // nulls of MUTF-8. We find the end of each run with the active
+    // implementation and copy it in bulk, and only decode what's between the
+    // runs byte-at-a-time.
+    //
+    // NOTE: The inner loop only goes back to the outer loop when the next word
+    // is ASCII, so short runs don't pay for a scan that finds next to nothing.
+    // Keeping the call to the active implementation out of the inner loop
+    // matters too: it measured ~35% faster on input that alternates between
+    // ASCII and nulls.
+    'runs: loop {
+        processed = copy_leading_ascii(bytes, index, flavor, decoded);
+        index = processed;
 
-                decoded.push(0x00);
-            }
-            0xc2..=0xdf => {
-                let second = next_continue!();
-                decoded.extend_from_slice(&[first, second]);
+        while processed < bytes.len() {
+            // NOTE: `processed` should be equal to `index` at the start of each
+            // iteration.
+            debug_assert!(index == processed);
+            // SAFETY: We know that `index` is less than `bytes.len()` due to the
+            // loop condition.
+            let first = unsafe { *bytes.get_unchecked(processed) };
+            // SAFETY: We know that `index` is less than `bytes.len()`, so at most,
+            // `index + 1` will be equal to `isize::MAX + 1`, which will never
+            // overflow a `usize`.
+            index = unsafe { index.unchecked_add(1) };
+
+            // PERF: Outside of surrogate pairs, and nulls in MUTF-8, valid input
+            // is UTF-8 as-is. So instead of decoding 2- and 3-byte characters
+            // one at a time, we jump to the next byte that could start either,
+            // and copy everything before it in bulk if it's valid.
+            #[cfg(feature = "memchr")]
+            if first >= 0x80 && first != 0xed && first != 0xc0 && processed >= plain_until {
+                // SAFETY: We know that `processed` is less than `bytes.len()`.
+                let rest = unsafe { bytes.get_unchecked(processed..) };
+                let (len, is_plain) = plain_run(rest, flavor, allow_utf8_4_byte);
+                // NOTE: When the runs are short, such as in text that mixes
+                // scripts every few characters, scanning costs more than it
+                // saves, so we back off for a while.
+                plain_until =
+                    processed + len.max(PLAIN_RUN_BACKOFF * usize::from(len < MIN_PLAIN_RUN));
+
+                if is_plain && len != 0 {
+                    // SAFETY: `len` is at most `rest.len()`.
+                    decoded.extend_from_slice(unsafe { rest.get_unchecked(..len) });
+                    processed += len;
+                    index = processed;
+                    continue;
+                }
             }
-            0xe0..=0xef => {
-                let second = next!();
-
-                match (first, second) {
-                    (0xe0, 0xa0..=0xbf)
-                    | (0xe1..=0xec | 0xee..=0xef, 0x80..=0xbf)
-                    | (0xed, 0x80..=0x9f) => {
-                        let third = next_continue!();
-                        decoded.extend_from_slice(&[first, second, third]);
+
+            match first {
+                0x00 if flavor == Flavor::Mutf8 => err!(DecodingErrorKind::Utf8NotCesu8),
+                0x00..=0x7f => {
+                    decoded.push(first);
+
+                    if starts_with_ascii_word(bytes, index, flavor) {
+                        continue 'runs;
+                    }
+                }
+                0xc0 if flavor == Flavor::Mutf8 => {
+                    if next!() != 0x80 {
+                        err!(DecodingErrorKind::OverlongEncoding);
                     }
-                    (0xed, 0xa0..=0xaf) => {
-                        if index + 4 > bytes.len() {
-                            err!();
+
+                    decoded.push(0x00);
+                }
+                0xc2..=0xdf => {
+                    let second = next_continue!();
+                    decoded.extend_from_slice(&[first, second]);
+                }
+                0xe0..=0xef => {
+                    let second = next!();
+
+                    // NOTE: After `0xe0`, anything below `0xa0` would be an
+                    // overlong encoding of a character that fits in 2 bytes,
+                    // such as `0xe0 0x80 0x80` for null, so it's left to the
+                    // error arm below.
+                    match (first, second) {
+                        (0xe0, 0xa0..=0xbf)
+                        | (0xe1..=0xec | 0xee..=0xef, 0x80..=0xbf)
+                        | (0xed, 0x80..=0x9f) => {
+                            let third = next_continue!();
+                            decoded.extend_from_slice(&[first, second, third]);
                         }
+                        (0xed, 0xa0..=0xaf) => {
+                            if index + 4 > bytes.len() {
+                                // SAFETY: We know that `index` is at most
+                                // `bytes.len()`.
+                                let rest = unsafe { bytes.get_unchecked(index..) };
+                                err!(surrogate_pair_error(rest));
+                            }
+
+                            // SAFETY: We know that `index + 4` is less than or
+                            // equal to `bytes.len()`, so this is safe.
+                            let slice = unsafe { bytes.get_unchecked(index..index + 4) };
+
+                            let &[third, fourth, fifth, sixth] = slice else {
+                                // SAFETY: We know that the slice is exactly four
+                                // bytes.
+                                unsafe { hint::unreachable_unchecked() };
+                            };
+
+                            // PERF: There was a lot of branching here before, so
+                            // this is some magic. Basically, we're checking if the
+                            // first byte is a continuation byte, the second byte is
+                            // equal to 0xed, the third byte checks if the value is
+                            // in the range 0xb0..=0xbf, and the fourth byte is a
+                            // continuation byte.
+                            let value = u32::from_be_bytes([third, fourth, fifth, sixth]);
+                            let validation_mask = 0b1100_0000_1111_1111_1111_0000_1100_0000u32;
+                            let desired = 0b1000_0000_1110_1101_1011_0000_1000_0000u32;
 
-                        // SAFETY: We know that `index + 4` is less than or
-                        // equal to `bytes.len()`, so this is safe.
-                        let slice = unsafe { bytes.get_unchecked(index..index + 4) };
-
-                        let &[third, fourth, fifth, sixth] = slice else {
-                            // SAFETY: We know that the slice is exactly four
-                            // bytes.
-                            unsafe { hint::unreachable_unchecked() };
-                        };
-
-                        // PERF: There was a lot of branching here before, so
-                        // this is some magic. Basically, we're checking if the
-                        // first byte is a continuation byte, the second byte is
-                        // equal to 0xed, the third byte checks if the value is
-                        // in the range 0xb0..=0xbf, and the fourth byte is a
-                        // continuation byte.
-                        let value = u32::from_be_bytes([third, fourth, fifth, sixth]);
-                        let validation_mask = 0b1100_0000_1111_1111_1111_0000_1100_0000u32;
-                        let desired = 0b1000_0000_1110_1101_1011_0000_1000_0000u32;
-
-                        if value & validation_mask != desired {
-                            err!();
+                            if value & validation_mask != desired {
+                                err!(surrogate_pair_error(slice));
+                            }
+
+                            index += 4;
+                            let c = decode_surrogate_pair(second, third, fifth, sixth);
+                            decoded.extend_from_slice(&c);
                         }
+                        _ => err!(three_byte_error(first, second)),
+                    }
+                }
+                0x80..=0xbf => err!(DecodingErrorKind::UnexpectedContinuation),
+                // NOTE: These can only start an overlong encoding of ASCII.
+                // The only exception is `0xc0 0x80` in MUTF-8, handled above.
+                0xc0..=0xc1 => err!(DecodingErrorKind::OverlongEncoding),
+                0xf0..=0xf4 if allow_utf8_4_byte => {
+                    let second = next!();
 
-                        index += 4;
-                        let c = decode_surrogate_pair(second, third, fifth, sixth);
-                        decoded.extend_from_slice(&c);
+                    // NOTE: After `0xf0`, anything below `0x90` would be an
+                    // overlong encoding, and after `0xf4`, anything above
+                    // `0x8f` would be above U+10FFFF.
+                    match (first, second) {
+                        (0xf0, 0x90..=0xbf) | (0xf1..=0xf3, 0x80..=0xbf) | (0xf4, 0x80..=0x8f) => {
+                            let third = next_continue!();
+                            let fourth = next_continue!();
+                            decoded.extend_from_slice(&[first, second, third, fourth]);
+                        }
+                        _ => err!(four_byte_error(first, second)),
                     }
-                    _ => err!(),
                 }
+                0xf0..=0xf4 => err!(DecodingErrorKind::Utf8NotCesu8),
+                _ => err!(DecodingErrorKind::InvalidByte),
             }
-            _ => err!(),
+
+            processed = index;
         }
 
-        processed = index;
+        break;
     }
 
-    // NOTE: We do a sanity check that the decoded string is valid UTF-8. We
-    // have to do this because `String::from_utf8_unchecked` doesn't have a
-    // sanity check in debug mode.
-    debug_assert!(from_utf8(&decoded).is_ok());
-    // SAFETY: We know that `decoded` is a valid UTF-8 string because we only
-    // ever push valid UTF-8 bytes to it.
-    let decoded = unsafe { String::from_utf8_unchecked(decoded) };
-    Ok(decoded)
+    Ok(replacements)
+}
+
+/// Copies the run of ASCII starting at `start` that is the same in UTF-8 and
+/// the given flavor to `buffer`, returning the index just past the end of the
+/// run.
+#[inline]
+fn copy_leading_ascii<B: Buffer>(
+    bytes: &[u8],
+    start: usize,
+    flavor: Flavor,
+    buffer: &mut B,
+) -> usize {
+    // SAFETY: `start` is at most `bytes.len()`.
+    let rest = unsafe { bytes.get_unchecked(start..) };
+    let ascii = match flavor {
+        Flavor::Cesu8 => count_leading_ascii(rest),
+        Flavor::Mutf8 => count_leading_non_null_ascii(rest),
+    };
+
+    // SAFETY: `ascii` is at most `rest.len()`.
+    buffer.extend_from_slice(unsafe { rest.get_unchecked(..ascii) });
+    start + ascii
+}
+
+/// Runs shorter than this aren't worth jumping over with `memchr`.
+#[cfg(feature = "memchr")]
+const MIN_PLAIN_RUN: usize = 32;
+
+/// The number of bytes decoded byte-at-a-time after a run shorter than
+/// [`MIN_PLAIN_RUN`], before `memchr` is tried again.
+#[cfg(feature = "memchr")]
+const PLAIN_RUN_BACKOFF: usize = 256;
+
+/// Returns the length of `rest` up to the next byte that could start a
+/// surrogate pair, or an encoded null in MUTF-8, and whether everything before
+/// it is the same in UTF-8 and the given flavor.
+///
+/// NOTE: `0xed` and `0xc0` are never continuation bytes, so if the run is
+/// valid, it always ends between two characters.
+#[cfg(feature = "memchr")]
+#[inline]
+fn plain_run(rest: &[u8], flavor: Flavor, allow_utf8_4_byte: bool) -> (usize, bool) {
+    let len = match flavor {
+        Flavor::Cesu8 => memchr::memchr(0xed, rest),
+        Flavor::Mutf8 => memchr::memchr2(0xed, 0xc0, rest),
+    }
+    .unwrap_or(rest.len());
+
+    // SAFETY: `len` is at most `rest.len()`.
+    let run = unsafe { rest.get_unchecked(..len) };
+    let is_plain = match flavor {
+        Flavor::Cesu8 => allow_utf8_4_byte || !contains_utf8_4_byte_char_header(run),
+        Flavor::Mutf8 => !contains_null_or_utf8_4_byte_char_header(run),
+    } && from_utf8(run).is_ok();
+
+    (len, is_plain)
+}
+
+/// Returns `true` if the eight bytes of `bytes` starting at `index` are all
+/// ASCII that is the same in UTF-8 and the given flavor.
+#[must_use]
+#[inline]
+fn starts_with_ascii_word(bytes: &[u8], index: usize, flavor: Flavor) -> bool {
+    const LOW: u64 = u64::from_ne_bytes([0x01; 8]);
+    const HIGH: u64 = u64::from_ne_bytes([0x80; 8]);
+
+    // NOTE: Checking the next byte first is cheaper, and it's enough to rule
+    // out input that alternates between ASCII and other characters, including
+    // nulls in MUTF-8.
+    match bytes.get(index) {
+        Some(0x00) if flavor == Flavor::Mutf8 => return false,
+        Some(&byte) if byte < 0x80 && bytes.len() - index >= 8 => {}
+        _ => return false,
+    }
+
+    // SAFETY: We just checked that `index` is less than `bytes.len()`.
+    let ptr = unsafe { bytes.as_ptr().add(index) };
+    // SAFETY: We just checked that there are at least eight bytes left, and
+    // `read_unaligned` doesn't need the pointer to be aligned.
+    let word = unsafe { ptr.cast::<u64>().read_unaligned() };
+    let is_ascii = word & HIGH == 0;
+
+    match flavor {
+        Flavor::Cesu8 => is_ascii,
+        // NOTE: This is the classic "has a zero byte" test. It can only give
+        // false positives for bytes above a zero byte, which doesn't matter,
+        // since any zero byte means the word isn't a match.
+        Flavor::Mutf8 => is_ascii && word.wrapping_sub(LOW) & !word & HIGH == 0,
+    }
+}
+
+/// Works out why the first two bytes of a 3-byte sequence are invalid, given
+/// that they aren't a valid character or the start of a surrogate pair.
+#[cold]
+fn three_byte_error(first: u8, second: u8) -> DecodingErrorKind {
+    match (first, second) {
+        (0xe0, 0x80..=0x9f) => DecodingErrorKind::OverlongEncoding,
+        (0xed, 0xb0..=0xbf) => DecodingErrorKind::InvalidSurrogatePair,
+        _ => DecodingErrorKind::ExpectedContinuation,
+    }
+}
+
+/// Works out why the first two bytes of a 4-byte UTF-8 sequence are invalid,
+/// given that they aren't the start of a valid character.
+#[cold]
+fn four_byte_error(first: u8, second: u8) -> DecodingErrorKind {
+    match (first, second) {
+        (0xf0, 0x80..=0x8f) => DecodingErrorKind::OverlongEncoding,
+        (0xf4, 0x90..=0xbf) => DecodingErrorKind::InvalidByte,
+        _ => DecodingErrorKind::ExpectedContinuation,
+    }
+}
+
+/// Returns how many bytes, starting at `start`, a single U+FFFD replaces when
+/// the sequence there is invalid.
+///
+/// NOTE: This follows the "maximal subpart" practice of the Unicode Standard,
+/// just like `String::from_utf8_lossy`: the longest prefix of a well-formed
+/// sequence is replaced as a whole, and any other byte on its own. The only
+/// difference is that a surrogate counts as a well-formed 3-byte sequence,
+/// since CESU-8 and MUTF-8 encode each half of a pair that way, so a lone
+/// surrogate is replaced by a single U+FFFD. Only continuation bytes are ever
+/// skipped, so the next sequence is never cut into.
+#[cold]
+fn invalid_len(bytes: &[u8], start: usize) -> usize {
+    let Some(&[first, ref rest @ ..]) = bytes.get(start..) else {
+        return 1;
+    };
+
+    let (len, second) = match first {
+        0xc2..=0xdf => (2, 0x80..=0xbf),
+        0xe0 => (3, 0xa0..=0xbf),
+        0xe1..=0xef => (3, 0x80..=0xbf),
+        0xf0 => (4, 0x90..=0xbf),
+        0xf1..=0xf3 => (4, 0x80..=0xbf),
+        0xf4 => (4, 0x80..=0x8f),
+        _ => return 1,
+    };
+
+    let continuations = rest
+        .iter()
+        .take(len - 1)
+        .enumerate()
+        .take_while(|&(index, byte)| {
+            if index == 0 {
+                second.contains(byte)
+            } else {
+                byte & 0b1100_0000 == 0b1000_0000
+            }
+        })
+        .count();
+
+    1 + continuations
+}
+
+/// Works out why the bytes following the first two bytes of a high surrogate
+/// aren't the rest of a surrogate pair. `rest` holds at most the four bytes
+/// that should complete the pair.
+#[cold]
+fn surrogate_pair_error(rest: &[u8]) -> DecodingErrorKind {
+    let expected: [fn(u8) -> bool; 4] = [
+        |byte| byte & 0b1100_0000 == 0b1000_0000,
+        |byte| byte == 0xed,
+        |byte| (0xb0..=0xbf).contains(&byte),
+        |byte| byte & 0b1100_0000 == 0b1000_0000,
+    ];
+
+    match rest
+        .iter()
+        .zip(expected)
+        .position(|(&byte, is_valid)| !is_valid(byte))
+    {
+        Some(0) => DecodingErrorKind::ExpectedContinuation,
+        Some(_) => DecodingErrorKind::InvalidSurrogatePair,
+        None => DecodingErrorKind::UnexpectedEof,
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -175,10 +1183,21 @@ pub(crate) struct DecodeOptions {
     pub(crate) lossy: bool,
 }
 
+/// Decodes a surrogate pair, given every byte of it that isn't a continuation
+/// of `0xed`.
+///
+/// NOTE: The callers have already checked that the first half is a high
+/// surrogate (`0xd800..=0xdbff`) and the second half is a low surrogate
+/// (`0xdc00..=0xdfff`). This is what makes the code point at least U+10000, so
+/// a pair can never encode a character that fits in 3 bytes, and swapped
+/// halves never get here.
 #[inline]
-fn decode_surrogate_pair(second: u8, third: u8, fifth: u8, sixth: u8) -> [u8; 4] {
+pub(crate) fn decode_surrogate_pair(second: u8, third: u8, fifth: u8, sixth: u8) -> [u8; 4] {
     let high = decode_surrogate(second, third);
     let low = decode_surrogate(fifth, sixth);
+    debug_assert!((0xd800..=0xdbff).contains(&high));
+    debug_assert!((0xdc00..=0xdfff).contains(&low));
+
     let code_point = 0x10000 + ((high - 0xd800) << 10 | (low - 0xdc00));
     decode_code_point(code_point)
 }
@@ -201,21 +1220,109 @@ fn decode_code_point(code_point: u32) -> [u8; 4] {
 /// Encodes a string into a vector of bytes using the given flavor of encoding:
 /// CESU-8 or MUTF-8.
 ///
-/// NOTE: This function is inlined. It is expected that the call site of this
-/// function is **not** inlined. This is to ensure that LLVM elides the flavor
-/// logic when the flavor is known at compile time.
-///
 /// # Panics
 ///
 /// If `value` is greater than <code>[isize::MAX] / 2</code> bytes long, this
 /// function might panic by trying to allocate a vector with a capacity greater
 /// than [`isize::MAX`] bytes.
+#[cfg(feature = "alloc")]
 #[must_use]
 #[inline]
 pub(crate) fn encode(value: &str, flavor: Flavor) -> Vec<u8> {
-    let capacity = value.len().checked_mul(2).unwrap_or(ISIZE_MAX_USIZE);
-    let mut encoded = Vec::with_capacity(capacity);
+    let mut encoded = Vec::new();
+    encode_to(value, flavor, &mut encoded);
+
+    // NOTE: The returned vector keeps its capacity for as long as it lives, so
+    // the slack of reserving twice the input length is given back whenever
+    // it's a sizable part of the buffer, such as for mostly-ASCII input with a
+    // single emoji.
+    //
+    // PERF: Short inputs almost always leave that much slack, and shrinking
+    // them measured as costly as encoding them in the first place, so only
+    // slack that's also large in absolute terms is given back. Counting the
+    // exact length up front instead was slower still for mostly-ASCII input.
+    let slack = encoded.capacity() - encoded.len();
+
+    if slack >= MIN_SHRINK_SLACK && slack > encoded.len() / MAX_SLACK_DIVISOR {
+        encoded.shrink_to_fit();
+    }
+
+    encoded
+}
 
+/// Encodes a string into a vector of bytes using the given flavor of encoding,
+/// counting the exact length of the output first so that the vector has no
+/// spare capacity.
+///
+/// # Panics
+///
+/// If the encoded output exceeds [`isize::MAX`] bytes, this function panics by
+/// trying to allocate a vector with a capacity greater than [`isize::MAX`]
+/// bytes.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+pub(crate) fn encode_exact(value: &str, flavor: Flavor) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(encoded_len(value.as_bytes(), flavor));
+    encode_to_reserved(value, flavor, &mut encoded);
+    encoded
+}
+
+/// Encodes a string using the given flavor of encoding, appending the bytes to
+/// `encoded`.
+///
+/// NOTE: This function is inlined. It is expected that the call site of this
+/// function is **not** inlined. This is to ensure that LLVM elides the flavor
+/// logic when the flavor is known at compile time.
+///
+/// # Panics
+///
+/// If `value` is greater than <code>[isize::MAX] / 2</code> bytes long, this
+/// function might panic by trying to reserve a capacity greater than
+/// [`isize::MAX`] bytes.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn encode_to(value: &str, flavor: Flavor, encoded: &mut Vec<u8>) {
+    // PERF: Speculatively reserving twice the input length means we never
+    // reallocate, and it measured ~20% faster than counting the exact length
+    // first on 16 KiB inputs. Starting at the input length and letting the
+    // vector grow was slower still whenever 4-byte characters were common.
+    // For large inputs, though, the slack can be megabytes, so we pay for the
+    // counting pass to keep peak memory down. The same goes for appending to
+    // a buffer that already holds data, since the caller keeps that slack for
+    // as long as they keep assembling values into it.
+    let capacity = if encoded.is_empty() && value.len() < EXACT_CAPACITY_THRESHOLD {
+        value.len().checked_mul(2).unwrap_or(ISIZE_MAX_USIZE)
+    } else {
+        encoded_len(value.as_bytes(), flavor)
+    };
+    encoded.reserve(capacity);
+    encode_to_reserved(value, flavor, encoded);
+}
+
+/// Like [`encode`], but returns an error instead of panicking when the output
+/// doesn't fit in memory.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn try_encode(value: &str, flavor: Flavor) -> Result<Vec<u8>, EncodeError> {
+    let mut encoded = Vec::new();
+    // NOTE: `try_reserve_exact` also fails if the length exceeds `isize::MAX`,
+    // which is the case that makes `encode` panic.
+    encoded
+        .try_reserve_exact(encoded_len(value.as_bytes(), flavor))
+        .map_err(|_| EncodeError(()))?;
+    encode_to_reserved(value, flavor, &mut encoded);
+    Ok(encoded)
+}
+
+/// Encodes a string using the given flavor of encoding, appending the bytes to
+/// `encoded`, which should already have room for them.
+///
+/// NOTE: If `encoded` doesn't have room, it grows like any other vector, so
+/// this is only ever slower, never unsound.
+#[cfg(feature = "alloc")]
+#[inline]
+fn encode_to_reserved(value: &str, flavor: Flavor, encoded: &mut Vec<u8>) {
     let bytes = value.as_bytes();
     let mut index = 0;
 
@@ -228,53 +1335,352 @@ pub(crate) fn encode(value: &str, flavor: Flavor) -> Vec<u8> {
         // this logic to the if statement you see here (without merging 2 and 3)
         // improved performance by over 80%.
         if first <= 0x7f {
+            index += 1;
+
             if flavor == Flavor::Mutf8 && first == 0x00 {
                 encoded.extend_from_slice(&[0xc0, 0x80]);
             } else {
                 encoded.push(first);
-            };
 
-            index += 1;
+                // PERF: Like when decoding, runs of ASCII are copied in bulk
+                // with the active implementation once the next word is all
+                // ASCII.
+                if starts_with_ascii_word(bytes, index, flavor) {
+                    index = copy_leading_ascii(bytes, index, flavor, encoded);
+                }
+            }
         } else if first <= 0xdf {
-            // SAFETY: We know that `bytes` is a valid UTF-8 string, so the
-            // slice is guaranteed to be valid.
-            let slice = unsafe { bytes.get_unchecked(index..index + 2) };
+            debug_assert!(first >= 0xc2 && is_utf8_sequence(bytes, index, 2));
+            // PERF: Text that is mostly 2-byte characters (Cyrillic, Greek,
+            // Hebrew, Arabic, ...) tends to come in long runs of 1- and 2-byte
+            // characters. Those are encoded the same in UTF-8 and CESU-8, so
+            // we find where the run ends and copy it in one go.
+            let end = index
+                + 2
+                + count_leading_1_or_2_byte(
+                    // SAFETY: We know that `bytes` is a valid UTF-8 string, so
+                    // the 2-byte character is in bounds.
+                    unsafe { bytes.get_unchecked(index + 2..) },
+                    flavor,
+                );
+            // SAFETY: `end` never exceeds `bytes.len()`.
+            let slice = unsafe { bytes.get_unchecked(index..end) };
+            // NOTE: The run only stops before a byte that isn't a continuation
+            // byte, so it never splits a character.
+            debug_assert!(from_utf8(slice).is_ok());
             encoded.extend_from_slice(slice);
-            index += 2;
+            index = end;
         } else if first <= 0xef {
+            debug_assert!(is_utf8_sequence(bytes, index, 3));
             // SAFETY: We know that `bytes` is a valid UTF-8 string, so the
             // slice is guaranteed to be valid.
             let slice = unsafe { bytes.get_unchecked(index..index + 3) };
             encoded.extend_from_slice(slice);
             index += 3;
         } else {
+            debug_assert!(first <= 0xf4 && is_utf8_sequence(bytes, index, 4));
             // SAFETY: We know that `bytes` is a valid UTF-8 string, so the
             // slice is guaranteed to be valid.
             let slice = unsafe { bytes.get_unchecked(index..index + 4) };
 
-            let &[first, second, third, fourth] = slice else {
-                // SAFETY: We know that the slice is exactly four bytes.
-                unsafe { hint::unreachable_unchecked() };
-            };
+            encoded.extend_from_slice(&encode_4_byte_char(slice));
+            index += 4;
+        }
+    }
+}
 
-            let code_point = (u32::from(first & 0b0000_0111) << 18)
-                | (u32::from(second & 0b0011_1111) << 12)
-                | (u32::from(third & 0b0011_1111) << 6)
-                | u32::from(fourth & 0b0011_1111);
+/// Encodes UTF-16 code units into a vector of bytes using the given flavor of
+/// encoding, one code unit at a time.
+///
+/// Every surrogate becomes its own 3-byte sequence, which is exactly how CESU-8
+/// encodes a surrogate pair. Lone surrogates are encoded the same way, so the
+/// output is only valid when `units` is valid UTF-16.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+pub(crate) fn encode_utf16(units: &[u16], flavor: Flavor) -> Vec<u8> {
+    // NOTE: Unlike UTF-8 input, every code unit is at most 3 bytes, so the
+    // exact length is cheap enough to always count first.
+    let len = units
+        .iter()
+        .map(|&unit| {
+            match unit {
+                0x0000 if flavor == Flavor::Mutf8 => 2,
+                0x0000..=0x007f => 1,
+                0x0080..=0x07ff => 2,
+                _ => 3,
+            }
+        })
+        .sum();
+    let mut encoded = Vec::with_capacity(len);
 
-            let [s1, s2] = to_surrogate_pair(code_point);
-            encoded.extend_from_slice(&encode_surrogate(s1));
-            encoded.extend_from_slice(&encode_surrogate(s2));
-            index += 4;
-        };
+    for &unit in units {
+        match unit {
+            0x0000 if flavor == Flavor::Mutf8 => encoded.extend_from_slice(&[0xc0, 0x80]),
+            0x0000..=0x007f => encoded.push((unit & 0b0111_1111) as u8),
+            0x0080..=0x07ff => {
+                encoded.extend_from_slice(&[
+                    0b1100_0000 | ((unit & 0b0000_0111_1100_0000) >> 6) as u8,
+                    0b1000_0000 | ((unit & 0b0000_0000_0011_1111) as u8),
+                ]);
+            }
+            // NOTE: A surrogate is encoded the same as any other code unit
+            // that takes 3 bytes.
+            _ => encoded.extend_from_slice(&encode_surrogate(unit)),
+        }
     }
 
+    debug_assert_eq!(encoded.len(), len);
     encoded
 }
 
+/// Encodes `value` in the given flavor without allocating, calling `sink` with
+/// each contiguous run of the output, and returns the total number of bytes
+/// passed to `sink`.
+///
+/// Runs of characters that are encoded the same in UTF-8 are borrowed
+/// straight from `value`. Each null (in MUTF-8) and each surrogate pair is
+/// passed to `sink` on its own.
+#[inline]
+pub(crate) fn encode_to_sink<F: FnMut(&[u8])>(value: &str, flavor: Flavor, mut sink: F) -> usize {
+    let bytes = value.as_bytes();
+    let mut index = 0;
+    let mut len = 0;
+
+    while index < bytes.len() {
+        // SAFETY: We know that `index` is less than `bytes.len()`.
+        let rest = unsafe { bytes.get_unchecked(index..) };
+        let run = match flavor {
+            Flavor::Cesu8 => rest.iter().position(|&byte| byte >= 0xf0),
+            Flavor::Mutf8 => rest.iter().position(|&byte| byte >= 0xf0 || byte == 0x00),
+        }
+        .unwrap_or(rest.len());
+
+        if run != 0 {
+            // SAFETY: `position` never returns an index past the end of `rest`.
+            sink(unsafe { rest.get_unchecked(..run) });
+            index += run;
+            len += run;
+        }
+
+        let Some(&first) = bytes.get(index) else {
+            break;
+        };
+
+        if first == 0x00 {
+            sink(&[0xc0, 0x80]);
+            index += 1;
+            len += 2;
+        } else {
+            debug_assert!(first <= 0xf4 && is_utf8_sequence(bytes, index, 4));
+            // SAFETY: We know that `bytes` is a valid UTF-8 string, so the
+            // slice is guaranteed to be valid.
+            let slice = unsafe { bytes.get_unchecked(index..index + 4) };
+            sink(&encode_4_byte_char(slice));
+            index += 4;
+            len += 6;
+        }
+    }
+
+    len
+}
+
+/// Encodes `value` in the given flavor into `out` without allocating, and
+/// returns the number of bytes written.
+///
+/// The encoded length is worked out first, so nothing is written to `out` if
+/// the output doesn't fit.
+#[inline]
+pub(crate) fn encode_to_slice(
+    value: &str,
+    flavor: Flavor,
+    out: &mut [u8],
+) -> Result<usize, BufferTooSmall> {
+    let len = encoded_len(value.as_bytes(), flavor);
+    let out = out.get_mut(..len).ok_or(BufferTooSmall(()))?;
+
+    let mut buffer = SliceBuffer::new(out);
+    encode_to_sink(value, flavor, |run| buffer.extend_from_slice(run));
+    debug_assert!(buffer
+        .into_written()
+        .is_some_and(|written| written.len() == len));
+    Ok(len)
+}
+
+/// Returns the number of leading bytes of `bytes`, which must be valid UTF-8,
+/// that belong to 1- and 2-byte characters that are encoded the same in the
+/// given flavor.
+///
+/// The count stops at the first lead byte of a 3- or 4-byte character, or at
+/// the first null byte in MUTF-8.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+fn count_leading_1_or_2_byte(bytes: &[u8], flavor: Flavor) -> usize {
+    let position = match flavor {
+        Flavor::Cesu8 => bytes.iter().position(|&byte| byte >= 0xe0),
+        Flavor::Mutf8 => bytes.iter().position(|&byte| byte >= 0xe0 || byte == 0x00),
+    };
+
+    position.unwrap_or(bytes.len())
+}
+
+/// Returns the exact length of `bytes`, which must be valid UTF-8, once
+/// encoded in the given flavor.
+///
+/// Every 4-byte character becomes a 6-byte surrogate pair, and in MUTF-8,
+/// every null byte becomes two bytes.
+#[must_use]
+#[inline]
+pub(crate) fn encoded_len(bytes: &[u8], flavor: Flavor) -> usize {
+    // PERF: Summing into a `u8` per chunk lets LLVM vectorize this. Each byte
+    // adds at most 2, so a chunk of 127 bytes can't overflow a `u8`.
+    let extra: usize = bytes
+        .chunks(127)
+        .map(|chunk| {
+            let extra: u8 = chunk
+                .iter()
+                .map(|&byte| {
+                    match flavor {
+                        Flavor::Cesu8 => u8::from(byte >= 0xf0) * 2,
+                        Flavor::Mutf8 => u8::from(byte >= 0xf0) * 2 + u8::from(byte == 0x00),
+                    }
+                })
+                .sum();
+            usize::from(extra)
+        })
+        .sum();
+    bytes.len() + extra
+}
+
+/// Returns the number of 4-byte characters in `bytes`, which must be valid
+/// UTF-8. Each of them becomes a surrogate pair once encoded.
+#[must_use]
+#[inline]
+pub(crate) fn surrogate_header_count(bytes: &[u8]) -> usize {
+    count_matching(bytes, |byte| byte >= 0xf0)
+}
+
+/// Returns `true` if `bytes` contains a high surrogate immediately followed by
+/// a low surrogate, which is how both CESU-8 and MUTF-8 encode every character
+/// outside of the Basic Multilingual Plane.
 #[must_use]
 #[inline]
-fn to_surrogate_pair(code_point: u32) -> [u16; 2] {
+pub(crate) fn contains_surrogate_pair(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+
+    loop {
+        // PERF: A surrogate pair never contains ASCII, so runs of it are
+        // skipped with the active implementation rather than a byte at a time.
+        rest = &rest[count_leading_ascii(rest)..];
+
+        match rest {
+            [] => return false,
+            [0xed, 0xa0..=0xaf, 0x80..=0xbf, 0xed, 0xb0..=0xbf, 0x80..=0xbf, ..] => return true,
+            [_, tail @ ..] => rest = tail,
+        }
+    }
+}
+
+/// Returns the number of null bytes in `bytes`.
+#[must_use]
+#[inline]
+pub(crate) fn null_count(bytes: &[u8]) -> usize {
+    count_matching(bytes, |byte| byte == 0x00)
+}
+
+/// Returns the number of bytes in `bytes` that `is_match` returns `true` for.
+#[must_use]
+#[inline]
+fn count_matching(bytes: &[u8], is_match: impl Fn(u8) -> bool) -> usize {
+    // PERF: Just like `encoded_len`, summing into a `u8` per chunk lets LLVM
+    // vectorize this. A chunk of 255 bytes can't overflow a `u8`.
+    bytes
+        .chunks(255)
+        .map(|chunk| {
+            let count: u8 = chunk.iter().map(|&byte| u8::from(is_match(byte))).sum();
+            usize::from(count)
+        })
+        .sum()
+}
+
+/// Rewrites every null in `bytes`, which must be valid CESU-8, as `0xc0 0x80`,
+/// which turns it into MUTF-8.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+pub(crate) fn cesu8_to_mutf8(bytes: &[u8]) -> Vec<u8> {
+    let mut transcoded = Vec::with_capacity(bytes.len() + null_count(bytes));
+
+    for (i, run) in bytes.split(|&byte| byte == 0x00).enumerate() {
+        if i != 0 {
+            transcoded.extend_from_slice(&[0xc0, 0x80]);
+        }
+
+        transcoded.extend_from_slice(run);
+    }
+
+    transcoded
+}
+
+/// Rewrites every `0xc0 0x80` in `bytes`, which must be valid MUTF-8, as a
+/// null, which turns it into CESU-8.
+#[cfg(feature = "alloc")]
+#[must_use]
+#[inline]
+pub(crate) fn mutf8_to_cesu8(bytes: &[u8]) -> Vec<u8> {
+    let mut transcoded = Vec::with_capacity(bytes.len());
+
+    // NOTE: In valid MUTF-8, `0xc0` is always followed by `0x80`, so every run
+    // after the first starts with the `0x80` of a null.
+    for (i, run) in bytes.split(|&byte| byte == 0xc0).enumerate() {
+        if i == 0 {
+            transcoded.extend_from_slice(run);
+        } else {
+            debug_assert_eq!(run.first(), Some(&0x80));
+            transcoded.push(0x00);
+            transcoded.extend_from_slice(run.get(1..).unwrap_or_default());
+        }
+    }
+
+    transcoded
+}
+
+/// Returns `true` if `bytes` has room for a sequence of `width` bytes starting
+/// at `index`, and every byte after the first is a continuation byte.
+///
+/// NOTE: This is only used in debug assertions to catch a `&str` that was
+/// constructed from invalid UTF-8, since `encode` trusts the `str` invariant.
+#[must_use]
+#[inline]
+fn is_utf8_sequence(bytes: &[u8], index: usize, width: usize) -> bool {
+    bytes
+        .get(index + 1..index + width)
+        .is_some_and(|rest| rest.iter().all(|&byte| byte & 0b1100_0000 == 0b1000_0000))
+}
+
+/// Encodes a 4-byte UTF-8 character as a CESU-8 surrogate pair.
+#[must_use]
+#[inline]
+pub(crate) fn encode_4_byte_char(slice: &[u8]) -> [u8; 6] {
+    let &[first, second, third, fourth] = slice else {
+        // SAFETY: Every caller passes a slice of exactly four bytes.
+        unsafe { hint::unreachable_unchecked() };
+    };
+
+    let code_point = (u32::from(first & 0b0000_0111) << 18)
+        | (u32::from(second & 0b0011_1111) << 12)
+        | (u32::from(third & 0b0011_1111) << 6)
+        | u32::from(fourth & 0b0011_1111);
+
+    let [s1, s2] = to_surrogate_pair(code_point);
+    let [high, low] = [encode_surrogate(s1), encode_surrogate(s2)];
+    [high[0], high[1], high[2], low[0], low[1], low[2]]
+}
+
+#[must_use]
+#[inline]
+pub(crate) const fn to_surrogate_pair(code_point: u32) -> [u16; 2] {
     let code_point = code_point - 0x10000;
     #[allow(clippy::cast_possible_truncation)]
     let high = ((code_point >> 10) as u16) | 0xd800;
@@ -284,7 +1690,7 @@ fn to_surrogate_pair(code_point: u32) -> [u16; 2] {
 
 #[must_use]
 #[inline]
-fn encode_surrogate(surrogate: u16) -> [u8; 3] {
+pub(crate) const fn encode_surrogate(surrogate: u16) -> [u8; 3] {
     [
         0b1110_0000 | ((surrogate & 0b1111_0000_0000_0000) >> 12) as u8,
         0b1000_0000 | ((surrogate & 0b0000_1111_1100_0000) >> 6) as u8,
@@ -292,4 +1698,27 @@ fn encode_surrogate(surrogate: u16) -> [u8; 3] {
     ]
 }
 
+/// The byte order mark, U+FEFF, which is encoded the same way in UTF-8, CESU-8,
+/// and MUTF-8.
+pub(crate) const BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+
+/// Inputs at least this long are encoded into a buffer of exactly the right
+/// size, rather than twice the input length.
+#[cfg(feature = "alloc")]
+const EXACT_CAPACITY_THRESHOLD: usize = 64 * 1024;
+
+/// Vectors returned by [`encode`] are shrunk to fit if more than this fraction
+/// of their length, i.e. a quarter, is unused capacity.
+#[cfg(feature = "alloc")]
+const MAX_SLACK_DIVISOR: usize = 4;
+
+/// Vectors returned by [`encode`] are never shrunk unless at least this many
+/// bytes of their capacity are unused.
+#[cfg(feature = "alloc")]
+const MIN_SHRINK_SLACK: usize = 4 * 1024;
+
+/// The size of the stack buffer that [`SinkBuffer`] collects decoded runs in.
+const SINK_BUFFER_SIZE: usize = 256;
+
+#[cfg(feature = "alloc")]
 const ISIZE_MAX_USIZE: usize = isize::MAX as usize;
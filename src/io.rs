@@ -0,0 +1,371 @@
+// NOTE: Newer Clippy suggests `core::io::ErrorKind`, which is newer than our
+// minimum supported Rust version.
+#![allow(clippy::std_instead_of_core)]
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::io::{self, ErrorKind, Read, Write};
+
+use simdutf8::compat::from_utf8;
+
+use crate::error::{DecodingError, DecodingErrorKind};
+use crate::internal::{self, Flavor};
+use crate::stream::Cesu8Decoder;
+
+/// An [`io::Write`] adapter that encodes UTF-8 to CESU-8 on the fly.
+///
+/// Every byte written to this adapter must be UTF-8, and is written to the
+/// inner writer as CESU-8. Writes don't need to line up with character
+/// boundaries: when a write ends in the middle of a character, the adapter
+/// holds on to the start of it until the next write completes it. At most 3
+/// bytes are ever held on to.
+///
+/// **NOTE:** [`flush`](Write::flush) doesn't write out a character that was
+/// cut off, since it isn't complete yet, and dropping the adapter discards it
+/// silently. Call [`finish`](Cesu8Writer::finish) when you're done writing to
+/// find out if the input ended in the middle of a character.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+///
+/// use simd_cesu8::Cesu8Writer;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let value = "a💖".as_bytes();
+/// let mut writer = Cesu8Writer::new(Vec::new());
+///
+/// // NOTE: The emoji is split between the writes.
+/// writer.write_all(&value[..3])?;
+/// writer.write_all(&value[3..])?;
+///
+/// let encoded = writer.finish()?;
+/// assert_eq!(encoded, [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cesu8Writer<W: Write> {
+    inner: W,
+    pending: [u8; MAX_UTF8_WIDTH],
+    pending_len: usize,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Cesu8Writer<W> {
+    /// Creates a new adapter that writes CESU-8 to `inner`.
+    #[must_use]
+    #[inline]
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: [0; MAX_UTF8_WIDTH],
+            pending_len: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the inner writer.
+    #[must_use]
+    #[inline]
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// **NOTE:** Writing to the inner writer directly while a character is
+    /// held on to will put the rest of the output out of order.
+    #[must_use]
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns `true` if the adapter is holding on to part of a character.
+    #[must_use]
+    #[inline]
+    pub const fn has_pending(&self) -> bool {
+        self.pending_len != 0
+    }
+
+    /// Finishes writing, flushing the inner writer and returning it.
+    ///
+    /// # Errors
+    ///
+    /// If the adapter is still holding on to part of a character, this
+    /// function will return an error of kind [`ErrorKind::InvalidData`], as the
+    /// input wasn't valid UTF-8. Otherwise, it returns any error from flushing
+    /// the inner writer.
+    #[inline]
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.has_pending() {
+            return Err(invalid_utf8());
+        }
+
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encodes `value`, and writes it to the inner writer.
+    fn write_str(&mut self, value: &str) -> io::Result<()> {
+        if crate::needs_encoded(value) {
+            self.buffer.clear();
+            crate::encode_into(value, &mut self.buffer);
+            self.inner.write_all(&self.buffer)
+        } else {
+            self.inner.write_all(value.as_bytes())
+        }
+    }
+
+    /// Completes the held on to character with the start of `buf`, returning
+    /// the number of bytes of `buf` that were used.
+    fn complete_pending(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut bytes = self.pending;
+        let width = utf8_char_width(bytes[0]);
+        let used = (width - self.pending_len).min(buf.len());
+        let len = self.pending_len + used;
+
+        // NOTE: `len` is at most `width`, which is at most `MAX_UTF8_WIDTH`,
+        // and `used` is at most `buf.len()`.
+        bytes[self.pending_len..len].copy_from_slice(&buf[..used]);
+
+        match from_utf8(&bytes[..len]) {
+            Ok(value) => self.write_str(value)?,
+            Err(error) if error.error_len().is_some() => return Err(invalid_utf8()),
+            Err(_) => {}
+        }
+
+        // NOTE: We only update the held on to character once we know nothing
+        // failed, so a failed write can be retried with the same bytes.
+        self.pending = bytes;
+        self.pending_len = if len == width { 0 } else { len };
+        Ok(used)
+    }
+}
+
+impl<W: Write> Write for Cesu8Writer<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+
+        if self.has_pending() {
+            written = self.complete_pending(buf)?;
+
+            if self.has_pending() {
+                return Ok(written);
+            }
+        }
+
+        // SAFETY: `written` is at most `buf.len()`.
+        let rest = unsafe { buf.get_unchecked(written..) };
+
+        match from_utf8(rest) {
+            Ok(value) => {
+                self.write_str(value)?;
+                Ok(buf.len())
+            }
+            Err(error) => {
+                let valid_up_to = error.valid_up_to();
+                let (valid, tail) = rest.split_at(valid_up_to);
+                // SAFETY: `from_utf8` checked that these bytes are valid UTF-8.
+                self.write_str(unsafe { core::str::from_utf8_unchecked(valid) })?;
+
+                if error.error_len().is_some() {
+                    // NOTE: We report the bytes before the invalid sequence as
+                    // written, so the error is returned by the next write.
+                    return if written + valid_up_to == 0 {
+                        Err(invalid_utf8())
+                    } else {
+                        Ok(written + valid_up_to)
+                    };
+                }
+
+                // NOTE: The input ends in the middle of a character, which the
+                // next write might complete.
+                self.pending[..tail.len()].copy_from_slice(tail);
+                self.pending_len = tail.len();
+                Ok(buf.len())
+            }
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// An [`io::Read`] adapter that decodes CESU-8 to UTF-8 on the fly.
+///
+/// This reads CESU-8 from the inner reader in chunks, and yields it as UTF-8.
+/// Chunks don't need to line up with character boundaries, and neither do the
+/// buffers passed to [`read`](Read::read): a surrogate pair that was cut off
+/// is held on to until the next chunk completes it, and decoded output that
+/// doesn't fit in the buffer is held on to until the next read.
+///
+/// Like [`decode_strict`](crate::decode_strict), this adapter treats valid
+/// UTF-8 that is not valid CESU-8 as invalid input.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// use simd_cesu8::Cesu8Reader;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let bytes: &[u8] = &[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// let mut reader = Cesu8Reader::new(bytes);
+///
+/// let mut decoded = String::new();
+/// reader.read_to_string(&mut decoded)?;
+/// assert_eq!(decoded, "a💖");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cesu8Reader<R: Read> {
+    inner: R,
+    decoder: Cesu8Decoder,
+    input: Vec<u8>,
+    output: String,
+    position: usize,
+}
+
+impl<R: Read> Cesu8Reader<R> {
+    /// Creates a new adapter that reads CESU-8 from `inner`.
+    #[must_use]
+    #[inline]
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            decoder: Cesu8Decoder::new(),
+            input: Vec::new(),
+            output: String::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    #[inline]
+    pub const fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// **NOTE:** Reading from the inner reader directly will skip over input
+    /// the adapter hasn't seen yet, which may leave a sequence cut off.
+    #[must_use]
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the inner reader.
+    ///
+    /// **NOTE:** Any input that was read from the inner reader, but not yet
+    /// returned from this adapter, is lost.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decodes the next chunk of input, returning `false` if the
+    /// inner reader is exhausted.
+    fn fill_output(&mut self) -> io::Result<bool> {
+        if self.input.is_empty() {
+            self.input = vec![0; READ_BUFFER_SIZE];
+        }
+
+        let len = self.inner.read(&mut self.input)?;
+
+        if len == 0 {
+            return if self.decoder.has_pending() {
+                Err(DecodingError::new(DecodingErrorKind::UnexpectedEof).into())
+            } else {
+                Ok(false)
+            };
+        }
+
+        self.output.clear();
+        self.position = 0;
+        self.decoder.feed(&self.input[..len], &mut self.output)?;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for Cesu8Reader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // NOTE: A chunk may decode to nothing at all when it only contains
+        // part of a sequence, so we keep going until there's something to
+        // return.
+        while self.position == self.output.len() {
+            if !self.fill_output()? {
+                return Ok(0);
+            }
+        }
+
+        let available = &self.output.as_bytes()[self.position..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Encodes `value` in the given flavor straight into `writer`, returning the
+/// number of bytes written.
+///
+/// NOTE: The sink can't fail, so the first error is held on to and every run
+/// after it is skipped.
+#[inline]
+pub(crate) fn encode_to_writer<W: Write + ?Sized>(
+    value: &str,
+    flavor: Flavor,
+    writer: &mut W,
+) -> io::Result<usize> {
+    let mut result = Ok(());
+    let len = internal::encode_to_sink(value, flavor, |run| {
+        if result.is_ok() {
+            result = writer.write_all(run);
+        }
+    });
+
+    result.map(|()| len)
+}
+
+/// Returns the number of bytes in the UTF-8 character that starts with `lead`.
+#[must_use]
+#[inline]
+const fn utf8_char_width(lead: u8) -> usize {
+    match lead {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+/// Returns the error for input that isn't valid UTF-8.
+#[must_use]
+#[cold]
+fn invalid_utf8() -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+}
+
+/// The number of bytes read from the inner reader at a time.
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The widest UTF-8 character.
+const MAX_UTF8_WIDTH: usize = 4;
@@ -0,0 +1,176 @@
+use core::iter::FusedIterator;
+
+use crate::error::DecodingError;
+use crate::internal::{self, Flavor};
+
+/// An iterator over the characters of CESU-8 or MUTF-8 bytes, decoded one at
+/// a time.
+///
+/// This is returned by [`decode_chars`](crate::decode_chars) and
+/// [`mutf8::decode_chars`](crate::mutf8::decode_chars). Nothing is decoded
+/// until it's asked for, so stopping early, such as with
+/// [`Iterator::take_while`] or [`Iterator::find`], never decodes the rest of
+/// the input, and nothing is ever allocated.
+///
+/// Like [`decode_strict`](crate::decode_strict), this iterator treats valid
+/// UTF-8 that is not valid CESU-8 as invalid input, since whether the whole
+/// input is valid UTF-8 isn't known until the end.
+///
+/// After an error is returned, the iterator only returns [`None`].
+///
+/// # Examples
+///
+/// ```
+/// let bytes = [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xff];
+/// let mut chars = simd_cesu8::decode_chars(&bytes);
+///
+/// assert_eq!(chars.next(), Some(Ok('a')));
+/// assert_eq!(chars.as_bytes(), [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xff]);
+/// assert_eq!(chars.next(), Some(Ok('💖')));
+/// assert!(chars.next().unwrap().is_err());
+/// assert_eq!(chars.next(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DecodeChars<'a> {
+    bytes: &'a [u8],
+    flavor: Flavor,
+}
+
+impl<'a> DecodeChars<'a> {
+    #[inline]
+    pub(crate) const fn new(bytes: &'a [u8], flavor: Flavor) -> Self {
+        Self { bytes, flavor }
+    }
+
+    /// Returns the bytes that haven't been decoded yet.
+    #[must_use]
+    #[inline]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+impl Iterator for DecodeChars<'_> {
+    type Item = Result<char, DecodingError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&first, rest) = self.bytes.split_first()?;
+
+        // PERF: ASCII is by far the most common case, so we skip the state
+        // machine for it.
+        if first.is_ascii() && (first != 0x00 || self.flavor == Flavor::Cesu8) {
+            self.bytes = rest;
+            return Some(Ok(char::from(first)));
+        }
+
+        match internal::decode_char(self.bytes, self.flavor) {
+            Ok((char, len)) => {
+                self.bytes = self.bytes.get(len..).unwrap_or_default();
+                Some(Ok(char))
+            }
+            Err(error) => {
+                self.bytes = &[];
+                Some(Err(error))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // NOTE: Every character takes up at least 1 byte, but an error takes up
+        // the rest of the input, however long it is.
+        (usize::from(!self.bytes.is_empty()), Some(self.bytes.len()))
+    }
+}
+
+impl FusedIterator for DecodeChars<'_> {}
+
+/// An iterator over the bytes of a string encoded to CESU-8 or MUTF-8,
+/// produced one at a time.
+///
+/// This is returned by [`encode_bytes`](crate::encode_bytes) and
+/// [`mutf8::encode_bytes`](crate::mutf8::encode_bytes). Nothing is encoded
+/// until it's asked for, and nothing is ever allocated, so the bytes can be fed
+/// straight into a hasher or a writer. The exact number of bytes left is always
+/// known, so collecting them only allocates once.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// let bytes = simd_cesu8::encode_bytes("a💖");
+/// assert_eq!(bytes.len(), 7);
+///
+/// let encoded: Vec<u8> = bytes.collect();
+/// assert_eq!(encoded, [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncodeBytes<'a> {
+    bytes: &'a [u8],
+    flavor: Flavor,
+    pending: [u8; 6],
+    pending_start: usize,
+    pending_end: usize,
+    len: usize,
+}
+
+impl<'a> EncodeBytes<'a> {
+    #[inline]
+    pub(crate) fn new(value: &'a str, flavor: Flavor) -> Self {
+        Self {
+            bytes: value.as_bytes(),
+            flavor,
+            pending: [0; 6],
+            pending_start: 0,
+            pending_end: 0,
+            len: internal::encoded_len(value.as_bytes(), flavor),
+        }
+    }
+}
+
+impl Iterator for EncodeBytes<'_> {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.pending_start < self.pending_end {
+            let byte = self.pending[self.pending_start];
+            self.pending_start += 1;
+            self.len -= 1;
+            return Some(byte);
+        }
+
+        let (&first, rest) = self.bytes.split_first()?;
+        self.len -= 1;
+
+        if first == 0x00 && self.flavor == Flavor::Mutf8 {
+            self.bytes = rest;
+            self.pending[0] = 0x80;
+            self.pending_start = 0;
+            self.pending_end = 1;
+            Some(0xc0)
+        } else if first < 0xf0 {
+            self.bytes = rest;
+            Some(first)
+        } else {
+            let (char, rest) = self.bytes.split_at(4);
+            self.bytes = rest;
+            self.pending = internal::encode_4_byte_char(char);
+            self.pending_start = 1;
+            self.pending_end = 6;
+            Some(self.pending[0])
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for EncodeBytes<'_> {}
+
+impl FusedIterator for EncodeBytes<'_> {}
@@ -0,0 +1,154 @@
+//! This module contains the `const` encoder behind the
+//! [`cesu8!`](crate::cesu8!) and [`mutf8!`](crate::mutf8!) macros.
+//!
+//! THIS MODULE IS NOT PART OF THE PUBLIC API AND IS SEMVER EXEMPT.
+
+use crate::internal::{encode_surrogate, to_surrogate_pair, Flavor};
+
+/// Encodes a string literal to CESU-8 at compile time, and evaluates to a
+/// `&'static [u8]`.
+///
+/// The literal is encoded by a `const fn` that produces exactly the same bytes
+/// as [`encode`](crate::encode), so there's no cost at runtime, and nothing is
+/// allocated. The macro can be used anywhere a constant can, including to
+/// initialize a `const` or `static`.
+///
+/// # Examples
+///
+/// ```
+/// const HEART: &[u8] = simd_cesu8::cesu8!("💖");
+///
+/// assert_eq!(HEART, [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// ```
+#[macro_export]
+macro_rules! cesu8 {
+    ($value:expr $(,)?) => {
+        $crate::__encode_literal!($value, $crate::Flavor::Cesu8)
+    };
+}
+
+/// Encodes a string literal to MUTF-8 at compile time, and evaluates to a
+/// `&'static [u8]`.
+///
+/// This is the MUTF-8 counterpart of [`cesu8!`](crate::cesu8!), which
+/// produces exactly the same bytes as [`mutf8::encode`](crate::mutf8::encode).
+///
+/// # Examples
+///
+/// ```
+/// static NAME: &[u8] = simd_cesu8::mutf8!("a\0💖");
+///
+/// assert_eq!(NAME, [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// ```
+#[macro_export]
+macro_rules! mutf8 {
+    ($value:expr $(,)?) => {
+        $crate::__encode_literal!($value, $crate::Flavor::Mutf8)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __encode_literal {
+    ($value:expr, $flavor:expr) => {{
+        // NOTE: Items aren't hygienic, so these names must not clash with any
+        // constant that `$value` refers to.
+        const __SIMD_CESU8_VALUE: &str = $value;
+        const __SIMD_CESU8_LEN: usize = $crate::literal::encoded_len(__SIMD_CESU8_VALUE, $flavor);
+        const __SIMD_CESU8_ENCODED: [u8; __SIMD_CESU8_LEN] =
+            $crate::literal::encode(__SIMD_CESU8_VALUE, $flavor);
+        let encoded: &'static [u8] = &__SIMD_CESU8_ENCODED;
+        encoded
+    }};
+}
+
+/// Returns the exact length of `value` once encoded in the given flavor.
+#[must_use]
+pub const fn encoded_len(value: &str, flavor: Flavor) -> usize {
+    let bytes = value.as_bytes();
+    let mut len = bytes.len();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] >= 0xf0 {
+            len += 2;
+        } else if bytes[index] == 0x00 && matches!(flavor, Flavor::Mutf8) {
+            len += 1;
+        }
+
+        index += 1;
+    }
+
+    len
+}
+
+/// Returns `true` if `value` needs to be encoded in the given flavor, scanning
+/// it one byte at a time so that it can be evaluated in a constant.
+#[must_use]
+pub(crate) const fn needs_encoded(value: &str, flavor: Flavor) -> bool {
+    let bytes = value.as_bytes();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] >= 0xf0 || (bytes[index] == 0x00 && matches!(flavor, Flavor::Mutf8)) {
+            return true;
+        }
+
+        index += 1;
+    }
+
+    false
+}
+
+/// Encodes `value` in the given flavor. `N` must be the length returned by
+/// [`encoded_len`].
+///
+/// # Panics
+///
+/// This function will panic if `N` isn't the encoded length of `value`. In a
+/// constant, this is a compile-time error instead.
+#[must_use]
+pub const fn encode<const N: usize>(value: &str, flavor: Flavor) -> [u8; N] {
+    let bytes = value.as_bytes();
+    let mut encoded = [0; N];
+    let mut read = 0;
+    let mut write = 0;
+
+    // NOTE: Mutable references aren't allowed in a `const fn` on our minimum
+    // supported Rust version, so every byte is written here.
+    while read < bytes.len() {
+        let first = bytes[read];
+
+        if first == 0x00 && matches!(flavor, Flavor::Mutf8) {
+            encoded[write] = 0xc0;
+            encoded[write + 1] = 0x80;
+            read += 1;
+            write += 2;
+        } else if first >= 0xf0 {
+            let code_point = (first as u32 & 0b0000_0111) << 18
+                | (bytes[read + 1] as u32 & 0b0011_1111) << 12
+                | (bytes[read + 2] as u32 & 0b0011_1111) << 6
+                | (bytes[read + 3] as u32 & 0b0011_1111);
+
+            let [high, low] = to_surrogate_pair(code_point);
+            let [high, low] = [encode_surrogate(high), encode_surrogate(low)];
+            let mut index = 0;
+
+            while index < 3 {
+                encoded[write + index] = high[index];
+                encoded[write + 3 + index] = low[index];
+                index += 1;
+            }
+
+            read += 4;
+            write += 6;
+        } else {
+            encoded[write] = first;
+            read += 1;
+            write += 1;
+        }
+    }
+
+    assert!(write == N, "`N` must be the encoded length of `value`");
+    encoded
+}
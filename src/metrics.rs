@@ -0,0 +1,147 @@
+#[cfg(feature = "metrics")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Process-wide counters of the work done by the encoding and decoding
+/// functions in this crate.
+///
+/// Every successful call to an encoding or decoding function adds to these
+/// counters, whether it borrowed its input or had to transform it. Calls that
+/// return a [`DecodingError`](crate::DecodingError) are not counted. The
+/// counters are only ever read and written with [`Ordering::Relaxed`], so they
+/// are meant for observability, not for synchronization.
+///
+/// The global instance is returned by [`metrics`].
+///
+/// # Examples
+///
+/// ```
+/// let before = simd_cesu8::metrics().transforms();
+///
+/// let encoded = simd_cesu8::encode("💖");
+/// assert_eq!(encoded.len(), 6);
+///
+/// assert!(simd_cesu8::metrics().transforms() > before);
+/// ```
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Debug, Default)]
+pub struct Metrics {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    transforms: AtomicU64,
+    replacements: AtomicU64,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// Creates a new set of counters, all set to zero.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            transforms: AtomicU64::new(0),
+            replacements: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the total number of bytes passed to the encoding and decoding
+    /// functions.
+    #[must_use]
+    #[inline]
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of bytes returned by the encoding and decoding
+    /// functions, including borrowed output.
+    #[must_use]
+    #[inline]
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls that had to transform their input into a
+    /// newly allocated output, rather than borrowing it.
+    #[must_use]
+    #[inline]
+    pub fn transforms(&self) -> u64 {
+        self.transforms.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of [U+FFFD REPLACEMENT CHARACTER]s (�) that lossy
+    /// decoding substituted for invalid input.
+    ///
+    /// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
+    #[must_use]
+    #[inline]
+    pub fn replacements(&self) -> u64 {
+        self.replacements.load(Ordering::Relaxed)
+    }
+
+    /// Sets every counter back to zero.
+    ///
+    /// The counters are reset one at a time, so a call that runs concurrently
+    /// with this one may be partially counted.
+    #[inline]
+    pub fn reset(&self) {
+        self.bytes_in.store(0, Ordering::Relaxed);
+        self.bytes_out.store(0, Ordering::Relaxed);
+        self.transforms.store(0, Ordering::Relaxed);
+        self.replacements.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "metrics")]
+static GLOBAL: Metrics = Metrics::new();
+
+/// Returns the global [`Metrics`] that every encoding and decoding function in
+/// this crate reports to.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[must_use]
+#[inline]
+pub fn metrics() -> &'static Metrics {
+    &GLOBAL
+}
+
+/// Records a successful call that read `bytes_in` bytes and returned
+/// `bytes_out` bytes, where `transformed` is `true` if the output had to be
+/// allocated.
+#[cfg(feature = "metrics")]
+#[inline]
+pub(crate) fn record(bytes_in: usize, bytes_out: usize, transformed: bool) {
+    GLOBAL
+        .bytes_in
+        .fetch_add(bytes_in as u64, Ordering::Relaxed);
+    GLOBAL
+        .bytes_out
+        .fetch_add(bytes_out as u64, Ordering::Relaxed);
+
+    if transformed {
+        GLOBAL.transforms.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Records `count` replacement characters emitted by lossy decoding.
+#[cfg(all(feature = "alloc", feature = "metrics"))]
+#[inline]
+pub(crate) fn record_replacements(count: usize) {
+    if count != 0 {
+        GLOBAL
+            .replacements
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+// NOTE: These are what the rest of the crate calls when the `metrics` feature
+// is disabled. They're empty, so every call site compiles away entirely.
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn record(_bytes_in: usize, _bytes_out: usize, _transformed: bool) {}
+
+#[cfg(all(feature = "alloc", not(feature = "metrics")))]
+#[inline(always)]
+pub(crate) fn record_replacements(_count: usize) {}
@@ -3,14 +3,47 @@
 //! `0x80`. In general, this module is nearly identical to the functions found
 //! in the root of this crate.
 
+#[cfg(feature = "alloc")]
 use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
 
 use simdutf8::basic::from_utf8;
+#[cfg(feature = "alloc")]
+use simdutf8::compat;
 
-use crate::error::DecodingError;
+use crate::error::{BufferTooSmall, DecodeToSliceError, DecodingError};
+#[cfg(feature = "alloc")]
+use crate::error::{
+    DecodingErrorAt,
+    DecodingErrorKind,
+    EncodeError,
+    TryDecodeError,
+    VarintStringError,
+};
 use crate::implementation::active::contains_null_or_utf8_4_byte_char_header;
-use crate::internal;
 use crate::internal::{DecodeOptions, Flavor};
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use crate::stream::Mutf8Decoder;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use crate::string::Mutf8String;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use crate::validated::Mutf8Buf;
+pub use crate::validated::Mutf8Str;
+#[cfg(feature = "alloc")]
+use crate::varint;
+use crate::{internal, metrics, DecodeChars, EncodeBytes};
 
 /// Converts a slice of bytes to a string, including invalid characters.
 ///
@@ -33,6 +66,17 @@ use crate::internal::{DecodeOptions, Flavor};
 /// REPLACEMENT CHARACTER] (�). If you don't need this guarantee, and can
 /// tolerate valid UTF-8 that is not valid MUTF-8, use [`decode_lossy`] instead.
 ///
+/// Invalid input is replaced the same way [`String::from_utf8_lossy`] does it:
+/// the longest prefix of a well-formed sequence is replaced by a single �, and
+/// any other invalid byte by a � of its own. A lone surrogate is well-formed
+/// in MUTF-8, but has no character to decode to, so it's replaced by a single �
+/// as well.
+///
+/// **NOTE:** A U+FFFD REPLACEMENT CHARACTER that is genuinely part of the
+/// input is valid MUTF-8, and decodes to itself. Because of this, the output
+/// alone can't tell you whether a � was in the input or replaced an invalid
+/// byte. Use [`decode_strict`] if you need to know.
+///
 /// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
 ///
 /// # Panics
@@ -102,9 +146,11 @@ use crate::internal::{DecodeOptions, Flavor};
 /// let decoded = mutf8::decode_lossy_strict(&bytes);
 /// assert_eq!(decoded, Cow::<str>::Owned(String::from("���")));
 /// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[must_use]
 #[inline]
-pub fn decode_lossy_strict(bytes: &[u8]) -> Cow<str> {
+pub fn decode_lossy_strict(bytes: &[u8]) -> Cow<'_, str> {
     if contains_null_or_utf8_4_byte_char_header(bytes) || from_utf8(bytes).is_err() {
         let result = internal::decode(bytes, DecodeOptions {
             flavor: Flavor::Mutf8,
@@ -115,8 +161,10 @@ pub fn decode_lossy_strict(bytes: &[u8]) -> Cow<str> {
         // valid string.
         let string = unsafe { result.unwrap_unchecked() };
 
+        metrics::record(bytes.len(), string.len(), true);
         Cow::Owned(string)
     } else {
+        metrics::record(bytes.len(), bytes.len(), false);
         // SAFETY: We know that `bytes` is a valid UTF-8 string.
         Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) })
     }
@@ -143,6 +191,17 @@ pub fn decode_lossy_strict(bytes: &[u8]) -> Cow<str> {
 /// a significant performance optimization. If you need to strictly enforce
 /// MUTF-8 decoding, use [`decode_lossy_strict`] instead.
 ///
+/// Invalid input is replaced the same way [`String::from_utf8_lossy`] does it:
+/// the longest prefix of a well-formed sequence is replaced by a single �, and
+/// any other invalid byte by a � of its own. A lone surrogate is well-formed
+/// in MUTF-8, but has no character to decode to, so it's replaced by a single �
+/// as well.
+///
+/// **NOTE:** A U+FFFD REPLACEMENT CHARACTER that is genuinely part of the
+/// input is valid MUTF-8, and decodes to itself. Because of this, the output
+/// alone can't tell you whether a � was in the input or replaced an invalid
+/// byte. Use [`decode_strict`] if you need to know.
+///
 /// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
 ///
 /// # Panics
@@ -211,10 +270,13 @@ pub fn decode_lossy_strict(bytes: &[u8]) -> Cow<str> {
 /// let decoded = mutf8::decode_lossy(&bytes);
 /// assert_eq!(decoded, Cow::<str>::Owned(String::from("�\0")));
 /// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[must_use]
 #[inline]
-pub fn decode_lossy(bytes: &[u8]) -> Cow<str> {
+pub fn decode_lossy(bytes: &[u8]) -> Cow<'_, str> {
     if let Ok(string) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
         Cow::Borrowed(string)
     } else {
         let result = internal::decode(bytes, DecodeOptions {
@@ -226,10 +288,143 @@ pub fn decode_lossy(bytes: &[u8]) -> Cow<str> {
         // valid string.
         let string = unsafe { result.unwrap_unchecked() };
 
+        metrics::record(bytes.len(), string.len(), true);
         Cow::Owned(string)
     }
 }
 
+/// Converts a slice of bytes to a string like [`decode_lossy`], also returning
+/// how many [U+FFFD REPLACEMENT CHARACTER]s (�) were substituted for invalid
+/// input.
+///
+/// The count comes from the same pass that decodes the input, so this is a
+/// cheap way to tell how corrupt the input was, such as to reject it above a
+/// threshold. Unlike counting the � in the output, this doesn't count a U+FFFD
+/// that was genuinely part of the input. When the input is borrowed, the count
+/// is always zero.
+///
+/// # Panics
+///
+/// This function panics in the same cases as [`decode_lossy`].
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let (decoded, replacements) = mutf8::decode_lossy_counted(b"Hello, world!");
+/// assert_eq!(decoded, "Hello, world!");
+/// assert_eq!(replacements, 0);
+///
+/// // NOTE: The unpaired surrogate is replaced by a single U+FFFD.
+/// let (decoded, replacements) =
+///     mutf8::decode_lossy_counted(&[b'a', 0xed, 0xa0, 0xbd, b'b', 0xff]);
+/// assert_eq!(decoded, "a\u{fffd}b\u{fffd}");
+/// assert_eq!(replacements, 2);
+/// ```
+///
+/// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[must_use]
+#[inline]
+pub fn decode_lossy_counted(bytes: &[u8]) -> (Cow<'_, str>, usize) {
+    if let Ok(string) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
+        (Cow::Borrowed(string), 0)
+    } else {
+        let options = DecodeOptions {
+            flavor: Flavor::Mutf8,
+            lossy: true,
+        };
+        let result = internal::decode_counted_with(bytes, options, |_, _| true);
+
+        // SAFETY: When `recover` always returns `true`, the function will
+        // always return a valid string.
+        let (string, replacements) = unsafe { result.unwrap_unchecked() };
+
+        metrics::record(bytes.len(), string.len(), true);
+        (Cow::Owned(string), replacements)
+    }
+}
+
+/// Converts a slice of bytes to a string, letting `recover` decide which
+/// invalid sequences are replaced and which are rejected.
+///
+/// This sits between [`decode_strict`] and [`decode_lossy_strict`]. Whenever
+/// an invalid sequence is found, `recover` is called with the
+/// [`DecodingErrorKind`] of the error. If it returns `true`, the invalid
+/// sequence is replaced with a single [U+FFFD REPLACEMENT CHARACTER] (�), and
+/// decoding continues after it, exactly like [`decode_lossy_strict`]: the
+/// longest prefix of a well-formed sequence is replaced as a whole, and any
+/// other invalid byte on its own. If it returns `false`, decoding stops and an
+/// error is returned.
+///
+/// **NOTE:** `recover` is called once for each � that would be substituted, so
+/// the continuation bytes that are replaced along with the start of an invalid
+/// sequence are never reported on their own.
+///
+/// The algorithm is as follows:
+///
+/// - If the input is valid UTF-8, but also valid MUTF-8, the function will
+///   return <code>[Cow::Borrowed]\(&[str]\)</code> without calling `recover`.
+/// - Otherwise, the function will return <code>[Cow::Owned]\([String]\)</code>
+///   if every error was recovered from. This case has the potential to panic.
+///
+/// [U+FFFD REPLACEMENT CHARACTER]: char::REPLACEMENT_CHARACTER
+/// [String]: alloc::string::String
+///
+/// # Errors
+///
+/// If `recover` returns `false` for any error, this function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// Replace truncated input, but reject lone surrogates:
+///
+/// ```
+/// use simd_cesu8::{mutf8, DecodingErrorKind};
+///
+/// let policy = |kind| kind != DecodingErrorKind::InvalidSurrogatePair;
+///
+/// let bytes = [b'a', 0xed, 0xa0];
+/// let decoded = mutf8::decode_selective_lossy(&bytes, policy);
+/// assert_eq!(decoded.as_deref(), Ok("a�"));
+///
+/// let bytes = [b'a', 0xed, 0xb0, 0x80];
+/// let result = mutf8::decode_selective_lossy(&bytes, policy);
+/// assert!(result.is_err());
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_selective_lossy(
+    bytes: &[u8],
+    mut recover: impl FnMut(DecodingErrorKind) -> bool,
+) -> Result<Cow<'_, str>, DecodingError> {
+    if contains_null_or_utf8_4_byte_char_header(bytes) || from_utf8(bytes).is_err() {
+        let options = DecodeOptions {
+            flavor: Flavor::Mutf8,
+            lossy: true,
+        };
+        let string = internal::decode_with(bytes, options, |kind, _| recover(kind))?;
+
+        metrics::record(bytes.len(), string.len(), true);
+        Ok(Cow::Owned(string))
+    } else {
+        // SAFETY: We know that `bytes` is a valid UTF-8 string.
+        let string = unsafe { core::str::from_utf8_unchecked(bytes) };
+        metrics::record(bytes.len(), bytes.len(), false);
+        Ok(Cow::Borrowed(string))
+    }
+}
+
 /// Converts a slice of bytes to a string.
 ///
 /// The algorithm is as follows:
@@ -299,18 +494,22 @@ pub fn decode_lossy(bytes: &[u8]) -> Cow<str> {
 /// let result = mutf8::decode_strict(&bytes);
 /// assert!(result.is_err());
 /// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[inline]
-pub fn decode_strict(bytes: &[u8]) -> Result<Cow<str>, DecodingError> {
+pub fn decode_strict(bytes: &[u8]) -> Result<Cow<'_, str>, DecodingError> {
     if contains_null_or_utf8_4_byte_char_header(bytes) || from_utf8(bytes).is_err() {
         let string = internal::decode(bytes, DecodeOptions {
             flavor: Flavor::Mutf8,
             lossy: false,
         })?;
 
+        metrics::record(bytes.len(), string.len(), true);
         Ok(Cow::Owned(string))
     } else {
         // SAFETY: We know that `bytes` is a valid UTF-8 string.
         let string = unsafe { core::str::from_utf8_unchecked(bytes) };
+        metrics::record(bytes.len(), bytes.len(), false);
         Ok(Cow::Borrowed(string))
     }
 }
@@ -395,9 +594,12 @@ pub fn decode_strict(bytes: &[u8]) -> Result<Cow<str>, DecodingError> {
 /// # Ok(())
 /// # }
 /// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[inline]
-pub fn decode(bytes: &[u8]) -> Result<Cow<str>, DecodingError> {
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, DecodingError> {
     if let Ok(value) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
         Ok(Cow::Borrowed(value))
     } else {
         let string = internal::decode(bytes, DecodeOptions {
@@ -405,106 +607,1680 @@ pub fn decode(bytes: &[u8]) -> Result<Cow<str>, DecodingError> {
             lossy: false,
         })?;
 
+        metrics::record(bytes.len(), string.len(), true);
         Ok(Cow::Owned(string))
     }
 }
 
-/// Encodes a string to MUTF-8.
+/// Converts at most the first `max_len` bytes of a slice of bytes to a string,
+/// and returns it along with the number of bytes that were consumed.
 ///
-/// The algorithm is as follows:
+/// The input is cut at the last character boundary at or before `max_len`, so
+/// a character, or a surrogate pair, is never split in two, and the rest of
+/// the input is never looked at. The consumed part is decoded exactly like
+/// [`decode`]. If `max_len` falls inside the first character, nothing is
+/// consumed.
 ///
-/// - If the input, as UTF-8, is also valid MUTF-8, the function will return
-///   <code>[Cow::Borrowed]\([&\[u8\]][slice]\)</code>.
-/// - If the input, as UTF-8, is not valid MUTF-8, the function will return
-///   <code>[Cow::Owned]\([Vec]<[u8]>\)</code>. This case has the potential to
-///   panic.
+/// # Errors
+///
+/// If the consumed part of the input is not valid MUTF-8 or UTF-8, this
+/// function will return a [`DecodingError`].
 ///
 /// # Panics
 ///
-/// This function will panic if the buffer required to encode the input exceeds
+/// This function will panic if the buffer required to decode the input exceeds
 /// [`isize::MAX`] bytes.
 ///
 /// # Examples
 ///
 /// ```
-/// # extern crate alloc;
-/// use alloc::borrow::Cow;
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// // NOTE: The surrogate pair would be cut by a `max_len` of 4.
+/// let bytes = [0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'a'];
+/// let (decoded, consumed) = mutf8::decode_prefix(&bytes, 4)?;
+/// assert_eq!(decoded, "\0");
+/// assert_eq!(consumed, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_prefix(bytes: &[u8], max_len: usize) -> Result<(Cow<'_, str>, usize), DecodingError> {
+    let len = internal::floor_char_boundary(bytes, max_len);
+    // SAFETY: `len` is at most `bytes.len()`.
+    let prefix = unsafe { bytes.get_unchecked(..len) };
+    decode(prefix).map(|decoded| (decoded, len))
+}
+
+/// Converts a null-terminated slice of MUTF-8 bytes to a string, and returns
+/// it along with the number of bytes that were consumed, including the
+/// terminator.
+///
+/// This is the layout of the string data in Android DEX files, among others.
+/// Since MUTF-8 encodes every null in the string as `0xc0 0x80`, the first
+/// `0x00` byte is always the terminator, and everything after it is never
+/// looked at. Everything before it is decoded exactly like [`decode`].
+///
+/// # Errors
+///
+/// If there is no `0x00` byte in the input, this function will return a
+/// [`DecodingError`] of kind
+/// [`UnexpectedEof`](DecodingErrorKind::UnexpectedEof). If the bytes before the
+/// terminator are not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`] as well.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
 ///
+/// ```
 /// use simd_cesu8::mutf8;
 ///
-/// let null = "\0";
-/// assert_eq!(null.len(), 1);
-/// assert_eq!(null.as_bytes(), &[0x00]);
-/// assert_eq!(mutf8::encode(null), Cow::<[u8]>::Owned(vec![0xc0, 0x80]));
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// // NOTE: The encoded null is part of the string, not its terminator.
+/// let bytes = [b'a', 0xc0, 0x80, b'b', 0x00, b'c', 0x00];
+/// let (decoded, consumed) = mutf8::decode_cstr(&bytes)?;
+/// assert_eq!(decoded, "a\0b");
+/// assert_eq!(consumed, 5);
+///
+/// let (decoded, consumed) = mutf8::decode_cstr(&bytes[consumed..])?;
+/// assert_eq!(decoded, "c");
+/// assert_eq!(consumed, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_cstr(bytes: &[u8]) -> Result<(Cow<'_, str>, usize), DecodingError> {
+    let len = bytes
+        .iter()
+        .position(|&byte| byte == 0x00)
+        .ok_or(DecodingError::new(DecodingErrorKind::UnexpectedEof))?;
+    // SAFETY: `position` never returns an index past the end of `bytes`.
+    let string = unsafe { bytes.get_unchecked(..len) };
+    decode(string).map(|decoded| (decoded, len + 1))
+}
+
+/// Returns an iterator over the characters of a slice of MUTF-8 bytes, decoded
+/// lazily.
 ///
-/// let single_byte  = "\u{0045}";
-/// assert_eq!(single_byte, "E");
-/// assert_eq!(single_byte.len(), 1);
-/// assert_eq!(single_byte.as_bytes(), &[0x45]);
-/// assert_eq!(mutf8::encode(single_byte), Cow::Borrowed(&[0x45]));
+/// Each character is decoded only when the iterator is advanced, so nothing is
+/// allocated, and stopping early never decodes the rest of the input. See
+/// [`DecodeChars`] for details.
 ///
-/// let two_bytes = "\u{0205}";
-/// assert_eq!(two_bytes, "ȅ");
-/// assert_eq!(two_bytes.len(), 2);
-/// assert_eq!(two_bytes.as_bytes(), &[0xc8, 0x85]);
-/// assert_eq!(mutf8::encode(two_bytes), Cow::Borrowed(&[0xc8, 0x85]));
+/// # Examples
 ///
-/// let three_bytes = "\u{20ac}";
-/// assert_eq!(three_bytes, "€");
-/// assert_eq!(three_bytes.len(), 3);
-/// assert_eq!(three_bytes.as_bytes(), &[0xe2, 0x82, 0xac]);
-/// assert_eq!(
-///     mutf8::encode(three_bytes),
-///     Cow::Borrowed(&[0xe2, 0x82, 0xac])
-/// );
+/// ```
+/// use simd_cesu8::mutf8;
 ///
-/// let four_bytes = "\u{10400}";
-/// assert_eq!(four_bytes, "𐐀");
-/// assert_eq!(four_bytes.len(), 4);
-/// assert_eq!(four_bytes.as_bytes(), &[0xf0, 0x90, 0x90, 0x80]);
-/// assert_eq!(
-///     mutf8::encode(four_bytes),
-///     Cow::<[u8]>::Owned(vec![0xed, 0xa0, 0x81, 0xed, 0xb0, 0x80])
-/// );
+/// let bytes = [b'a', 0xc0, 0x80, b'b', 0xff];
+/// let chars = mutf8::decode_chars(&bytes).take_while(Result::is_ok);
+/// assert_eq!(chars.count(), 3);
+///
+/// // NOTE: The invalid byte at the end is never reached.
+/// let found = mutf8::decode_chars(&bytes).find(|char| *char == Ok('\0'));
+/// assert_eq!(found, Some(Ok('\0')));
+/// ```
 #[must_use]
 #[inline]
-pub fn encode(value: &str) -> Cow<[u8]> {
-    if contains_null_or_utf8_4_byte_char_header(value.as_bytes()) {
-        Cow::Owned(internal::encode(value, Flavor::Mutf8))
+pub fn decode_chars(bytes: &[u8]) -> DecodeChars<'_> {
+    DecodeChars::new(bytes, Flavor::Mutf8)
+}
+
+/// Converts a slice of bytes to UTF-16 code units, without going through a
+/// string first.
+///
+/// This function accepts the same input as [`decode`], and returns the UTF-16
+/// encoding of the same string, such as for passing to Java as a `char[]`.
+///
+/// # Errors
+///
+/// This function returns an error in the same cases as [`decode`].
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::string::String;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// let units = mutf8::decode_to_utf16(&bytes)?;
+///
+/// assert_eq!(units, [0x61, 0x00, 0xd83d, 0xdc96]);
+/// assert_eq!(String::from_utf16(&units).unwrap(), "a\0💖");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_to_utf16(bytes: &[u8]) -> Result<Vec<u16>, DecodingError> {
+    let units: Vec<u16> = if let Ok(value) = from_utf8(bytes) {
+        value.encode_utf16().collect()
     } else {
-        Cow::Borrowed(value.as_bytes())
-    }
+        internal::decode_to_utf16(bytes, Flavor::Mutf8)?
+    };
+
+    metrics::record(bytes.len(), units.len() * 2, true);
+    Ok(units)
 }
 
-/// Returns `true` if the input string needs to be encoded to MUTF-8.
+/// Converts a slice of bytes to UTF-16 code units like [`decode_to_utf16`],
+/// but keeps unpaired surrogates instead of rejecting them.
+///
+/// MUTF-8 can encode a lone surrogate as its own 3-byte sequence, which is how
+/// ill-formed UTF-16, such as a Java string with an unpaired surrogate, ends
+/// up encoded. This function passes each lone surrogate through as a single
+/// code unit, so no information is lost, and [`encode_utf16`] turns the code
+/// units back into the exact same bytes.
+///
+/// Surrogates that form a pair are decoded as a pair, just like in
+/// [`decode_to_utf16`], so valid input gives the same code units.
+///
+/// # Errors
+///
+/// This function returns an error in the same cases as [`decode`], except
+/// for a well-formed 3-byte sequence that encodes a lone surrogate.
 ///
 /// # Examples
 ///
 /// ```
 /// use simd_cesu8::mutf8;
 ///
-/// let null = "\0";
-/// assert_eq!(null.len(), 1);
-/// assert!(mutf8::needs_encoded(null));
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// // NOTE: The high surrogate isn't followed by a low surrogate.
+/// let bytes = [b'a', 0xed, 0xa0, 0xbd, b'b'];
+/// assert!(mutf8::decode_to_utf16(&bytes).is_err());
 ///
-/// let single_byte = "E";
-/// assert_eq!(single_byte.len(), 1);
-/// assert!(!mutf8::needs_encoded(single_byte));
+/// let units = mutf8::decode_to_utf16_lossless(&bytes)?;
+/// assert_eq!(units, [0x61, 0xd83d, 0x62]);
+/// assert_eq!(mutf8::encode_utf16(&units), bytes);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_to_utf16_lossless(bytes: &[u8]) -> Result<Vec<u16>, DecodingError> {
+    let units: Vec<u16> = if let Ok(value) = from_utf8(bytes) {
+        value.encode_utf16().collect()
+    } else {
+        internal::decode_to_utf16_lossless(bytes, Flavor::Mutf8)?
+    };
+
+    metrics::record(bytes.len(), units.len() * 2, true);
+    Ok(units)
+}
+
+/// Converts a slice of bytes to a string, returning an error instead of
+/// aborting when the output can't be allocated.
 ///
-/// let two_bytes = "ȅ";
-/// assert_eq!(two_bytes.len(), 2);
-/// assert!(!mutf8::needs_encoded(two_bytes));
+/// This function accepts the same input as [`decode`], and returns the same
+/// string. It's meant for servers that handle untrusted input, where running
+/// out of memory must be handled rather than abort the process.
 ///
-/// let three_bytes = "€";
-/// assert_eq!(three_bytes.len(), 3);
-/// assert!(!mutf8::needs_encoded(three_bytes));
+/// # Errors
+///
+/// If the input isn't valid, this function will return
+/// [`TryDecodeError::Decoding`], in the same cases as [`decode`]. If the
+/// output can't be allocated, it will return [`TryDecodeError::Allocation`].
+///
+/// # Examples
 ///
-/// let four_bytes = "𐐀";
-/// assert_eq!(four_bytes.len(), 4);
-/// assert!(mutf8::needs_encoded(four_bytes));
 /// ```
-#[must_use]
+/// use simd_cesu8::{mutf8, TryDecodeError};
+///
+/// # fn main() -> Result<(), TryDecodeError> {
+/// let bytes = [0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// assert_eq!(mutf8::try_decode(&bytes)?, "\0💖");
+///
+/// let error = mutf8::try_decode(&[0xed, 0xa0, 0xbd]).unwrap_err();
+/// assert!(matches!(error, TryDecodeError::Decoding(_)));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[inline]
-pub fn needs_encoded(value: &str) -> bool {
-    contains_null_or_utf8_4_byte_char_header(value.as_bytes())
+pub fn try_decode(bytes: &[u8]) -> Result<Cow<'_, str>, TryDecodeError> {
+    if let Ok(string) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
+        Ok(Cow::Borrowed(string))
+    } else {
+        let string = internal::try_decode(bytes, Flavor::Mutf8)?;
+        metrics::record(bytes.len(), string.len(), true);
+        Ok(Cow::Owned(string))
+    }
+}
+
+/// Converts a slice of bytes to a string, reporting where decoding failed.
+///
+/// This function accepts the same input as [`decode`], and returns the same
+/// string. On error, though, it returns a [`DecodingErrorAt`] instead of the
+/// zero-sized [`DecodingError`], which carries the offset of the invalid
+/// sequence, the byte at that offset, and the [`DecodingErrorKind`]. Working
+/// that out costs nothing until an error occurs, but prefer [`decode`] when
+/// you don't need it.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingErrorAt`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::{mutf8, DecodingErrorKind};
+///
+/// let bytes = [b'a', b'b', 0xed, 0xa0, 0xbd, b'c'];
+/// let error = mutf8::decode_verbose(&bytes).unwrap_err();
+///
+/// assert_eq!(error.offset(), 2);
+/// assert_eq!(error.byte(), 0xed);
+/// assert_eq!(error.kind(), DecodingErrorKind::InvalidSurrogatePair);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_verbose(bytes: &[u8]) -> Result<Cow<'_, str>, DecodingErrorAt> {
+    match compat::from_utf8(bytes) {
+        Ok(string) => {
+            metrics::record(bytes.len(), bytes.len(), false);
+            Ok(Cow::Borrowed(string))
+        }
+        Err(error) => {
+            let string = internal::decode_verbose(bytes, Flavor::Mutf8, error.valid_up_to())?;
+            metrics::record(bytes.len(), string.len(), true);
+            Ok(Cow::Owned(string))
+        }
+    }
+}
+
+/// Converts a slice of bytes to a string, appending it to `out`.
+///
+/// This function accepts the same input as [`decode`], and appends the same
+/// string that [`decode`] would return. Nothing in `out` is cleared, so
+/// decoding many records into one reused string doesn't allocate once it has
+/// grown large enough.
+///
+/// Room for the whole decoded string is reserved up front, so `out` is
+/// reallocated at most once per call, even when it already holds data. Since
+/// decoding never makes the input longer, [`decoded_len_bound`] bytes of room
+/// are always enough.
+///
+/// The algorithm is as follows:
+///
+/// - If the input is valid UTF-8, it is appended to `out` as-is.
+/// - Otherwise, the input is decoded directly into `out`. This case has the
+///   potential to panic.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`].
+///
+/// **NOTE:** When an error is returned, `out` may already contain the part of
+/// the string before the invalid sequence.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::string::String;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let mut out = String::new();
+/// mutf8::decode_into(&[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96], &mut out)?;
+/// mutf8::decode_into(&[b'a', 0xc0, 0x80], &mut out)?;
+/// assert_eq!(out, "💖a\0");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_into(bytes: &[u8], out: &mut String) -> Result<(), DecodingError> {
+    if let Ok(string) = from_utf8(bytes) {
+        out.push_str(string);
+        metrics::record(bytes.len(), bytes.len(), false);
+    } else {
+        let start = out.len();
+        let options = DecodeOptions {
+            flavor: Flavor::Mutf8,
+            lossy: false,
+        };
+        internal::decode_into(bytes, options, out)?;
+        metrics::record(bytes.len(), out.len() - start, true);
+    }
+
+    Ok(())
+}
+
+/// Converts a mutable slice of bytes to a string in place, without allocating.
+///
+/// This function accepts the same input as [`decode`]. Decoding MUTF-8 to UTF-8
+/// never makes the input longer, so the decoded string is written over the
+/// start of `bytes`, and that prefix is returned. This is useful when you
+/// already own a mutable buffer, such as one handed over through JNI.
+///
+/// The algorithm is as follows:
+///
+/// - If the input is valid UTF-8, the whole slice is returned as-is.
+/// - Otherwise, the input is validated, and then decoded in place. Every
+///   surrogate pair shrinks from 6 bytes to 4, and every null shrinks from 2
+///   bytes to 1, so the returned string may be shorter than `bytes`, and the
+///   bytes after it are left unspecified.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`], and `bytes` will be left untouched.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let mut bytes = [0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// let decoded = simd_cesu8::mutf8::decode_in_place(&mut bytes)?;
+/// assert_eq!(decoded, "\0💖");
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn decode_in_place(bytes: &mut [u8]) -> Result<&mut str, DecodingError> {
+    let len = bytes.len();
+
+    if from_utf8(bytes).is_ok() {
+        metrics::record(len, len, false);
+        // SAFETY: We just checked that `bytes` is valid UTF-8.
+        Ok(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
+    } else {
+        let decoded = internal::decode_in_place(bytes, Flavor::Mutf8)?;
+        metrics::record(len, decoded.len(), true);
+        Ok(decoded)
+    }
+}
+
+/// Converts a slice of bytes to an atomically reference-counted string.
+///
+/// This function accepts the same input as [`decode`], but always returns an
+/// <code>[Arc]<[str]></code>, which is useful when the decoded string is
+/// shared across threads, such as in a read-mostly cache.
+///
+/// **NOTE:** An `Arc` stores its reference counts in the same allocation as
+/// its data, so the decoded string is always copied once into the `Arc`, even
+/// if [`decode`] would have returned <code>[Cow::Owned]\([String]\)</code>.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, the function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::sync::Arc;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0xc0, 0x80];
+/// let decoded = mutf8::decode_arc(&bytes)?;
+/// assert_eq!(decoded, Arc::from("\0"));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", target_has_atomic = "ptr"))))]
+#[inline]
+pub fn decode_arc(bytes: &[u8]) -> Result<Arc<str>, DecodingError> {
+    decode(bytes).map(Arc::from)
+}
+
+/// Converts a slice of bytes to a reference-counted string.
+///
+/// This function is the single-threaded equivalent of [`decode_arc`]. It
+/// accepts the same input as [`decode`], but always returns an
+/// <code>[Rc]<[str]></code>.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, the function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::rc::Rc;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0xc0, 0x80];
+/// let decoded = mutf8::decode_rc(&bytes)?;
+/// assert_eq!(decoded, Rc::from("\0"));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_rc(bytes: &[u8]) -> Result<Rc<str>, DecodingError> {
+    decode(bytes).map(Rc::from)
+}
+
+/// Converts a [`Bytes`](bytes::Bytes) to a string that shares its allocation
+/// whenever it can.
+///
+/// This function accepts the same input as [`decode`]. Whenever [`decode`]
+/// would return <code>[Cow::Borrowed]\(&[str]\)</code>, the returned
+/// [`BytesStr`](crate::BytesStr) is a cheap, reference-counted clone of
+/// `input`, so the string is never copied. Otherwise, the decoded string is
+/// moved into a new [`Bytes`](bytes::Bytes).
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let input = Bytes::from_static(&[b'a', 0xc0, 0x80, b'b']);
+/// assert_eq!(mutf8::decode_bytes(&input)?, "a\0b");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+#[inline]
+pub fn decode_bytes(input: &bytes::Bytes) -> Result<crate::BytesStr, DecodingError> {
+    let bytes = match decode(input)? {
+        Cow::Borrowed(_) => input.clone(),
+        Cow::Owned(string) => bytes::Bytes::from(string),
+    };
+
+    Ok(crate::BytesStr::from_utf8_unchecked(bytes))
+}
+
+/// Converts a slice of bytes to a byte string, including invalid characters.
+///
+/// This function decodes the input exactly like [`decode_lossy`], but returns
+/// a [`BStr`](bstr::BStr) instead of a [`str`], which is convenient when the
+/// result is only going to be displayed or logged alongside other byte
+/// strings. If [`decode_lossy`] would borrow the input, so does this function.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let bytes = [b'a', 0xc0, 0x80, 0xff];
+/// let decoded = mutf8::decode_bstr_lossy(&bytes);
+/// assert_eq!(&*decoded, "a\0�");
+/// ```
+#[cfg(feature = "bstr")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bstr")))]
+#[must_use]
+#[inline]
+pub fn decode_bstr_lossy(bytes: &[u8]) -> Cow<'_, bstr::BStr> {
+    match decode_lossy(bytes) {
+        Cow::Borrowed(string) => Cow::Borrowed(bstr::BStr::new(string)),
+        Cow::Owned(string) => Cow::Owned(bstr::BString::from(string)),
+    }
+}
+
+/// Converts a slice of bytes to a string, decoding large inputs in parallel.
+///
+/// This is the MUTF-8 counterpart of [`decode_par`](crate::decode_par). It
+/// accepts the same input as [`decode`], and returns the same string, but
+/// always as an owned [`String`].
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes: Vec<u8> = [b'a', 0xc0, 0x80].repeat(100_000);
+/// let decoded = mutf8::decode_par(&bytes)?;
+/// assert_eq!(decoded, "a\0".repeat(100_000));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[inline]
+pub fn decode_par(bytes: &[u8]) -> Result<String, DecodingError> {
+    if let Ok(string) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
+        Ok(String::from(string))
+    } else {
+        let string = crate::par::decode(bytes, Flavor::Mutf8)?;
+        metrics::record(bytes.len(), string.len(), true);
+        Ok(string)
+    }
+}
+
+/// Converts a slice of bytes to a UTF-8 byte vector allocated with a custom
+/// allocator.
+///
+/// This function accepts the same input as [`decode`], but the decoded string
+/// is always written into a fresh <code>[Vec]<[u8], A></code> that uses
+/// `alloc`, such as an arena or bump allocator. The returned vector is
+/// guaranteed to be valid UTF-8.
+///
+/// **NOTE:** This function requires the `allocator_api` feature, which in turn
+/// requires a nightly compiler.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, the function will return a
+/// [`DecodingError`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(allocator_api)]
+/// # extern crate alloc;
+/// use alloc::alloc::Global;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0xc0, 0x80];
+/// let decoded = mutf8::decode_in(&bytes, Global)?;
+/// assert_eq!(core::str::from_utf8(&decoded), Ok("\0"));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "allocator_api")]
+#[cfg_attr(docsrs, doc(cfg(feature = "allocator_api")))]
+#[inline]
+pub fn decode_in<A: Allocator>(bytes: &[u8], alloc: A) -> Result<Vec<u8, A>, DecodingError> {
+    if from_utf8(bytes).is_ok() {
+        let mut decoded = Vec::with_capacity_in(bytes.len(), alloc);
+        decoded.extend_from_slice(bytes);
+        metrics::record(bytes.len(), bytes.len(), false);
+        Ok(decoded)
+    } else {
+        let decoded = internal::decode_in(
+            bytes,
+            DecodeOptions {
+                flavor: Flavor::Mutf8,
+                lossy: false,
+            },
+            alloc,
+        )?;
+        metrics::record(bytes.len(), decoded.len(), true);
+        Ok(decoded)
+    }
+}
+
+/// Encodes a string to MUTF-8.
+///
+/// The algorithm is as follows:
+///
+/// - If the input, as UTF-8, is also valid MUTF-8, the function will return
+///   <code>[Cow::Borrowed]\([&\[u8\]][slice]\)</code>.
+/// - If the input, as UTF-8, is not valid MUTF-8, the function will return
+///   <code>[Cow::Owned]\([Vec]<[u8]>\)</code>. This case has the potential to
+///   panic.
+///
+/// **NOTE:** To avoid counting the output first, the returned vector is
+/// allocated with room for twice the length of the input, and only shrunk when
+/// that leaves 4 KiB or more unused. A short, mostly-ASCII string can thus keep
+/// up to twice the capacity it needs for as long as the vector lives. Use
+/// [`encode_exact`] if that matters more than the speed of encoding.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::borrow::Cow;
+///
+/// use simd_cesu8::mutf8;
+///
+/// let null = "\0";
+/// assert_eq!(null.len(), 1);
+/// assert_eq!(null.as_bytes(), &[0x00]);
+/// assert_eq!(mutf8::encode(null), Cow::<[u8]>::Owned(vec![0xc0, 0x80]));
+///
+/// let single_byte  = "\u{0045}";
+/// assert_eq!(single_byte, "E");
+/// assert_eq!(single_byte.len(), 1);
+/// assert_eq!(single_byte.as_bytes(), &[0x45]);
+/// assert_eq!(mutf8::encode(single_byte), Cow::Borrowed(&[0x45]));
+///
+/// let two_bytes = "\u{0205}";
+/// assert_eq!(two_bytes, "ȅ");
+/// assert_eq!(two_bytes.len(), 2);
+/// assert_eq!(two_bytes.as_bytes(), &[0xc8, 0x85]);
+/// assert_eq!(mutf8::encode(two_bytes), Cow::Borrowed(&[0xc8, 0x85]));
+///
+/// let three_bytes = "\u{20ac}";
+/// assert_eq!(three_bytes, "€");
+/// assert_eq!(three_bytes.len(), 3);
+/// assert_eq!(three_bytes.as_bytes(), &[0xe2, 0x82, 0xac]);
+/// assert_eq!(
+///     mutf8::encode(three_bytes),
+///     Cow::Borrowed(&[0xe2, 0x82, 0xac])
+/// );
+///
+/// let four_bytes = "\u{10400}";
+/// assert_eq!(four_bytes, "𐐀");
+/// assert_eq!(four_bytes.len(), 4);
+/// assert_eq!(four_bytes.as_bytes(), &[0xf0, 0x90, 0x90, 0x80]);
+/// assert_eq!(
+///     mutf8::encode(four_bytes),
+///     Cow::<[u8]>::Owned(vec![0xed, 0xa0, 0x81, 0xed, 0xb0, 0x80])
+/// );
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[must_use]
+#[inline]
+pub fn encode(value: &str) -> Cow<'_, [u8]> {
+    if contains_null_or_utf8_4_byte_char_header(value.as_bytes()) {
+        let encoded = internal::encode(value, Flavor::Mutf8);
+        metrics::record(value.len(), encoded.len(), true);
+        Cow::Owned(encoded)
+    } else {
+        metrics::record(value.len(), value.len(), false);
+        Cow::Borrowed(value.as_bytes())
+    }
+}
+
+/// Encodes a string to MUTF-8, allocating exactly as much room as the output
+/// needs.
+///
+/// This function returns the same bytes as [`encode`]. Instead of speculating
+/// on the capacity, it counts the exact length of the output first, so an
+/// owned result never has spare capacity. That makes it slower than
+/// [`encode`], especially for long inputs, but it's the better choice when
+/// the result is kept around for a long time.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let encoded = mutf8::encode_exact(&("a".repeat(100) + "\0💖")).into_owned();
+/// assert_eq!(encoded.len(), 108);
+/// assert_eq!(encoded.capacity(), 108);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[must_use]
+#[inline]
+pub fn encode_exact(value: &str) -> Cow<'_, [u8]> {
+    if contains_null_or_utf8_4_byte_char_header(value.as_bytes()) {
+        let encoded = internal::encode_exact(value, Flavor::Mutf8);
+        metrics::record(value.len(), encoded.len(), true);
+        Cow::Owned(encoded)
+    } else {
+        metrics::record(value.len(), value.len(), false);
+        Cow::Borrowed(value.as_bytes())
+    }
+}
+
+/// Converts a string to MUTF-8 bytes, returning an error instead of panicking
+/// when the output doesn't fit in memory.
+///
+/// This function returns the same bytes as [`encode`]. Instead of speculating
+/// on the capacity, it counts the exact length of the output first, and
+/// reports an error if that exceeds [`isize::MAX`] bytes or can't be
+/// allocated.
+///
+/// # Errors
+///
+/// If the encoded output would exceed [`isize::MAX`] bytes, or can't be
+/// allocated, this function will return an [`EncodeError`].
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::EncodeError> {
+/// assert_eq!(*mutf8::try_encode("a")?, *b"a");
+/// assert_eq!(*mutf8::try_encode("\0")?, [0xc0, 0x80]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn try_encode(value: &str) -> Result<Cow<'_, [u8]>, EncodeError> {
+    if contains_null_or_utf8_4_byte_char_header(value.as_bytes()) {
+        let encoded = internal::try_encode(value, Flavor::Mutf8)?;
+        metrics::record(value.len(), encoded.len(), true);
+        Ok(Cow::Owned(encoded))
+    } else {
+        metrics::record(value.len(), value.len(), false);
+        Ok(Cow::Borrowed(value.as_bytes()))
+    }
+}
+
+/// Encodes a string to MUTF-8, appending the bytes to `out`.
+///
+/// This function produces the same bytes as [`encode`], but writes them into
+/// a vector you own, so encoding many strings into one buffer reuses its
+/// capacity instead of allocating for each one.
+///
+/// Room for the whole encoded string is reserved up front, so `out` is
+/// reallocated at most once per call. When `out` already holds data, only
+/// [`encoded_len`] bytes are reserved, so assembling many values into one
+/// buffer doesn't leave the slack of each one behind. When it's empty and
+/// `value` is short, that room is twice the length of `value` instead, which
+/// always fits the encoded string without counting it first.
+///
+/// The algorithm is as follows:
+///
+/// - If the input, as UTF-8, is also valid MUTF-8, it is appended to `out`
+///   as-is.
+/// - Otherwise, the input is encoded directly into `out`. This case has the
+///   potential to panic.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// let mut out = Vec::new();
+/// mutf8::encode_into("a\0", &mut out);
+/// mutf8::encode_into("💖", &mut out);
+/// assert_eq!(out, [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn encode_into(value: &str, out: &mut Vec<u8>) {
+    if needs_encoded(value) {
+        let start = out.len();
+        internal::encode_to(value, Flavor::Mutf8, out);
+        metrics::record(value.len(), out.len() - start, true);
+    } else {
+        out.extend_from_slice(value.as_bytes());
+        metrics::record(value.len(), value.len(), false);
+    }
+}
+
+/// Encodes many strings to MUTF-8 into one contiguous buffer, returning the
+/// buffer along with the `(offset, len)` of each encoded string in it.
+///
+/// This is the shape of a string pool, such as a constant pool or a string
+/// table: each string is encoded with [`encode_into`], one after another, and
+/// the span at index `n` covers exactly the bytes that [`encode`] would return
+/// for `values[n]`.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let (pool, spans) = mutf8::encode_pool(&["a", "\0", "💖"]);
+/// assert_eq!(pool, [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// assert_eq!(spans, [(0, 1), (1, 2), (3, 6)]);
+/// assert_eq!(&pool[spans[1].0..][..spans[1].1], [0xc0, 0x80]);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[must_use]
+#[inline]
+pub fn encode_pool(values: &[&str]) -> (Vec<u8>, Vec<(usize, usize)>) {
+    // NOTE: Every string is at least as long encoded, so this is never too
+    // much room, and most inputs need no more.
+    let mut pool = Vec::with_capacity(
+        values
+            .iter()
+            .map(|value| value.len())
+            .fold(0, usize::saturating_add),
+    );
+    let mut spans = Vec::with_capacity(values.len());
+
+    for value in values {
+        let offset = pool.len();
+        encode_into(value, &mut pool);
+        spans.push((offset, pool.len() - offset));
+    }
+
+    (pool, spans)
+}
+
+/// Returns an iterator over the bytes of a string encoded to MUTF-8, produced
+/// lazily.
+///
+/// The iterator produces exactly the bytes that [`encode`] would return, but
+/// one at a time, without allocating. See [`EncodeBytes`] for details.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// let encoded: Vec<u8> = mutf8::encode_bytes("a\0").collect();
+/// assert_eq!(encoded, [b'a', 0xc0, 0x80]);
+/// ```
+#[must_use]
+#[inline]
+pub fn encode_bytes(value: &str) -> EncodeBytes<'_> {
+    EncodeBytes::new(value, Flavor::Mutf8)
+}
+
+/// Encodes UTF-16 code units to MUTF-8, without going through UTF-8 first.
+///
+/// MUTF-8 is defined as the UTF-8 encoding of each UTF-16 code unit on its
+/// own, with null encoded as `0xc0 0x80`, so each code unit is encoded
+/// directly: a surrogate pair becomes two 3-byte sequences, just like
+/// [`encode`] produces for a 4-byte character. This is how Java encodes a
+/// `char[]`.
+///
+/// Lone surrogates aren't rejected. Each one is encoded as a 3-byte sequence,
+/// the same as Java does, so ill-formed UTF-16 is encoded without losing
+/// anything. Only [`decode_to_utf16_lossless`] decodes it back, since every
+/// other decoding function rejects a lone surrogate.
+///
+/// # Panics
+///
+/// This function will panic if the encoded output exceeds [`isize::MAX`]
+/// bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// let units: Vec<u16> = "\0💖".encode_utf16().collect();
+/// let encoded = mutf8::encode_utf16(&units);
+///
+/// assert_eq!(encoded, [0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// assert_eq!(encoded, *mutf8::encode("\0💖"));
+///
+/// // NOTE: This high surrogate isn't followed by a low surrogate.
+/// let encoded = mutf8::encode_utf16(&[0xd83d, 0x62]);
+///
+/// assert_eq!(encoded, [0xed, 0xa0, 0xbd, b'b']);
+/// assert!(mutf8::decode(&encoded).is_err());
+/// assert_eq!(
+///     mutf8::decode_to_utf16_lossless(&encoded),
+///     Ok(Vec::from([0xd83d, 0x62]))
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[must_use]
+#[inline]
+pub fn encode_utf16(units: &[u16]) -> Vec<u8> {
+    let encoded = internal::encode_utf16(units, Flavor::Mutf8);
+    metrics::record(units.len() * 2, encoded.len(), true);
+    encoded
+}
+
+/// Returns `true` if the input string needs to be encoded to MUTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let null = "\0";
+/// assert_eq!(null.len(), 1);
+/// assert!(mutf8::needs_encoded(null));
+///
+/// let single_byte = "E";
+/// assert_eq!(single_byte.len(), 1);
+/// assert!(!mutf8::needs_encoded(single_byte));
+///
+/// let two_bytes = "ȅ";
+/// assert_eq!(two_bytes.len(), 2);
+/// assert!(!mutf8::needs_encoded(two_bytes));
+///
+/// let three_bytes = "€";
+/// assert_eq!(three_bytes.len(), 3);
+/// assert!(!mutf8::needs_encoded(three_bytes));
+///
+/// let four_bytes = "𐐀";
+/// assert_eq!(four_bytes.len(), 4);
+/// assert!(mutf8::needs_encoded(four_bytes));
+/// ```
+#[must_use]
+#[inline]
+pub fn needs_encoded(value: &str) -> bool {
+    contains_null_or_utf8_4_byte_char_header(value.as_bytes())
+}
+
+/// Returns `true` if the input string needs to be encoded to MUTF-8, like
+/// [`needs_encoded`], but can be evaluated in a constant.
+///
+/// This scans the input one byte at a time instead of using the fastest
+/// implementation for the target, so prefer [`needs_encoded`] at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// const NAMES: [&str; 2] = ["ȅ€", "a\0"];
+/// const NEEDS_ENCODED: [bool; 2] = [
+///     mutf8::needs_encoded_const(NAMES[0]),
+///     mutf8::needs_encoded_const(NAMES[1]),
+/// ];
+///
+/// assert_eq!(NEEDS_ENCODED, [false, true]);
+/// assert_eq!(NEEDS_ENCODED[1], mutf8::needs_encoded(NAMES[1]));
+/// ```
+#[must_use]
+#[inline]
+pub const fn needs_encoded_const(value: &str) -> bool {
+    crate::literal::needs_encoded(value, Flavor::Mutf8)
+}
+
+/// Returns exactly how many bytes the MUTF-8 encoding of the input will take
+/// up, without encoding it.
+///
+/// Every 4-byte character becomes a 6-byte surrogate pair, so this is the
+/// UTF-8 length plus 2 bytes for each 4-byte character, plus 1 byte for each
+/// null. Together with [`encode_to_slice`] or [`encode_into`], this lets you
+/// allocate a buffer of exactly the right size once.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let value = "a\0💖";
+/// assert_eq!(mutf8::encoded_len(value), 9);
+///
+/// let mut buffer = [0; 9];
+/// assert_eq!(mutf8::encode_to_slice(value, &mut buffer), Ok(9));
+/// ```
+#[must_use]
+#[inline]
+pub fn encoded_len(value: &str) -> usize {
+    internal::encoded_len(value.as_bytes(), Flavor::Mutf8)
+}
+
+/// Returns an upper bound on the UTF-8 length of the string that decoding the
+/// input would produce, without decoding it.
+///
+/// Decoding MUTF-8 never makes the input longer: a surrogate pair shrinks from
+/// 6 bytes to 4, an encoded null shrinks from 2 bytes to 1, and everything else
+/// stays the same length. So the bound is simply the length of the input.
+/// Together with [`decode_to_slice`] or [`decode_into`], this lets you reserve
+/// enough room for the decoded string once.
+///
+/// **NOTE:** This only bounds input that decodes successfully. Lossy decoding
+/// can produce up to 3 bytes of U+FFFD for each invalid byte.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodeToSliceError> {
+/// let bytes = [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// assert_eq!(mutf8::decoded_len_bound(&bytes), 9);
+///
+/// let mut buffer = [0; 9];
+/// let decoded = mutf8::decode_to_slice(&bytes, &mut buffer)?;
+/// assert_eq!(decoded, "a\0💖");
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+#[inline]
+pub const fn decoded_len_bound(bytes: &[u8]) -> usize {
+    bytes.len()
+}
+
+/// Returns the number of 4-byte characters in the input, each of which is
+/// encoded as a surrogate pair in MUTF-8.
+///
+/// This doesn't count nulls, which are also encoded differently in MUTF-8; see
+/// [`null_count`] for those.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// assert_eq!(mutf8::surrogate_header_count("Hello, world!"), 0);
+/// assert_eq!(mutf8::surrogate_header_count("a💖\0💖"), 2);
+/// ```
+#[must_use]
+#[inline]
+pub fn surrogate_header_count(value: &str) -> usize {
+    internal::surrogate_header_count(value.as_bytes())
+}
+
+/// Returns the number of nulls in the input, each of which is encoded as
+/// `0xc0 0x80` in MUTF-8.
+///
+/// Together with [`surrogate_header_count`], this tells you exactly what
+/// encoding the input would change: the encoded length is the UTF-8 length
+/// plus 2 bytes for each 4-byte character, plus 1 byte for each null, which is
+/// what [`encoded_len`] returns.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let value = "a\0💖\0";
+/// assert_eq!(mutf8::null_count(value), 2);
+/// assert_eq!(
+///     mutf8::encoded_len(value),
+///     value.len() + 2 * mutf8::surrogate_header_count(value) + mutf8::null_count(value)
+/// );
+/// ```
+#[must_use]
+#[inline]
+pub fn null_count(value: &str) -> usize {
+    internal::null_count(value.as_bytes())
+}
+
+/// Returns `true` if the input contains a surrogate pair, without decoding it.
+///
+/// This is the decoding counterpart of [`needs_encoded`], and behaves exactly
+/// like [`contains_surrogate_pair`](crate::contains_surrogate_pair), since
+/// MUTF-8 encodes characters outside of the Basic Multilingual Plane the same
+/// way CESU-8 does.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// assert!(!mutf8::contains_surrogate_pair(&[
+///     b'a', 0xc0, 0x80, 0xe2, 0x82, 0xac
+/// ]));
+/// assert!(mutf8::contains_surrogate_pair(&[
+///     b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96
+/// ]));
+/// ```
+#[must_use]
+#[inline]
+pub fn contains_surrogate_pair(bytes: &[u8]) -> bool {
+    internal::contains_surrogate_pair(bytes)
+}
+
+/// Returns `true` if the input is valid MUTF-8, without decoding it.
+///
+/// This runs the same checks as [`decode_strict`], but pushes nothing and
+/// stops at the first invalid sequence, so it never allocates. Just like
+/// [`decode_strict`], valid UTF-8 that is not valid MUTF-8 is rejected, and so
+/// is a raw null byte, which MUTF-8 always encodes as `0xc0 0x80`.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// assert!(mutf8::is_valid_mutf8(b"Hello, world!"));
+/// assert!(mutf8::is_valid_mutf8(&[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]));
+///
+/// // NOTE: This is a valid UTF-8 string, but not valid MUTF-8.
+/// assert!(!mutf8::is_valid_mutf8(&[0xf0, 0x9f, 0x92, 0x96]));
+/// assert!(!mutf8::is_valid_mutf8(&[0xed, 0xa0, 0xbd]));
+///
+/// assert!(mutf8::is_valid_mutf8(&[b'a', 0xc0, 0x80, b'b']));
+/// assert!(!mutf8::is_valid_mutf8(b"a\0b"));
+/// ```
+#[must_use]
+#[inline]
+pub fn is_valid_mutf8(bytes: &[u8]) -> bool {
+    internal::validate(bytes, Flavor::Mutf8)
+}
+
+/// Encodes a string to MUTF-8, passing the output to `sink` in contiguous runs
+/// instead of collecting it.
+///
+/// This is a push-based alternative to [`encode`] that never allocates, which
+/// is useful when the encoded bytes are only forwarded somewhere else, such as
+/// to a hasher and a socket at the same time. Concatenating every run passed
+/// to `sink` gives exactly the bytes that [`encode`] would return.
+///
+/// The runs are as follows:
+///
+/// - Runs of characters that are the same in UTF-8 and MUTF-8 are borrowed from
+///   `value` as-is, without copying them.
+/// - Every 4-byte character is passed as its own 6-byte surrogate pair.
+/// - Every null is passed as its own `[0xc0, 0x80]` run.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// let mut encoded = Vec::new();
+/// let mut runs = 0;
+///
+/// mutf8::encode_to_sink("a💖b", |run| {
+///     encoded.extend_from_slice(run);
+///     runs += 1;
+/// });
+///
+/// assert_eq!(encoded, [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'b']);
+/// assert_eq!(runs, 3);
+/// ```
+#[inline]
+pub fn encode_to_sink(value: &str, sink: impl FnMut(&[u8])) {
+    let len = internal::encode_to_sink(value, Flavor::Mutf8, sink);
+    metrics::record(value.len(), len, false);
+}
+
+/// Encodes a string as MUTF-8, writing it straight to `writer` instead of
+/// collecting it.
+///
+/// This is [`encode_to_sink`] for an [`io::Write`](std::io::Write) sink: the
+/// encoded bytes are written in runs as they are produced, so no intermediate
+/// buffer is ever allocated. The bytes written are exactly the bytes that
+/// [`encode`] would return.
+///
+/// **NOTE:** Wrap `writer` in a [`BufWriter`](std::io::BufWriter) if it is
+/// expensive to write to, since a string with many 4-byte characters or nulls
+/// is written in many small runs.
+///
+/// # Errors
+///
+/// If writing to `writer` fails, this function returns the error and stops
+/// writing. By then, some of the encoded bytes may have been written already.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut encoded = Vec::new();
+///
+/// mutf8::encode_to_writer(&mut encoded, "a💖b")?;
+/// assert_eq!(encoded, [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'b']);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[inline]
+pub fn encode_to_writer<W: std::io::Write + ?Sized>(
+    writer: &mut W,
+    value: &str,
+) -> std::io::Result<()> {
+    let len = crate::io::encode_to_writer(value, Flavor::Mutf8, writer)?;
+    metrics::record(value.len(), len, false);
+    Ok(())
+}
+
+/// Decodes a slice of bytes, passing the UTF-8 output to `sink` in runs instead
+/// of collecting it.
+///
+/// This function accepts the same input as [`decode`], but never allocates.
+/// Valid UTF-8 input is passed to `sink` as a single run. Otherwise, the
+/// decoded string is collected in a small buffer on the stack and passed to
+/// `sink` whenever it fills up. Every run is a complete string on its own, and
+/// concatenating them gives exactly the string that [`decode`] would return.
+///
+/// # Errors
+///
+/// If the input is not valid MUTF-8 or UTF-8, this function will return a
+/// [`DecodingError`].
+///
+/// **NOTE:** Runs are passed to `sink` as decoding goes, so by the time an
+/// error is returned, `sink` may have already been called with some of the
+/// string before the invalid sequence.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::string::String;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'b'];
+/// let mut decoded = String::new();
+///
+/// mutf8::decode_to_sink(&bytes, |run| decoded.push_str(run))?;
+/// assert_eq!(decoded, "a💖b");
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn decode_to_sink(bytes: &[u8], mut sink: impl FnMut(&str)) -> Result<(), DecodingError> {
+    if let Ok(string) = from_utf8(bytes) {
+        sink(string);
+        metrics::record(bytes.len(), bytes.len(), false);
+        return Ok(());
+    }
+
+    let mut len = 0;
+    let mut buffer = internal::SinkBuffer::new(|run: &str| {
+        len += run.len();
+        sink(run);
+    });
+    let options = DecodeOptions {
+        flavor: Flavor::Mutf8,
+        lossy: false,
+    };
+    internal::decode_to(bytes, options, &mut buffer)?;
+    buffer.flush();
+
+    metrics::record(bytes.len(), len, false);
+    Ok(())
+}
+
+/// Encodes a string to MUTF-8, writing the bytes into a buffer you own.
+///
+/// This function produces the same bytes as [`encode`], but never allocates,
+/// so it can be used where there's no allocator at all. Use
+/// [`encoded_len`] to find out how large `out` needs to be.
+///
+/// # Errors
+///
+/// If the encoded output doesn't fit in `out`, this function will return a
+/// [`BufferTooSmall`], and `out` is left untouched.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let mut out = [0; 9];
+/// let len = mutf8::encode_to_slice("a\0💖", &mut out).unwrap();
+/// assert_eq!(out[..len], [
+///     b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96
+/// ]);
+///
+/// let mut small = [0; 8];
+/// assert!(mutf8::encode_to_slice("a\0💖", &mut small).is_err());
+/// ```
+#[inline]
+pub fn encode_to_slice(value: &str, out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    if needs_encoded(value) {
+        let len = internal::encode_to_slice(value, Flavor::Mutf8, out)?;
+        metrics::record(value.len(), len, true);
+        Ok(len)
+    } else {
+        let out = out.get_mut(..value.len()).ok_or(BufferTooSmall(()))?;
+        out.copy_from_slice(value.as_bytes());
+        metrics::record(value.len(), value.len(), false);
+        Ok(value.len())
+    }
+}
+
+/// Decodes a slice of bytes, writing the UTF-8 output into a buffer you own.
+///
+/// This function accepts the same input as [`decode`], but never allocates,
+/// so it can be used where there's no allocator at all. The decoded string is
+/// never longer than the input, so an `out` that is at least as long as
+/// `bytes` is always large enough.
+///
+/// # Errors
+///
+/// This function will return a [`DecodeToSliceError`] if:
+///
+/// - The input is not valid MUTF-8 or UTF-8 ([`DecodeToSliceError::Decoding`]).
+///   This takes precedence over the output not fitting.
+/// - The decoded string doesn't fit in `out`
+///   ([`DecodeToSliceError::BufferTooSmall`]).
+///
+/// **NOTE:** When an error is returned, `out` may have already been written
+/// to.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodeToSliceError> {
+/// let bytes = [b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// let mut out = [0; 16];
+/// assert_eq!(mutf8::decode_to_slice(&bytes, &mut out)?, "a\0💖");
+///
+/// let mut small = [0; 5];
+/// assert!(mutf8::decode_to_slice(&bytes, &mut small).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn decode_to_slice<'a>(bytes: &[u8], out: &'a mut [u8]) -> Result<&'a str, DecodeToSliceError> {
+    if from_utf8(bytes).is_ok() {
+        let out = out.get_mut(..bytes.len()).ok_or(BufferTooSmall(()))?;
+        out.copy_from_slice(bytes);
+        metrics::record(bytes.len(), bytes.len(), false);
+        // SAFETY: We just copied `bytes`, which we know is valid UTF-8, into
+        // `out`.
+        return Ok(unsafe { core::str::from_utf8_unchecked(out) });
+    }
+
+    let decoded = internal::decode_to_slice(bytes, Flavor::Mutf8, out)?;
+    metrics::record(bytes.len(), decoded.len(), true);
+    Ok(decoded)
+}
+
+/// Reads a MUTF-8 string that is prefixed with its length in bytes as an
+/// unsigned LEB128 varint.
+///
+/// This function reads the varint at the start of `bytes`, decodes exactly
+/// that many of the following bytes with [`decode_strict`], and returns the
+/// decoded string along with the total number of bytes consumed, including the
+/// length prefix. Any bytes after the string are ignored.
+///
+/// # Errors
+///
+/// This function will return a [`VarintStringError`] if:
+///
+/// - The length prefix is longer than ten bytes, or its value doesn't fit in a
+///   [`usize`] ([`VarintStringError::InvalidLength`]).
+/// - The input ends before the length prefix or the string it describes does
+///   ([`VarintStringError::UnexpectedEnd`]).
+/// - The string is not valid MUTF-8 ([`VarintStringError::Decoding`]).
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::VarintStringError> {
+/// let bytes = [0x04, b'a', 0xc0, 0x80, b'b', 0xff];
+/// let (decoded, consumed) = mutf8::read_varint_string(&bytes)?;
+/// assert_eq!(decoded, "a\0b");
+/// assert_eq!(consumed, 5);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn read_varint_string(bytes: &[u8]) -> Result<(Cow<'_, str>, usize), VarintStringError> {
+    varint::read_string(bytes, decode_strict)
+}
+
+/// Encodes a string to MUTF-8 and appends it to `buffer`, prefixed with its
+/// encoded length in bytes as an unsigned LEB128 varint.
+///
+/// This is the counterpart of [`read_varint_string`].
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// let mut buffer = Vec::new();
+/// mutf8::write_varint_string("a\0b", &mut buffer);
+/// assert_eq!(buffer, [0x04, b'a', 0xc0, 0x80, b'b']);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn write_varint_string(value: &str, buffer: &mut Vec<u8>) {
+    varint::write_string(value, buffer, encode);
+}
+
+/// Reads a Java-style MUTF-8 string that is prefixed with its length in bytes
+/// as a big-endian [`u16`].
+///
+/// Java class files (`CONSTANT_Utf8_info`) and `DataInput::readUTF` store
+/// strings this way. This function reads the length prefix at the start of
+/// `bytes`, decodes exactly that many of the following bytes with
+/// [`decode_strict`], and returns the decoded string along with the total
+/// number of bytes consumed, including the length prefix. Any bytes after the
+/// string are ignored.
+///
+/// # Errors
+///
+/// This function will return a [`DecodingError`] if the length prefix or the
+/// string it describes extends past the end of `bytes`, or if the string is
+/// not valid MUTF-8.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0x00, 0x04, b'a', 0xc0, 0x80, b'b', 0xff];
+/// let (decoded, consumed) = mutf8::decode_java_string(&bytes)?;
+/// assert_eq!(decoded, "a\0b");
+/// assert_eq!(consumed, 6);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn decode_java_string(bytes: &[u8]) -> Result<(Cow<'_, str>, usize), DecodingError> {
+    let Some((&prefix, remaining)) = bytes.split_first_chunk::<2>() else {
+        return Err(DecodingError::new(DecodingErrorKind::UnexpectedEof));
+    };
+
+    let len = usize::from(u16::from_be_bytes(prefix));
+
+    let Some(string) = remaining.get(..len) else {
+        return Err(DecodingError::new(DecodingErrorKind::UnexpectedEof));
+    };
+
+    Ok((decode_strict(string)?, 2 + len))
+}
+
+/// Encodes a string to MUTF-8 and appends it to `buffer`, prefixed with its
+/// encoded length in bytes as a big-endian [`u16`].
+///
+/// This is the counterpart of [`decode_java_string`], and writes the same bytes
+/// as Java's `DataOutput::writeUTF`.
+///
+/// # Errors
+///
+/// If the encoded string is longer than [`u16::MAX`] bytes, so its length
+/// can't be written, this function will return an [`EncodeError`], and
+/// `buffer` is left untouched.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to encode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate alloc;
+/// use alloc::vec::Vec;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::EncodeError> {
+/// let mut buffer = Vec::new();
+/// mutf8::encode_java_string("a\0b", &mut buffer)?;
+/// assert_eq!(buffer, [0x00, 0x04, b'a', 0xc0, 0x80, b'b']);
+///
+/// assert!(mutf8::encode_java_string(&"a".repeat(65536), &mut buffer).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[inline]
+pub fn encode_java_string(value: &str, buffer: &mut Vec<u8>) -> Result<(), EncodeError> {
+    let len = u16::try_from(encoded_len(value)).map_err(|_| EncodeError(()))?;
+    buffer.extend_from_slice(&len.to_be_bytes());
+    encode_into(value, buffer);
+    Ok(())
+}
+
+/// Reads a Java-style MUTF-8 string from a cursor, advancing the cursor past
+/// it.
+///
+/// Java class files and `DataInput::readUTF` store strings as a big-endian
+/// [`u16`] byte length followed by exactly that many bytes of MUTF-8. This
+/// function reads the string at the cursor's current position with
+/// [`decode_java_string`], and then moves the cursor to the first byte after
+/// the string.
+///
+/// The returned string borrows from the cursor's underlying slice whenever
+/// [`decode_strict`] would return <code>[Cow::Borrowed]\(&[str]\)</code>.
+///
+/// If an error is returned, the position of the cursor is left unchanged.
+///
+/// # Errors
+///
+/// This function will return a [`DecodingError`] if the length prefix or the
+/// string it describes extends past the end of the underlying slice, or if the
+/// string is not valid MUTF-8.
+///
+/// # Panics
+///
+/// This function will panic if the buffer required to decode the input exceeds
+/// [`isize::MAX`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use simd_cesu8::mutf8;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0x00, 0x04, b'a', 0xc0, 0x80, b'b', 0xff];
+/// let mut cursor = Cursor::new(&bytes[..]);
+///
+/// let decoded = mutf8::read_java_string_cursor(&mut cursor)?;
+/// assert_eq!(decoded, "a\0b");
+/// assert_eq!(cursor.position(), 6);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+// NOTE: Nightly Clippy suggests `core::io::Cursor`, which isn't stable.
+#[allow(clippy::std_instead_of_core)]
+#[inline]
+pub fn read_java_string_cursor<'a>(
+    cursor: &mut std::io::Cursor<&'a [u8]>,
+) -> Result<Cow<'a, str>, DecodingError> {
+    let bytes: &'a [u8] = cursor.get_ref();
+    let position = usize::try_from(cursor.position()).unwrap_or(usize::MAX);
+    let remaining = bytes.get(position..).unwrap_or_default();
+
+    let (decoded, consumed) = decode_java_string(remaining)?;
+    // NOTE: `position + consumed` is at most `bytes.len()`, so this can't
+    // overflow.
+    cursor.set_position((position + consumed) as u64);
+    Ok(decoded)
 }
@@ -0,0 +1,56 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::error::DecodingError;
+use crate::internal::{self, DecodeOptions, Flavor};
+
+/// The smallest chunk worth handing to another thread. Below this, the cost of
+/// splitting the work outweighs decoding it in place.
+const MIN_CHUNK_LEN: usize = 64 * 1024;
+
+/// Decodes `bytes` in the given flavor like [`internal::decode`], but splits it
+/// into chunks that are decoded in parallel and then concatenated.
+///
+/// NOTE: Each chunk is decoded with the state machine, never the UTF-8 fast
+/// path, since a chunk can be valid UTF-8 when the whole input isn't.
+pub(crate) fn decode(bytes: &[u8], flavor: Flavor) -> Result<String, DecodingError> {
+    let options = DecodeOptions {
+        flavor,
+        lossy: false,
+    };
+    let count = (bytes.len() / MIN_CHUNK_LEN).clamp(1, rayon::current_num_threads());
+
+    if count == 1 {
+        return internal::decode(bytes, options);
+    }
+
+    let mut bounds = Vec::with_capacity(count + 1);
+    bounds.push(0);
+
+    for n in 1..count {
+        let start = internal::floor_char_boundary(bytes, bytes.len() / count * n);
+        // NOTE: Backing up can never reach the previous bound, since every
+        // chunk is far longer than a character, but this keeps the chunks in
+        // order even if it could.
+        bounds.push(start.max(bounds[n - 1]));
+    }
+
+    bounds.push(bytes.len());
+
+    let chunks: Vec<_> = bounds
+        .par_windows(2)
+        .map(|bounds| internal::decode(&bytes[bounds[0]..bounds[1]], options))
+        .collect();
+
+    // NOTE: The chunks are checked in order, so the error is always the one for
+    // the first invalid chunk, no matter which thread finished first.
+    let mut decoded = String::with_capacity(bytes.len());
+
+    for chunk in chunks {
+        decoded.push_str(&chunk?);
+    }
+
+    Ok(decoded)
+}
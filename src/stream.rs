@@ -0,0 +1,374 @@
+use alloc::string::String;
+
+use crate::error::{DecodingError, DecodingErrorKind};
+use crate::internal::{DecodeOptions, Flavor};
+use crate::{internal, metrics};
+
+/// A streaming CESU-8 decoder for input that arrives in chunks.
+///
+/// Every call to [`feed`](Cesu8Decoder::feed) or [`push`](Cesu8Decoder::push)
+/// decodes as much of the chunk as it can, and holds on to a sequence that was
+/// cut off at the end of the chunk until the next call completes it. This
+/// includes a 6-byte surrogate pair, so the chunks can be split at any byte. At
+/// most 5 bytes are ever held on to.
+///
+/// Like [`decode_strict`](crate::decode_strict), this decoder treats valid
+/// UTF-8 that is not valid CESU-8 as invalid input, since whether the whole
+/// input is valid UTF-8 isn't known until the last chunk.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8Decoder;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// // NOTE: The surrogate pair is split 3/3 between the second and third
+/// // chunks.
+/// let mut decoder = Cesu8Decoder::new();
+///
+/// assert_eq!(decoder.push(&[b'a', 0xe2])?, "a");
+/// assert_eq!(decoder.push(&[0x82, 0xac, 0xed, 0xa0, 0xbd])?, "€");
+/// assert_eq!(decoder.push(&[0xed, 0xb2, 0x96])?, "💖");
+///
+/// decoder.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Cesu8Decoder {
+    pending: Pending,
+    decoded: String,
+}
+
+impl Cesu8Decoder {
+    /// Creates a new decoder with nothing held on to.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            pending: Pending::new(),
+            decoded: String::new(),
+        }
+    }
+
+    /// Decodes the next chunk of input, appending every complete character to
+    /// `decoded`.
+    ///
+    /// # Errors
+    ///
+    /// If the input so far is not valid CESU-8, this function will return a
+    /// [`DecodingError`]. The decoder shouldn't be used after an error.
+    ///
+    /// **NOTE:** `decoded` may already contain some of the characters of the
+    /// chunk before the invalid sequence.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[inline]
+    pub fn feed(&mut self, chunk: &[u8], decoded: &mut String) -> Result<(), DecodingError> {
+        self.pending.feed(chunk, decoded, Flavor::Cesu8)
+    }
+
+    /// Decodes the next chunk of input, returning every complete character in
+    /// it.
+    ///
+    /// The returned string is borrowed from a buffer owned by the decoder,
+    /// which is reused by every call, so decoding a stream only allocates
+    /// while the chunks keep getting bigger.
+    ///
+    /// # Errors
+    ///
+    /// If the input so far is not valid CESU-8, this function will return a
+    /// [`DecodingError`]. The decoder shouldn't be used after an error.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[inline]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<&str, DecodingError> {
+        self.decoded.clear();
+        self.pending.feed(chunk, &mut self.decoded, Flavor::Cesu8)?;
+        Ok(&self.decoded)
+    }
+
+    /// Returns `true` if the decoder is holding on to part of a sequence.
+    #[must_use]
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        self.pending.len != 0
+    }
+
+    /// Finishes decoding, making sure the input didn't end in the middle of a
+    /// sequence.
+    ///
+    /// # Errors
+    ///
+    /// If the decoder is still holding on to part of a sequence, this function
+    /// will return a [`DecodingError`].
+    #[inline]
+    pub fn finish(self) -> Result<(), DecodingError> {
+        if self.has_pending() {
+            Err(DecodingError::new(DecodingErrorKind::UnexpectedEof))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A streaming MUTF-8 decoder for input that arrives in chunks.
+///
+/// Every call to [`feed`](Mutf8Decoder::feed) or [`push`](Mutf8Decoder::push)
+/// decodes as much of the chunk as it can, and holds on to a sequence that was
+/// cut off at the end of the chunk until the next call completes it. This
+/// includes a `0xc0 0x80` null and a 6-byte surrogate pair, so the chunks
+/// can be split at any byte. At most 5 bytes are ever held on to.
+///
+/// Like [`mutf8::decode_strict`](crate::mutf8::decode_strict), this decoder
+/// treats valid UTF-8 that is not valid MUTF-8 as invalid input, since whether
+/// the whole input is valid UTF-8 isn't known until the last chunk.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8::Mutf8Decoder;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// // NOTE: Both the null and the surrogate pair are split between chunks.
+/// let mut decoder = Mutf8Decoder::new();
+///
+/// assert_eq!(decoder.push(&[b'a', 0xc0])?, "a");
+/// assert_eq!(decoder.push(&[0x80, 0xed, 0xa0])?, "\0");
+/// assert_eq!(decoder.push(&[0xbd, 0xed, 0xb2, 0x96])?, "💖");
+///
+/// decoder.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Mutf8Decoder {
+    pending: Pending,
+    decoded: String,
+}
+
+impl Mutf8Decoder {
+    /// Creates a new decoder with nothing held on to.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            pending: Pending::new(),
+            decoded: String::new(),
+        }
+    }
+
+    /// Decodes the next chunk of input, appending every complete character to
+    /// `decoded`.
+    ///
+    /// # Errors
+    ///
+    /// If the input so far is not valid MUTF-8, this function will return a
+    /// [`DecodingError`]. The decoder shouldn't be used after an error.
+    ///
+    /// **NOTE:** `decoded` may already contain some of the characters of the
+    /// chunk before the invalid sequence.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[inline]
+    pub fn feed(&mut self, chunk: &[u8], decoded: &mut String) -> Result<(), DecodingError> {
+        self.pending.feed(chunk, decoded, Flavor::Mutf8)
+    }
+
+    /// Decodes the next chunk of input, returning every complete character in
+    /// it.
+    ///
+    /// The returned string is borrowed from a buffer owned by the decoder,
+    /// which is reused by every call, so decoding a stream only allocates
+    /// while the chunks keep getting bigger.
+    ///
+    /// # Errors
+    ///
+    /// If the input so far is not valid MUTF-8, this function will return a
+    /// [`DecodingError`]. The decoder shouldn't be used after an error.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[inline]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<&str, DecodingError> {
+        self.decoded.clear();
+        self.pending.feed(chunk, &mut self.decoded, Flavor::Mutf8)?;
+        Ok(&self.decoded)
+    }
+
+    /// Returns `true` if the decoder is holding on to part of a sequence.
+    #[must_use]
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        self.pending.len != 0
+    }
+
+    /// Finishes decoding, making sure the input didn't end in the middle of a
+    /// sequence.
+    ///
+    /// # Errors
+    ///
+    /// If the decoder is still holding on to part of a sequence, this function
+    /// will return a [`DecodingError`].
+    #[inline]
+    pub fn finish(self) -> Result<(), DecodingError> {
+        if self.has_pending() {
+            Err(DecodingError::new(DecodingErrorKind::UnexpectedEof))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The end of the previous chunk, when it was cut off in the middle of a
+/// sequence.
+#[derive(Debug, Clone, Default)]
+struct Pending {
+    bytes: [u8; MAX_WIDTH],
+    len: usize,
+}
+
+impl Pending {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; MAX_WIDTH],
+            len: 0,
+        }
+    }
+
+    fn feed(
+        &mut self,
+        mut chunk: &[u8],
+        decoded: &mut String,
+        flavor: Flavor,
+    ) -> Result<(), DecodingError> {
+        let start = decoded.len();
+        let bytes_in = chunk.len();
+
+        if self.len != 0 {
+            // NOTE: We complete the pending sequence one byte at a time. This
+            // only ever takes a handful of bytes, and it means we never have to
+            // handle a sequence that starts in one chunk and ends in the next
+            // anywhere else.
+            loop {
+                // SAFETY: `len` is never greater than `MAX_WIDTH`.
+                let pending = unsafe { self.bytes.get_unchecked(..self.len) };
+
+                if let Some(width) = sequence_width(pending) {
+                    if self.len >= width {
+                        break;
+                    }
+                }
+
+                let Some((&byte, rest)) = chunk.split_first() else {
+                    return Ok(());
+                };
+
+                // SAFETY: No sequence is wider than `MAX_WIDTH` bytes, and we
+                // stop as soon as it's complete.
+                *unsafe { self.bytes.get_unchecked_mut(self.len) } = byte;
+                self.len += 1;
+                chunk = rest;
+            }
+
+            let len = self.len;
+            self.len = 0;
+            // SAFETY: `len` is never greater than `MAX_WIDTH`.
+            decode_to(unsafe { self.bytes.get_unchecked(..len) }, decoded, flavor)?;
+        }
+
+        let (complete, rest) = chunk.split_at(chunk.len() - incomplete_suffix_len(chunk));
+        decode_to(complete, decoded, flavor)?;
+
+        // SAFETY: `incomplete_suffix_len` is never greater than `MAX_PENDING`,
+        // which is less than `MAX_WIDTH`.
+        unsafe { self.bytes.get_unchecked_mut(..rest.len()) }.copy_from_slice(rest);
+        self.len = rest.len();
+
+        metrics::record(bytes_in, decoded.len() - start, false);
+        Ok(())
+    }
+}
+
+/// Decodes `bytes` strictly, appending the result to `decoded`.
+#[inline]
+fn decode_to(bytes: &[u8], decoded: &mut String, flavor: Flavor) -> Result<(), DecodingError> {
+    let options = DecodeOptions {
+        flavor,
+        lossy: false,
+    };
+    internal::decode_into(bytes, options, decoded).map(|_| ())
+}
+
+/// Returns the number of bytes in the sequence that starts with `bytes`, or
+/// [`None`] if that can't be known until more bytes arrive.
+///
+/// Invalid lead bytes are treated as single bytes, so that the decoder
+/// reports them as soon as it sees them.
+#[must_use]
+#[inline]
+fn sequence_width(bytes: &[u8]) -> Option<usize> {
+    match *bytes {
+        [0xc0..=0xdf, ..] => Some(2),
+        // NOTE: Whether `0xed` starts a 3-byte character or a 6-byte
+        // surrogate pair depends on the byte after it.
+        [0xed] => None,
+        [0xed, 0xa0..=0xaf, ..] => Some(6),
+        [0xe0..=0xef, ..] => Some(3),
+        _ => Some(1),
+    }
+}
+
+/// Returns the number of bytes at the end of `bytes` that belong to a sequence
+/// that was cut off, and might still be completed by the next chunk.
+#[must_use]
+#[inline]
+fn incomplete_suffix_len(bytes: &[u8]) -> usize {
+    let tail = bytes.len().saturating_sub(MAX_PENDING);
+    // SAFETY: `tail` is at most `bytes.len()`.
+    let suffix = unsafe { bytes.get_unchecked(tail..) };
+
+    let Some(lead) = suffix
+        .iter()
+        .rposition(|&byte| byte & 0b1100_0000 != 0b1000_0000)
+    else {
+        return 0;
+    };
+
+    // SAFETY: `rposition` always returns an index within `suffix`.
+    let last = unsafe { suffix.get_unchecked(lead..) };
+
+    if sequence_width(last).is_some_and(|width| width <= last.len()) {
+        return 0;
+    }
+
+    // NOTE: If the last sequence is the start of a low surrogate, it's only
+    // complete with the high surrogate before it, so we hold on to both.
+    let start = tail + lead;
+    let is_low_surrogate = matches!(*last, [0xed] | [0xed, 0xb0..=0xbf]);
+    let is_after_high_surrogate =
+        start >= 3 && matches!(bytes.get(start - 3..start), Some([0xed, 0xa0..=0xaf, _]));
+
+    if is_low_surrogate && is_after_high_surrogate {
+        bytes.len() - (start - 3)
+    } else {
+        bytes.len() - start
+    }
+}
+
+/// The widest sequence, which is a surrogate pair.
+const MAX_WIDTH: usize = 6;
+
+/// The most bytes a cut-off sequence can have.
+const MAX_PENDING: usize = MAX_WIDTH - 1;
@@ -0,0 +1,249 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::internal::Flavor;
+use crate::{decode_strict, encode, mutf8};
+
+/// An owned string that is serialized as CESU-8 bytes.
+///
+/// In code, this is a normal [`String`] that dereferences to [`str`]. Only its
+/// [`Serialize`] and [`Deserialize`] implementations differ: it's serialized
+/// with [`encode`](crate::encode), and deserialized from bytes with
+/// [`decode_strict`](crate::decode_strict), so the data format stores the
+/// CESU-8 bytes rather than the UTF-8 string.
+///
+/// Comparing, ordering, and hashing go by the wrapped string, so they agree
+/// with [`String`] exactly, rather than with the order of the CESU-8 bytes,
+/// which sorts 4-byte characters before U+E000 through U+FFFF. Since the
+/// string is stored decoded, this costs nothing over comparing a [`String`].
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8String;
+///
+/// let value = Cesu8String::from("💖");
+/// assert_eq!(value.len(), 4);
+/// assert_eq!(value.into_string(), "💖");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cesu8String(String);
+
+impl Cesu8String {
+    /// Wraps a string so that it's serialized as CESU-8.
+    #[must_use]
+    #[inline]
+    pub const fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// Returns the wrapped string.
+    #[must_use]
+    #[inline]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for Cesu8String {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Cesu8String {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Cesu8String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<String> for Cesu8String {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Cesu8String {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self(String::from(value))
+    }
+}
+
+impl From<Cesu8String> for String {
+    #[inline]
+    fn from(value: Cesu8String) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Cesu8String {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Cesu8String {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_bytes(StringVisitor(Flavor::Cesu8))
+            .map(Self)
+    }
+}
+
+/// An owned string that is serialized as MUTF-8 bytes.
+///
+/// This is the MUTF-8 counterpart of [`Cesu8String`](crate::Cesu8String),
+/// which is serialized with [`mutf8::encode`], and deserialized with
+/// [`mutf8::decode_strict`]. Like it, it's compared, ordered, and hashed as the
+/// wrapped string.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8::Mutf8String;
+///
+/// let value = Mutf8String::from("a\0b");
+/// assert_eq!(&*value, "a\0b");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mutf8String(String);
+
+impl Mutf8String {
+    /// Wraps a string so that it's serialized as MUTF-8.
+    #[must_use]
+    #[inline]
+    pub const fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// Returns the wrapped string.
+    #[must_use]
+    #[inline]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for Mutf8String {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Mutf8String {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Mutf8String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl From<String> for Mutf8String {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Mutf8String {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self(String::from(value))
+    }
+}
+
+impl From<Mutf8String> for String {
+    #[inline]
+    fn from(value: Mutf8String) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Mutf8String {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&mutf8::encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Mutf8String {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_bytes(StringVisitor(Flavor::Mutf8))
+            .map(Self)
+    }
+}
+
+/// Strictly decodes the bytes of either newtype in the given flavor.
+struct StringVisitor(Flavor);
+
+impl StringVisitor {
+    fn decode<'a, E: de::Error>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>, E> {
+        let result = match self.0 {
+            Flavor::Cesu8 => decode_strict(bytes),
+            Flavor::Mutf8 => mutf8::decode_strict(bytes),
+        };
+
+        result.map_err(|_| de::Error::invalid_value(de::Unexpected::Bytes(bytes), self))
+    }
+}
+
+impl<'de> Visitor<'de> for StringVisitor {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Flavor::Cesu8 => f.write_str("CESU-8 bytes"),
+            Flavor::Mutf8 => f.write_str("MUTF-8 bytes"),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<String, E> {
+        self.decode(bytes).map(Cow::into_owned)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<String, E> {
+        match self.decode(&bytes)? {
+            // SAFETY: The bytes were borrowed as-is, so they're valid UTF-8.
+            Cow::Borrowed(_) => Ok(unsafe { String::from_utf8_unchecked(bytes) }),
+            Cow::Owned(value) => Ok(value),
+        }
+    }
+
+    // NOTE: Some data formats, like JSON, have no bytes of their own, and
+    // store them as a sequence of integers instead.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<String, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+
+        self.visit_byte_buf(bytes)
+    }
+}
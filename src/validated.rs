@@ -0,0 +1,685 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::fmt::{self, Write as _};
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "alloc")]
+use core::ops::Deref;
+
+use simdutf8::basic::from_utf8;
+
+use crate::error::DecodingError;
+use crate::internal;
+#[cfg(feature = "alloc")]
+use crate::internal::DecodeOptions;
+use crate::internal::Flavor;
+#[cfg(feature = "alloc")]
+use crate::metrics;
+
+/// A borrowed slice of bytes that is known to be valid CESU-8.
+///
+/// This is to CESU-8 what [`str`] is to UTF-8: it's always used behind a
+/// reference, and creating one validates the bytes once, so decoding it later
+/// can never fail.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8Str;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+/// let value = Cesu8Str::from_bytes(&bytes)?;
+/// assert!(value == "💖");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// It can be compared to a [`str`] without decoding it to a new string. The
+/// comparison stops at the first difference, and [`Hash`] is consistent with
+/// it, so equal values hash equally. The decoded string is fed to the hasher in
+/// pieces, though, so it only hashes the same as the decoded [`str`] with a
+/// streaming hasher, like the default one in `std`. Hashers that treat each
+/// write on its own, or override `Hasher::write_str`, may not agree with it:
+///
+/// ```
+/// use simd_cesu8::Cesu8Str;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let value = Cesu8Str::from_bytes(&[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96])?;
+/// assert!(value == "a💖");
+/// assert!(value != "b💖");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Cesu8Str([u8]);
+
+impl Cesu8Str {
+    /// Validates a slice of bytes as CESU-8.
+    ///
+    /// # Errors
+    ///
+    /// If the input is not valid CESU-8, this function will return a
+    /// [`DecodingError`]. This includes valid UTF-8 that is not valid CESU-8,
+    /// just like [`decode_strict`](crate::decode_strict).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8Str;
+    ///
+    /// assert!(Cesu8Str::from_bytes(b"Hello, world!").is_ok());
+    /// assert!(Cesu8Str::from_bytes(&[0xf0, 0x9f, 0x92, 0x96]).is_err());
+    /// ```
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<&Self, DecodingError> {
+        if internal::validate(bytes, Flavor::Cesu8) {
+            // SAFETY: We just validated the bytes.
+            Ok(unsafe { Self::from_bytes_unchecked(bytes) })
+        } else {
+            Err(internal::invalid(bytes, Flavor::Cesu8))
+        }
+    }
+
+    /// Converts a slice of bytes to a [`Cesu8Str`] without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid CESU-8, as checked by [`Cesu8Str::from_bytes`].
+    #[must_use]
+    #[inline]
+    pub const unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        // SAFETY: `Cesu8Str` is a `#[repr(transparent)]` wrapper around `[u8]`,
+        // so the two have the same layout.
+        unsafe { &*(core::ptr::from_ref(bytes) as *const Self) }
+    }
+
+    /// Returns the underlying CESU-8 bytes.
+    #[must_use]
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the string without copying it if the CESU-8 bytes are also
+    /// valid UTF-8, or [`None`] if decoding it would require a transform.
+    ///
+    /// Valid CESU-8 is valid UTF-8 exactly when it contains no surrogate pairs,
+    /// so this is a single validation pass over the bytes. If this returns
+    /// [`None`], use [`to_str`](Cesu8Str::to_str) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::Cesu8Str;
+    ///
+    /// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+    /// let value = Cesu8Str::from_bytes(b"Hello, world!")?;
+    /// assert_eq!(value.as_utf8(), Some("Hello, world!"));
+    ///
+    /// let value = Cesu8Str::from_bytes(&[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96])?;
+    /// assert_eq!(value.as_utf8(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn as_utf8(&self) -> Option<&str> {
+        from_utf8(&self.0).ok()
+    }
+
+    /// Decodes the CESU-8 bytes to a string.
+    ///
+    /// This returns <code>[Cow::Borrowed]\(&[str]\)</code> whenever
+    /// [`as_utf8`](Cesu8Str::as_utf8) would return [`Some`], and
+    /// <code>[Cow::Owned]\([String]\)</code> otherwise.
+    ///
+    /// [String]: alloc::string::String
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[must_use]
+    #[inline]
+    pub fn to_str(&self) -> Cow<'_, str> {
+        to_str(&self.0, Flavor::Cesu8)
+    }
+}
+
+impl AsRef<[u8]> for Cesu8Str {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Cesu8Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_utf8(&self.0, Flavor::Cesu8, f)
+    }
+}
+
+impl PartialEq<str> for Cesu8Str {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        eq_utf8(&self.0, Flavor::Cesu8, other)
+    }
+}
+
+impl PartialEq<Cesu8Str> for str {
+    #[inline]
+    fn eq(&self, other: &Cesu8Str) -> bool {
+        eq_utf8(&other.0, Flavor::Cesu8, self)
+    }
+}
+
+impl Hash for Cesu8Str {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_utf8(&self.0, Flavor::Cesu8, state);
+    }
+}
+
+/// An owned buffer of bytes that is known to be valid CESU-8.
+///
+/// This is to [`Cesu8Str`] what [`String`](alloc::string::String) is to
+/// [`str`]. Each [`char`] is encoded as it's pushed, so collecting characters
+/// into one never builds a UTF-8 string only to scan it again.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::Cesu8Buf;
+///
+/// let value: Cesu8Buf = "a💖".chars().collect();
+/// assert_eq!(value.as_bytes(), [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+/// assert!(*value == *"a💖");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Cesu8Buf(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl Cesu8Buf {
+    /// Creates an empty buffer.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Encodes a character to CESU-8, and appends it to the buffer.
+    #[inline]
+    pub fn push(&mut self, char: char) {
+        self.0
+            .extend_from_slice(internal::encode_char(char, Flavor::Cesu8, &mut [0; 6]));
+    }
+
+    /// Returns the underlying CESU-8 bytes.
+    #[must_use]
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for Cesu8Buf {
+    type Target = Cesu8Str;
+
+    #[inline]
+    fn deref(&self) -> &Cesu8Str {
+        // SAFETY: Only whole characters are ever pushed, each encoded as
+        // CESU-8.
+        unsafe { Cesu8Str::from_bytes_unchecked(&self.0) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<[u8]> for Cesu8Buf {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<Cesu8Str> for Cesu8Buf {
+    #[inline]
+    fn borrow(&self) -> &Cesu8Str {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for Cesu8Buf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Hash for Cesu8Buf {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<char> for Cesu8Buf {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut value = Self::new();
+        value.extend(iter);
+        value
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<char> for Cesu8Buf {
+    #[inline]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.0.reserve(iter.size_hint().0);
+
+        for char in iter {
+            self.push(char);
+        }
+    }
+}
+
+/// A borrowed slice of bytes that is known to be valid MUTF-8.
+///
+/// This is the MUTF-8 counterpart of [`Cesu8Str`](crate::Cesu8Str).
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8::Mutf8Str;
+///
+/// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+/// let bytes = [b'a', 0xc0, 0x80, b'b'];
+/// let value = Mutf8Str::from_bytes(&bytes)?;
+/// assert!(value == "a\0b");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Mutf8Str([u8]);
+
+impl Mutf8Str {
+    /// Validates a slice of bytes as MUTF-8.
+    ///
+    /// # Errors
+    ///
+    /// If the input is not valid MUTF-8, this function will return a
+    /// [`DecodingError`]. This includes valid UTF-8 that is not valid MUTF-8,
+    /// just like [`mutf8::decode_strict`](crate::mutf8::decode_strict).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::mutf8::Mutf8Str;
+    ///
+    /// assert!(Mutf8Str::from_bytes(&[b'a', 0xc0, 0x80, b'b']).is_ok());
+    /// assert!(Mutf8Str::from_bytes(b"a\0b").is_err());
+    /// ```
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Result<&Self, DecodingError> {
+        if internal::validate(bytes, Flavor::Mutf8) {
+            // SAFETY: We just validated the bytes.
+            Ok(unsafe { Self::from_bytes_unchecked(bytes) })
+        } else {
+            Err(internal::invalid(bytes, Flavor::Mutf8))
+        }
+    }
+
+    /// Converts a slice of bytes to a [`Mutf8Str`] without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid MUTF-8, as checked by [`Mutf8Str::from_bytes`].
+    #[must_use]
+    #[inline]
+    pub const unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        // SAFETY: `Mutf8Str` is a `#[repr(transparent)]` wrapper around `[u8]`,
+        // so the two have the same layout.
+        unsafe { &*(core::ptr::from_ref(bytes) as *const Self) }
+    }
+
+    /// Returns the underlying MUTF-8 bytes.
+    #[must_use]
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the string without copying it if the MUTF-8 bytes are also
+    /// valid UTF-8, or [`None`] if decoding it would require a transform.
+    ///
+    /// Valid MUTF-8 is valid UTF-8 exactly when it contains no surrogate pairs
+    /// and no encoded nulls, so this is a single validation pass over the
+    /// bytes. If this returns [`None`], use [`to_str`](Mutf8Str::to_str)
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use simd_cesu8::mutf8::Mutf8Str;
+    ///
+    /// # fn main() -> Result<(), simd_cesu8::DecodingError> {
+    /// let value = Mutf8Str::from_bytes(b"Hello, world!")?;
+    /// assert_eq!(value.as_utf8(), Some("Hello, world!"));
+    ///
+    /// let value = Mutf8Str::from_bytes(&[0xc0, 0x80])?;
+    /// assert_eq!(value.as_utf8(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn as_utf8(&self) -> Option<&str> {
+        from_utf8(&self.0).ok()
+    }
+
+    /// Decodes the MUTF-8 bytes to a string.
+    ///
+    /// This returns <code>[Cow::Borrowed]\(&[str]\)</code> whenever
+    /// [`as_utf8`](Mutf8Str::as_utf8) would return [`Some`], and
+    /// <code>[Cow::Owned]\([String]\)</code> otherwise.
+    ///
+    /// [String]: alloc::string::String
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the buffer required to decode the input
+    /// exceeds [`isize::MAX`] bytes.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[must_use]
+    #[inline]
+    pub fn to_str(&self) -> Cow<'_, str> {
+        to_str(&self.0, Flavor::Mutf8)
+    }
+}
+
+impl AsRef<[u8]> for Mutf8Str {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Mutf8Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_utf8(&self.0, Flavor::Mutf8, f)
+    }
+}
+
+impl PartialEq<str> for Mutf8Str {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        eq_utf8(&self.0, Flavor::Mutf8, other)
+    }
+}
+
+impl PartialEq<Mutf8Str> for str {
+    #[inline]
+    fn eq(&self, other: &Mutf8Str) -> bool {
+        eq_utf8(&other.0, Flavor::Mutf8, self)
+    }
+}
+
+impl Hash for Mutf8Str {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_utf8(&self.0, Flavor::Mutf8, state);
+    }
+}
+
+/// An owned buffer of bytes that is known to be valid MUTF-8.
+///
+/// This is the MUTF-8 counterpart of [`Cesu8Buf`](crate::Cesu8Buf), where each
+/// `'\0'` is pushed as `0xc0 0x80`.
+///
+/// # Examples
+///
+/// ```
+/// use simd_cesu8::mutf8::Mutf8Buf;
+///
+/// let value: Mutf8Buf = "a\0".chars().collect();
+/// assert_eq!(value.as_bytes(), [b'a', 0xc0, 0x80]);
+/// assert!(*value == *"a\0");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Mutf8Buf(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl Mutf8Buf {
+    /// Creates an empty buffer.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Encodes a character to MUTF-8, and appends it to the buffer.
+    #[inline]
+    pub fn push(&mut self, char: char) {
+        self.0
+            .extend_from_slice(internal::encode_char(char, Flavor::Mutf8, &mut [0; 6]));
+    }
+
+    /// Returns the underlying MUTF-8 bytes.
+    #[must_use]
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for Mutf8Buf {
+    type Target = Mutf8Str;
+
+    #[inline]
+    fn deref(&self) -> &Mutf8Str {
+        // SAFETY: Only whole characters are ever pushed, each encoded as
+        // MUTF-8.
+        unsafe { Mutf8Str::from_bytes_unchecked(&self.0) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<[u8]> for Mutf8Buf {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<Mutf8Str> for Mutf8Buf {
+    #[inline]
+    fn borrow(&self) -> &Mutf8Str {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for Mutf8Buf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Hash for Mutf8Buf {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<char> for Mutf8Buf {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut value = Self::new();
+        value.extend(iter);
+        value
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<char> for Mutf8Buf {
+    #[inline]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.0.reserve(iter.size_hint().0);
+
+        for char in iter {
+            self.push(char);
+        }
+    }
+}
+
+/// Decodes bytes that were already validated in the given flavor.
+#[cfg(feature = "alloc")]
+#[inline]
+fn to_str(bytes: &[u8], flavor: Flavor) -> Cow<'_, str> {
+    if let Ok(string) = from_utf8(bytes) {
+        metrics::record(bytes.len(), bytes.len(), false);
+        Cow::Borrowed(string)
+    } else {
+        let result = internal::decode(bytes, DecodeOptions {
+            flavor,
+            lossy: false,
+        });
+
+        // SAFETY: The bytes were validated when the wrapper was created, so
+        // decoding them can't fail.
+        let string = unsafe { result.unwrap_unchecked() };
+
+        metrics::record(bytes.len(), string.len(), true);
+        Cow::Owned(string)
+    }
+}
+
+/// Calls `f` with consecutive pieces of the UTF-8 that `bytes` decodes to,
+/// which together make up the whole string. `bytes` must already be valid in
+/// the given flavor. This stops as soon as `f` returns `false`, and returns
+/// whether it got to the end.
+///
+/// NOTE: Valid CESU-8 and MUTF-8 only differ from UTF-8 in their surrogate
+/// pairs and encoded nulls, so everything between them is passed through as
+/// one piece, and nothing is ever allocated.
+fn try_for_each_utf8<F: FnMut(&[u8]) -> bool>(bytes: &[u8], flavor: Flavor, mut f: F) -> bool {
+    let mut start = 0;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let pair;
+        let (len, decoded): (usize, &[u8]) = match bytes[index..] {
+            [0xc0, ..] if flavor == Flavor::Mutf8 => (2, &[0x00]),
+            [0xed, second @ 0xa0..=0xaf, third, _, fifth, sixth, ..] => {
+                pair = internal::decode_surrogate_pair(second, third, fifth, sixth);
+                (6, &pair)
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+
+        if !f(&bytes[start..index]) || !f(decoded) {
+            return false;
+        }
+
+        index += len;
+        start = index;
+    }
+
+    f(&bytes[start..])
+}
+
+/// Compares bytes that were already validated in the given flavor to `other`,
+/// decoding them only as far as the first difference.
+#[inline]
+fn eq_utf8(bytes: &[u8], flavor: Flavor, other: &str) -> bool {
+    // NOTE: Decoding never makes the input longer, so this rules out most
+    // mismatches without decoding anything.
+    if bytes.len() < other.len() {
+        return false;
+    }
+
+    let mut rest = other.as_bytes();
+    let matched = try_for_each_utf8(bytes, flavor, |piece| {
+        if let Some(tail) = rest.strip_prefix(piece) {
+            rest = tail;
+            true
+        } else {
+            false
+        }
+    });
+
+    matched && rest.is_empty()
+}
+
+/// Formats bytes that were already validated in the given flavor the same way
+/// that [`fmt::Debug`] formats the [`str`] they decode to, without allocating.
+fn fmt_utf8(bytes: &[u8], flavor: Flavor, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut result = f.write_char('"');
+
+    try_for_each_utf8(bytes, flavor, |piece| {
+        // SAFETY: The bytes were validated when the wrapper was created, and
+        // every piece ends on a character boundary, so it's valid UTF-8.
+        let piece = unsafe { core::str::from_utf8_unchecked(piece) };
+
+        result = result.and_then(|()| {
+            piece.chars().try_for_each(|char| {
+                match char {
+                    // NOTE: Unlike `char`, `str` doesn't escape single quotes.
+                    '\'' => f.write_char(char),
+                    _ => write!(f, "{}", char.escape_debug()),
+                }
+            })
+        });
+        result.is_ok()
+    });
+
+    result.and_then(|()| f.write_char('"'))
+}
+
+/// Hashes bytes that were already validated in the given flavor the same way
+/// that [`str`] hashes the string they decode to.
+///
+/// NOTE: The string is fed to the hasher in pieces, so this only matches the
+/// hash of the [`str`] for streaming hashers, as documented on [`Cesu8Str`].
+#[inline]
+fn hash_utf8<H: Hasher>(bytes: &[u8], flavor: Flavor, state: &mut H) {
+    try_for_each_utf8(bytes, flavor, |piece| {
+        if !piece.is_empty() {
+            state.write(piece);
+        }
+
+        true
+    });
+
+    // NOTE: This is what `str` writes after its bytes, so that `("ab", "c")`
+    // and `("a", "bc")` hash differently.
+    state.write_u8(0xff);
+}
@@ -0,0 +1,78 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::error::{DecodingError, VarintStringError};
+
+/// The most bytes a LEB128 varint can take to encode a [`u64`].
+const MAX_LEN: usize = 10;
+
+/// Reads a LEB128 varint from the start of `bytes`, returning its value and
+/// the number of bytes it took up.
+#[inline]
+fn read(bytes: &[u8]) -> Result<(usize, usize), VarintStringError> {
+    let mut value = 0u64;
+
+    for (index, &byte) in bytes.iter().take(MAX_LEN).enumerate() {
+        let payload = u64::from(byte & 0x7f);
+
+        // NOTE: The last byte only has room for the single highest bit of a
+        // `u64`, so anything more would overflow.
+        if index == MAX_LEN - 1 && payload > 1 {
+            return Err(VarintStringError::InvalidLength);
+        }
+
+        value |= payload << (7 * index);
+
+        if byte & 0x80 == 0 {
+            let value = usize::try_from(value).map_err(|_| VarintStringError::InvalidLength)?;
+            return Ok((value, index + 1));
+        }
+    }
+
+    if bytes.len() >= MAX_LEN {
+        Err(VarintStringError::InvalidLength)
+    } else {
+        Err(VarintStringError::UnexpectedEnd)
+    }
+}
+
+/// Appends `value` to `buffer` as a LEB128 varint.
+#[inline]
+fn write(mut value: usize, buffer: &mut Vec<u8>) {
+    #[allow(clippy::cast_possible_truncation)]
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    buffer.push(value as u8);
+}
+
+/// Reads a varint length prefix, then decodes that many bytes with `decode`.
+#[inline]
+pub(crate) fn read_string<'a>(
+    bytes: &'a [u8],
+    decode: fn(&'a [u8]) -> Result<Cow<'a, str>, DecodingError>,
+) -> Result<(Cow<'a, str>, usize), VarintStringError> {
+    let (len, prefix) = read(bytes)?;
+
+    // SAFETY: `read` never reports more bytes than it was given.
+    let remaining = unsafe { bytes.get_unchecked(prefix..) };
+
+    let Some(string) = remaining.get(..len) else {
+        return Err(VarintStringError::UnexpectedEnd);
+    };
+
+    let decoded = decode(string)?;
+    Ok((decoded, prefix + len))
+}
+
+/// Encodes `value` with `encode`, then appends it to `buffer` after a varint
+/// length prefix.
+#[inline]
+pub(crate) fn write_string(value: &str, buffer: &mut Vec<u8>, encode: fn(&str) -> Cow<'_, [u8]>) {
+    let encoded = encode(value);
+    write(encoded.len(), buffer);
+    buffer.extend_from_slice(&encoded);
+}
@@ -0,0 +1,57 @@
+//! Tests for the reusable scratch buffers.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{mutf8, Cesu8Arena, Cesu8EncodeArena};
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn encode_arena_matches_encode() {
+    let mut arena = Cesu8EncodeArena::new();
+
+    for value in VALUES {
+        assert_eq!(arena.encode_next(value), &*simd_cesu8::encode(value));
+        assert_eq!(arena.encode_next_mutf8(value), &*mutf8::encode(value));
+    }
+}
+
+#[test]
+fn decode_arena_matches_decode() {
+    let mut arena = Cesu8Arena::new();
+
+    for value in VALUES {
+        let encoded = simd_cesu8::encode(value);
+        assert_eq!(arena.decode_next(&encoded).unwrap(), value);
+
+        let encoded = mutf8::encode(value);
+        assert_eq!(arena.decode_next_mutf8(&encoded).unwrap(), value);
+    }
+}
+
+#[test]
+fn arenas_stop_growing_once_they_fit_the_largest_string() {
+    let mut encode_arena = Cesu8EncodeArena::new();
+    let mut decode_arena = Cesu8Arena::new();
+
+    for value in VALUES {
+        let _ = encode_arena.encode_next_mutf8(value);
+        decode_arena
+            .decode_next_mutf8(&mutf8::encode(value))
+            .unwrap();
+    }
+
+    let encode_capacity = encode_arena.capacity();
+    let decode_capacity = decode_arena.capacity();
+
+    for _ in 0..100 {
+        for value in VALUES {
+            let _ = encode_arena.encode_next_mutf8(value);
+            decode_arena
+                .decode_next_mutf8(&mutf8::encode(value))
+                .unwrap();
+        }
+    }
+
+    assert_eq!(encode_arena.capacity(), encode_capacity);
+    assert_eq!(decode_arena.capacity(), decode_capacity);
+}
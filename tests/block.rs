@@ -0,0 +1,62 @@
+//! Tests for the block-at-a-time primitives.
+
+use simd_cesu8::implementation::block::{process_block, BlockResult, Flavor, BLOCK_SIZE};
+
+fn expected(block: &[u8; BLOCK_SIZE], flavor: Flavor) -> BlockResult {
+    let index = block.iter().position(|&byte| {
+        (byte & 0b1111_1000 == 0b1111_0000) || (flavor == Flavor::Mutf8 && byte == 0x00)
+    });
+
+    match index {
+        Some(index) => BlockResult::Transform { index },
+        None => BlockResult::TransformFree,
+    }
+}
+
+#[test]
+fn finds_the_first_transform_byte_at_every_index() {
+    for index in 0..BLOCK_SIZE {
+        for byte in [0x00, 0xf0, 0xf4, 0xf7] {
+            let mut block = [b'a'; BLOCK_SIZE];
+            block[index] = byte;
+
+            for flavor in [Flavor::Cesu8, Flavor::Mutf8] {
+                assert_eq!(
+                    process_block(&block, flavor),
+                    expected(&block, flavor),
+                    "{byte:#x} at {index} in {flavor:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn reports_only_the_first_of_several() {
+    let mut block = [0x80; BLOCK_SIZE];
+    block[40] = 0xf0;
+    block[50] = 0x00;
+    block[60] = 0xf0;
+
+    let result = process_block(&block, Flavor::Cesu8);
+    assert_eq!(result, BlockResult::Transform { index: 40 });
+
+    block[30] = 0x00;
+    let result = process_block(&block, Flavor::Mutf8);
+    assert_eq!(result, BlockResult::Transform { index: 30 });
+}
+
+#[test]
+fn multi_byte_text_is_transform_free() {
+    let text = "ȅ€".repeat(BLOCK_SIZE);
+    let block: &[u8; BLOCK_SIZE] = text.as_bytes()[1..=BLOCK_SIZE].try_into().unwrap();
+
+    assert_eq!(
+        process_block(block, Flavor::Cesu8),
+        BlockResult::TransformFree
+    );
+    assert_eq!(
+        process_block(block, Flavor::Mutf8),
+        BlockResult::TransformFree
+    );
+}
@@ -0,0 +1,51 @@
+//! Tests for the `bstr` feature.
+#![cfg(feature = "bstr")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+
+use simd_cesu8::mutf8;
+
+#[test]
+fn valid_utf8_is_borrowed() {
+    let bytes = b"Hello, world!";
+
+    let decoded = simd_cesu8::decode_bstr_lossy(bytes);
+    assert!(matches!(decoded, Cow::Borrowed(_)));
+    assert_eq!(decoded.as_ptr(), bytes.as_ptr());
+
+    let decoded = mutf8::decode_bstr_lossy(bytes);
+    assert!(matches!(decoded, Cow::Borrowed(_)));
+    assert_eq!(decoded.as_ptr(), bytes.as_ptr());
+}
+
+#[test]
+fn matches_decode_lossy() {
+    let inputs: [&[u8]; 5] = [
+        b"",
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+        &[b'a', 0xc0, 0x80, b'b'],
+        &[b'a', 0xed, 0xa0, 0xbd, b'b'],
+        &[0xf0, 0x9f, 0x92, 0x96, 0xff],
+    ];
+
+    for bytes in inputs {
+        assert_eq!(
+            simd_cesu8::decode_bstr_lossy(bytes).as_ref(),
+            simd_cesu8::decode_lossy(bytes).as_bytes(),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            mutf8::decode_bstr_lossy(bytes).as_ref(),
+            mutf8::decode_lossy(bytes).as_bytes(),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn displays_replacement_characters() {
+    let decoded = mutf8::decode_bstr_lossy(&[b'a', 0xc0, 0x80, 0xff]);
+    assert_eq!(decoded.to_string(), "a\0�");
+}
@@ -0,0 +1,36 @@
+//! Tests for the `bytes` feature.
+#![cfg(feature = "bytes")]
+
+use bytes::Bytes;
+use simd_cesu8::mutf8;
+
+#[test]
+fn valid_utf8_shares_the_input() {
+    let input = Bytes::from(b"Hello, world!".to_vec());
+
+    let decoded = simd_cesu8::decode_bytes(&input).unwrap();
+    assert_eq!(decoded, "Hello, world!");
+    assert_eq!(decoded.as_bytes().as_ptr(), input.as_ptr());
+
+    let decoded = mutf8::decode_bytes(&input).unwrap();
+    assert_eq!(decoded, "Hello, world!");
+    assert_eq!(decoded.into_bytes().as_ptr(), input.as_ptr());
+}
+
+#[test]
+fn transformed_input_is_decoded_into_new_bytes() {
+    let input = Bytes::from_static(&[b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+
+    let decoded = mutf8::decode_bytes(&input).unwrap();
+    assert_eq!(decoded, "a\0💖");
+    assert_ne!(decoded.as_bytes().as_ptr(), input.as_ptr());
+
+    assert!(simd_cesu8::decode_bytes(&input).is_err());
+}
+
+#[test]
+fn invalid_input_is_rejected() {
+    let input = Bytes::from_static(&[b'a', 0xff]);
+    assert!(simd_cesu8::decode_bytes(&input).is_err());
+    assert!(mutf8::decode_bytes(&input).is_err());
+}
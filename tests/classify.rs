@@ -0,0 +1,72 @@
+//! Tests for classifying bytes as UTF-8, CESU-8 with surrogate pairs, or
+//! invalid.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::{Cesu8Class, Cesu8Str};
+
+#[test]
+fn empty_input_is_ascii_or_bmp() {
+    assert_eq!(simd_cesu8::classify_cesu8(b""), Cesu8Class::AsciiOrBmp);
+}
+
+#[test]
+fn bmp_text_is_ascii_or_bmp() {
+    for value in ["Hello, world!", "\0", "ȅ€", "\u{ffff}"] {
+        let class = simd_cesu8::classify_cesu8(value.as_bytes());
+        assert_eq!(class, Cesu8Class::AsciiOrBmp, "{value:?}");
+    }
+}
+
+#[test]
+fn encoded_astral_text_contains_surrogate_pairs() {
+    for value in ["💖", "a💖b", "\u{10000}", "\u{10ffff}"] {
+        let encoded = simd_cesu8::encode(value);
+        let class = simd_cesu8::classify_cesu8(&encoded);
+        assert_eq!(class, Cesu8Class::ContainsSurrogatePairs, "{value:?}");
+    }
+}
+
+#[test]
+fn invalid_input_is_invalid() {
+    let cases: [&[u8]; 5] = [
+        "💖".as_bytes(),
+        &[0xed, 0xa0, 0xbd],
+        &[0xed, 0xb2, 0x96],
+        &[0xc0, 0x80],
+        &[0xff],
+    ];
+
+    for bytes in cases {
+        assert_eq!(
+            simd_cesu8::classify_cesu8(bytes),
+            Cesu8Class::Invalid,
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn agrees_with_separate_checks() {
+    let mut bytes = Vec::new();
+
+    for value in ["a", "💖", "\0"] {
+        bytes.extend_from_slice(&simd_cesu8::encode(value));
+    }
+
+    for len in 0..=bytes.len() {
+        let prefix = &bytes[..len];
+        let expected = if core::str::from_utf8(prefix).is_ok() {
+            Cesu8Class::AsciiOrBmp
+        } else if Cesu8Str::from_bytes(prefix).is_ok() {
+            Cesu8Class::ContainsSurrogatePairs
+        } else {
+            Cesu8Class::Invalid
+        };
+
+        assert_eq!(simd_cesu8::classify_cesu8(prefix), expected, "{prefix:x?}");
+    }
+}
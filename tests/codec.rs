@@ -0,0 +1,70 @@
+//! Tests for the single-character building blocks in `codec`.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{codec, mutf8};
+
+#[test]
+fn encode_char_matches_encode() {
+    let mut buffer = [0; codec::MAX_CHAR_LEN];
+    let mut utf8 = [0; 4];
+
+    for char in (0..=char::MAX as u32).step_by(7).filter_map(char::from_u32) {
+        let value = char.encode_utf8(&mut utf8);
+
+        assert_eq!(
+            *codec::encode_char_to_cesu8(char, &mut buffer),
+            *simd_cesu8::encode(value),
+            "{char:?}"
+        );
+        assert_eq!(
+            *codec::encode_char_to_mutf8(char, &mut buffer),
+            *mutf8::encode(value),
+            "{char:?}"
+        );
+    }
+}
+
+#[test]
+fn decode_one_round_trips() {
+    let mut buffer = [0; codec::MAX_CHAR_LEN];
+
+    for char in (0..=char::MAX as u32).step_by(7).filter_map(char::from_u32) {
+        let encoded = codec::encode_char_to_cesu8(char, &mut buffer);
+        let len = encoded.len();
+        assert_eq!(codec::decode_one(encoded), Ok((char, len)), "{char:?}");
+
+        let encoded = codec::encode_char_to_mutf8(char, &mut buffer);
+        let len = encoded.len();
+        assert_eq!(
+            codec::decode_one_mutf8(encoded),
+            Ok((char, len)),
+            "{char:?}"
+        );
+    }
+}
+
+#[test]
+fn decode_one_only_looks_at_the_first_character() {
+    let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xff, 0xff];
+    assert_eq!(codec::decode_one(&bytes), Ok(('💖', 6)));
+    assert_eq!(codec::decode_one(&[b'a', 0xff]), Ok(('a', 1)));
+}
+
+#[test]
+fn decode_one_rejects_invalid_input() {
+    let inputs: [&[u8]; 6] = [
+        &[],
+        &[0x80],
+        &[0xed, 0xa0, 0xbd],
+        &[0xed, 0xa0, 0xbd, b'a'],
+        &[0xf0, 0x9f, 0x92, 0x96],
+        &[0xc0, 0x80],
+    ];
+
+    for bytes in inputs {
+        assert!(codec::decode_one(bytes).is_err(), "{bytes:x?}");
+    }
+
+    assert!(codec::decode_one_mutf8(&[0x00]).is_err());
+    assert!(codec::decode_one_mutf8(&[]).is_err());
+}
@@ -0,0 +1,137 @@
+//! Tests for decoding with an explicit `DecodeConfig`.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+
+use simd_cesu8::{mutf8, DecodeConfig, Flavor};
+
+const INPUTS: [&[u8]; 8] = [
+    b"",
+    b"Hello, world!",
+    &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    &[0xf0, 0x9f, 0x92, 0x96],
+    &[b'a', 0xc0, 0x80],
+    &[b'a', 0x00],
+    &[0xed, 0xa0, 0xbd],
+    &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf0, 0x9f, 0x92, 0x96],
+];
+
+const fn allowing_utf8(flavor: Flavor) -> DecodeConfig {
+    DecodeConfig {
+        allow_utf8_4_byte: true,
+        ..DecodeConfig::new(flavor)
+    }
+}
+
+#[test]
+fn new_matches_decode_strict() {
+    for bytes in INPUTS {
+        assert_eq!(
+            simd_cesu8::decode_opts(bytes, DecodeConfig::new(Flavor::Cesu8)),
+            simd_cesu8::decode_strict(bytes),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            simd_cesu8::decode_opts(bytes, DecodeConfig::new(Flavor::Mutf8)),
+            mutf8::decode_strict(bytes),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn lossy_matches_decode_lossy_strict() {
+    for bytes in INPUTS {
+        let config = DecodeConfig {
+            lossy: true,
+            ..DecodeConfig::new(Flavor::Cesu8)
+        };
+        assert_eq!(
+            simd_cesu8::decode_opts(bytes, config).unwrap(),
+            simd_cesu8::decode_lossy_strict(bytes),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn allowing_utf8_accepts_mixed_input() {
+    let bytes = [
+        b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'b', 0xf4, 0x8f, 0xbf, 0xbf, 0xc0, 0x80,
+    ];
+
+    assert!(simd_cesu8::decode(&bytes).is_err());
+    assert!(mutf8::decode(&bytes).is_err());
+    assert_eq!(
+        simd_cesu8::decode_opts(&bytes, allowing_utf8(Flavor::Mutf8)).unwrap(),
+        "a💖b\u{10ffff}\0"
+    );
+}
+
+#[test]
+fn allowing_utf8_borrows_valid_utf8() {
+    let bytes = "a💖b".as_bytes();
+
+    assert!(matches!(
+        simd_cesu8::decode_opts(bytes, allowing_utf8(Flavor::Cesu8)),
+        Ok(Cow::Borrowed("a💖b"))
+    ));
+    assert!(matches!(
+        simd_cesu8::decode_opts(bytes, allowing_utf8(Flavor::Mutf8)),
+        Ok(Cow::Owned(_))
+    ));
+}
+
+#[test]
+fn allowing_utf8_still_rejects_invalid_4_byte_sequences() {
+    let inputs: [&[u8]; 5] = [
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf0, 0x8f, 0xbf, 0xbf],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf4, 0x90, 0x80, 0x80],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf0, 0x9f, 0x92],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf0, 0x9f, 0x92, b'a'],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xf5, 0x80, 0x80, 0x80],
+    ];
+
+    for bytes in inputs {
+        assert!(
+            simd_cesu8::decode_opts(bytes, allowing_utf8(Flavor::Cesu8)).is_err(),
+            "{bytes:x?}"
+        );
+
+        let config = DecodeConfig {
+            lossy: true,
+            ..allowing_utf8(Flavor::Cesu8)
+        };
+        let decoded = simd_cesu8::decode_opts(bytes, config).unwrap();
+        assert!(decoded.starts_with('💖'), "{bytes:x?}");
+        assert!(decoded.contains('\u{fffd}'), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn strip_bom_skips_only_a_leading_bom() {
+    let config = DecodeConfig {
+        strip_bom: true,
+        ..DecodeConfig::new(Flavor::Cesu8)
+    };
+    let bytes = [0xef, 0xbb, 0xbf, b'a', 0xef, 0xbb, 0xbf];
+
+    assert!(simd_cesu8::has_bom(&bytes));
+    assert!(matches!(
+        simd_cesu8::decode_opts(&bytes, config),
+        Ok(Cow::Borrowed("a\u{feff}"))
+    ));
+    assert_eq!(
+        simd_cesu8::decode_opts(&bytes, DecodeConfig::new(Flavor::Cesu8)).unwrap(),
+        "\u{feff}a\u{feff}"
+    );
+    assert_eq!(
+        simd_cesu8::decode_opts(&bytes[3..], config).unwrap(),
+        "a\u{feff}"
+    );
+
+    let bytes = [0xef, 0xbb, 0xbf, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+    assert_eq!(simd_cesu8::decode_opts(&bytes, config).unwrap(), "💖");
+}
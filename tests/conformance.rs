@@ -0,0 +1,100 @@
+//! Conformance tests against the reference vectors in Unicode Technical Report
+//! #26 (CESU-8) and the Java Virtual Machine Specification, §4.4.7 (MUTF-8).
+//!
+//! Unlike the random tests, these don't compare against the `cesu8` crate, so
+//! they would catch a bug that both crates share.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+
+use simd_cesu8::mutf8;
+
+/// The example sequence from UTR #26, `<U+004D, U+0430, U+4E8C, U+10302>`,
+/// which covers one character of every UTF-8 width.
+const EXAMPLE: &str = "\u{004d}\u{0430}\u{4e8c}\u{10302}";
+
+const EXAMPLE_UTF_8: [u8; 10] = [0x4d, 0xd0, 0xb0, 0xe4, 0xba, 0x8c, 0xf0, 0x90, 0x8c, 0x82];
+
+const EXAMPLE_CESU_8: [u8; 12] = [
+    0x4d, 0xd0, 0xb0, 0xe4, 0xba, 0x8c, 0xed, 0xa0, 0x80, 0xed, 0xbc, 0x82,
+];
+
+/// Every supplementary character is the CESU-8 form of its UTF-16 surrogate
+/// pair, where each surrogate takes three bytes.
+const SUPPLEMENTARY: [(char, [u8; 6]); 4] = [
+    ('\u{10000}', [0xed, 0xa0, 0x80, 0xed, 0xb0, 0x80]),
+    ('\u{10302}', [0xed, 0xa0, 0x80, 0xed, 0xbc, 0x82]),
+    ('\u{1f496}', [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]),
+    ('\u{10ffff}', [0xed, 0xaf, 0xbf, 0xed, 0xbf, 0xbf]),
+];
+
+/// Characters in the Basic Multilingual Plane are identical in UTF-8 and
+/// CESU-8, including the boundaries of each width.
+const BMP: [(char, &[u8]); 8] = [
+    ('\u{0001}', &[0x01]),
+    ('\u{007f}', &[0x7f]),
+    ('\u{0080}', &[0xc2, 0x80]),
+    ('\u{07ff}', &[0xdf, 0xbf]),
+    ('\u{0800}', &[0xe0, 0xa0, 0x80]),
+    ('\u{d7ff}', &[0xed, 0x9f, 0xbf]),
+    ('\u{e000}', &[0xee, 0x80, 0x80]),
+    ('\u{ffff}', &[0xef, 0xbf, 0xbf]),
+];
+
+#[test]
+fn utr_26_example_encodes_exactly() {
+    assert_eq!(EXAMPLE.as_bytes(), EXAMPLE_UTF_8);
+    assert_eq!(*simd_cesu8::encode(EXAMPLE), EXAMPLE_CESU_8);
+    assert_eq!(*mutf8::encode(EXAMPLE), EXAMPLE_CESU_8);
+}
+
+#[test]
+fn utr_26_example_decodes_exactly() {
+    assert_eq!(simd_cesu8::decode_strict(&EXAMPLE_CESU_8).unwrap(), EXAMPLE);
+    assert_eq!(mutf8::decode_strict(&EXAMPLE_CESU_8).unwrap(), EXAMPLE);
+}
+
+#[test]
+fn utr_26_rejects_the_utf_8_form_of_supplementary_characters() {
+    assert!(simd_cesu8::decode_strict(&EXAMPLE_UTF_8).is_err());
+    assert!(mutf8::decode_strict(&EXAMPLE_UTF_8).is_err());
+}
+
+#[test]
+fn supplementary_characters_are_surrogate_pairs() {
+    for (c, expected) in SUPPLEMENTARY {
+        let mut utf_8 = [0; 4];
+        let value = &*c.encode_utf8(&mut utf_8);
+
+        assert_eq!(*simd_cesu8::encode(value), expected, "{c:?}");
+        assert_eq!(*mutf8::encode(value), expected, "{c:?}");
+        assert_eq!(simd_cesu8::decode_strict(&expected).unwrap(), value);
+        assert_eq!(mutf8::decode_strict(&expected).unwrap(), value);
+    }
+}
+
+#[test]
+fn bmp_characters_are_unchanged() {
+    for (c, expected) in BMP {
+        let mut utf_8 = [0; 4];
+        let value = &*c.encode_utf8(&mut utf_8);
+
+        assert_eq!(simd_cesu8::encode(value), Cow::Borrowed(expected), "{c:?}");
+        assert_eq!(mutf8::encode(value), Cow::Borrowed(expected), "{c:?}");
+        assert_eq!(simd_cesu8::decode_strict(expected).unwrap(), value);
+        assert_eq!(mutf8::decode_strict(expected).unwrap(), value);
+    }
+}
+
+#[test]
+fn null_follows_each_specification() {
+    // NOTE: UTR #26 encodes null like UTF-8 does, while the JVM specification
+    // uses the 2-byte form so that strings never contain a zero byte.
+    assert_eq!(*simd_cesu8::encode("\0"), [0x00]);
+    assert_eq!(*mutf8::encode("\0"), [0xc0, 0x80]);
+    assert_eq!(simd_cesu8::decode_strict(&[0x00]).unwrap(), "\0");
+    assert_eq!(mutf8::decode_strict(&[0xc0, 0x80]).unwrap(), "\0");
+    assert!(mutf8::decode_strict(&[0x00]).is_err());
+}
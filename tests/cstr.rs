@@ -0,0 +1,60 @@
+//! Tests for decoding null-terminated MUTF-8, as found in DEX files.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 5] = ["", "Hello, world!", "\0", "a\0💖ȅ€", "\0\0💖"];
+
+#[test]
+fn decode_cstr_reads_consecutive_strings() {
+    let mut bytes = Vec::new();
+
+    for value in VALUES {
+        bytes.extend_from_slice(&mutf8::encode(value));
+        bytes.push(0x00);
+    }
+
+    let mut rest = &bytes[..];
+
+    for value in VALUES {
+        let (decoded, consumed) = mutf8::decode_cstr(rest).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, mutf8::encode(value).len() + 1, "{value:?}");
+        rest = &rest[consumed..];
+    }
+
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn decode_cstr_borrows_plain_strings() {
+    assert!(matches!(
+        mutf8::decode_cstr(b"abc\0\xff"),
+        Ok((Cow::Borrowed("abc"), 4))
+    ));
+}
+
+#[test]
+fn decode_cstr_requires_a_terminator() {
+    for bytes in [&b""[..], b"abc", &[b'a', 0xc0, 0x80]] {
+        assert!(mutf8::decode_cstr(bytes).is_err(), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn decode_cstr_rejects_invalid_strings() {
+    let cases: [&[u8]; 3] = [
+        &[0xed, 0xa0, 0xbd, 0x00],
+        &[b'a', 0xff, 0x00],
+        &[b'a', 0xc0, 0x00],
+    ];
+
+    for bytes in cases {
+        assert!(mutf8::decode_cstr(bytes).is_err(), "{bytes:x?}");
+    }
+}
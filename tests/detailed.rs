@@ -0,0 +1,92 @@
+//! Tests for the `detailed-errors` feature.
+
+use core::mem;
+
+use simd_cesu8::DecodingError;
+#[cfg(feature = "detailed-errors")]
+use simd_cesu8::{mutf8, Cesu8Decoder, Cesu8Str, DecodingErrorKind};
+
+#[cfg(feature = "detailed-errors")]
+const INPUTS: [&[u8]; 8] = [
+    &[0x80],
+    &[0xc8, b'a'],
+    &[b'a', 0xe2, 0x82],
+    &[0xed, 0xa0, 0xbd, b'a', b'b', b'c'],
+    &[0xc0, 0x80],
+    &[0xe0, 0x80, 0x80],
+    &[0xf0, 0x9f, 0x92, 0x96, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    &[b'a', 0xff],
+];
+
+#[cfg(not(feature = "detailed-errors"))]
+#[test]
+fn decoding_error_is_zero_sized() {
+    assert_eq!(mem::size_of::<DecodingError>(), 0);
+}
+
+#[cfg(feature = "detailed-errors")]
+#[test]
+fn decoding_error_is_no_bigger_than_its_kind() {
+    assert_eq!(
+        mem::size_of::<DecodingError>(),
+        mem::size_of::<DecodingErrorKind>()
+    );
+}
+
+#[cfg(feature = "detailed-errors")]
+#[test]
+fn kind_matches_decode_verbose() {
+    for bytes in INPUTS {
+        let expected = simd_cesu8::decode_verbose(bytes).unwrap_err().kind();
+
+        assert_eq!(
+            simd_cesu8::decode_strict(bytes).unwrap_err().kind(),
+            expected,
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            simd_cesu8::cesu8_to_mutf8(bytes).unwrap_err().kind(),
+            expected,
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            Cesu8Str::from_bytes(bytes).unwrap_err().kind(),
+            expected,
+            "{bytes:x?}"
+        );
+
+        // NOTE: Some of the inputs, like `0xc0 0x80`, are valid MUTF-8.
+        let Err(error) = mutf8::decode_verbose(bytes) else {
+            continue;
+        };
+
+        assert_eq!(
+            mutf8::decode_strict(bytes).unwrap_err().kind(),
+            error.kind(),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            simd_cesu8::mutf8_to_cesu8(bytes).unwrap_err().kind(),
+            error.kind(),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[cfg(feature = "detailed-errors")]
+#[test]
+fn unfinished_stream_is_unexpected_eof() {
+    let mut decoder = Cesu8Decoder::new();
+    decoder.push(&[b'a', 0xed, 0xa0]).unwrap();
+    assert_eq!(
+        decoder.finish().unwrap_err().kind(),
+        DecodingErrorKind::UnexpectedEof
+    );
+}
+
+#[cfg(feature = "detailed-errors")]
+#[test]
+fn decode_cstr_without_a_terminator_is_unexpected_eof() {
+    let error = mutf8::decode_cstr(&[b'a', 0xc0, 0x80]).unwrap_err();
+    assert_eq!(error.kind(), DecodingErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,540 @@
+//! Targeted tests for edge cases of encoding and decoding that random data is
+//! unlikely to hit.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::{mutf8, DecodeConfig, DecodingErrorKind, Flavor};
+
+#[test]
+fn replacement_character_round_trips() {
+    let value = "a\u{fffd}b";
+    assert_eq!(value.as_bytes(), &[b'a', 0xef, 0xbf, 0xbd, b'b']);
+
+    let encoded = simd_cesu8::encode(value);
+    assert_eq!(encoded, Cow::Borrowed(value.as_bytes()));
+    assert_eq!(simd_cesu8::decode(&encoded).unwrap(), value);
+    assert_eq!(simd_cesu8::decode_strict(&encoded).unwrap(), value);
+
+    let encoded = mutf8::encode(value);
+    assert_eq!(encoded, Cow::Borrowed(value.as_bytes()));
+    assert_eq!(mutf8::decode(&encoded).unwrap(), value);
+    assert_eq!(mutf8::decode_strict(&encoded).unwrap(), value);
+}
+
+#[test]
+fn replacement_character_round_trips_next_to_transformed_chars() {
+    let value = "\u{fffd}💖\0\u{fffd}";
+
+    let encoded = simd_cesu8::encode(value);
+    assert!(matches!(encoded, Cow::Owned(_)));
+    assert_eq!(simd_cesu8::decode_strict(&encoded).unwrap(), value);
+
+    let encoded = mutf8::encode(value);
+    assert!(matches!(encoded, Cow::Owned(_)));
+    assert_eq!(mutf8::decode_strict(&encoded).unwrap(), value);
+}
+
+#[test]
+fn replacement_character_is_ambiguous_when_lossy() {
+    // NOTE: A genuine U+FFFD in the input decodes to the same string as an
+    // invalid byte that was replaced with U+FFFD.
+    let genuine = [0xef, 0xbf, 0xbd];
+    let invalid = [0xff];
+
+    assert_eq!(
+        simd_cesu8::decode_lossy_strict(&genuine),
+        simd_cesu8::decode_lossy_strict(&invalid)
+    );
+    assert_eq!(
+        mutf8::decode_lossy_strict(&genuine),
+        mutf8::decode_lossy_strict(&invalid)
+    );
+}
+
+#[test]
+fn decode_lossy_counted_tells_genuine_replacement_characters_apart() {
+    // NOTE: The genuine U+FFFD forces the decoder off of the borrowing path.
+    let bytes = [0xef, 0xbf, 0xbd, 0xff, 0xef, 0xbf, 0xbd];
+
+    let (decoded, replacements) = simd_cesu8::decode_lossy_counted(&bytes);
+    assert_eq!(decoded, "\u{fffd}\u{fffd}\u{fffd}");
+    assert_eq!(replacements, 1);
+
+    let (decoded, replacements) = mutf8::decode_lossy_counted(&bytes);
+    assert_eq!(decoded, "\u{fffd}\u{fffd}\u{fffd}");
+    assert_eq!(replacements, 1);
+}
+
+/// A surrogate pair that forces the decoders through the transcoding path, even
+/// when the rest of the input is valid UTF-8.
+const SURROGATE_PAIR: [u8; 6] = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+
+#[test]
+fn ed_80_to_9f_decodes_as_bmp() {
+    for second in 0x80..=0x9f {
+        for third in 0x80..=0xbf {
+            let code_point = 0xd000 | u32::from(second & 0x3f) << 6 | u32::from(third & 0x3f);
+            let expected = char::from_u32(code_point).unwrap();
+            assert!(('\u{d000}'..='\u{d7ff}').contains(&expected));
+
+            let mut bytes = vec![0xed, second, third];
+            let mut string = String::from(expected);
+            assert_eq!(simd_cesu8::decode(&bytes).unwrap(), string);
+            assert_eq!(mutf8::decode(&bytes).unwrap(), string);
+
+            bytes.extend_from_slice(&SURROGATE_PAIR);
+            string.push('💖');
+            assert_eq!(simd_cesu8::decode(&bytes).unwrap(), string);
+            assert_eq!(simd_cesu8::decode_strict(&bytes).unwrap(), string);
+            assert_eq!(mutf8::decode(&bytes).unwrap(), string);
+            assert_eq!(mutf8::decode_strict(&bytes).unwrap(), string);
+        }
+    }
+}
+
+#[test]
+fn ed_a0_to_af_decodes_as_surrogate_pair() {
+    for high in 0xa0..=0xaf {
+        for low in 0xb0..=0xbf {
+            let bytes = [0xed, high, 0x80, 0xed, low, 0x80];
+            let high_surrogate = 0xd000 | u32::from(high & 0x3f) << 6;
+            let low_surrogate = 0xd000 | u32::from(low & 0x3f) << 6;
+            let code_point = 0x10000 + ((high_surrogate - 0xd800) << 10 | (low_surrogate - 0xdc00));
+            let expected = String::from(char::from_u32(code_point).unwrap());
+
+            assert_eq!(simd_cesu8::decode(&bytes).unwrap(), expected);
+            assert_eq!(simd_cesu8::decode_strict(&bytes).unwrap(), expected);
+            assert_eq!(mutf8::decode(&bytes).unwrap(), expected);
+            assert_eq!(mutf8::decode_strict(&bytes).unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn ed_a0_to_af_without_low_surrogate_is_rejected() {
+    for high in 0xa0..=0xaf {
+        for bytes in [
+            &[0xed, high, 0x80][..],
+            &[0xed, high, 0x80, b'a', b'b', b'c'],
+            &[0xed, high, 0x80, 0xed, 0x9f, 0xbf],
+            &[0xed, high, 0x80, 0xed, 0xa0, 0x80],
+        ] {
+            assert!(simd_cesu8::decode(bytes).is_err());
+            assert!(simd_cesu8::decode_strict(bytes).is_err());
+            assert!(mutf8::decode(bytes).is_err());
+            assert!(mutf8::decode_strict(bytes).is_err());
+        }
+    }
+}
+
+#[test]
+fn ed_b0_to_bf_is_rejected_as_lone_low_surrogate() {
+    for second in 0xb0..=0xbf {
+        let mut bytes = vec![0xed, second, 0x80];
+        assert!(simd_cesu8::decode(&bytes).is_err());
+        assert!(mutf8::decode(&bytes).is_err());
+        assert_eq!(simd_cesu8::decode_lossy(&bytes), "\u{fffd}");
+        assert_eq!(mutf8::decode_lossy(&bytes), "\u{fffd}");
+
+        bytes.extend_from_slice(&SURROGATE_PAIR);
+        assert!(simd_cesu8::decode(&bytes).is_err());
+        assert!(simd_cesu8::decode_strict(&bytes).is_err());
+        assert!(mutf8::decode(&bytes).is_err());
+        assert!(mutf8::decode_strict(&bytes).is_err());
+        assert_eq!(simd_cesu8::decode_lossy(&bytes), "\u{fffd}💖");
+    }
+}
+
+#[test]
+fn surrogate_pair_with_swapped_halves_is_rejected() {
+    for high in 0xa0..=0xaf {
+        for low in 0xb0..=0xbf {
+            let mut bytes = [0xed, low, 0x80, 0xed, high, 0x80];
+            assert_rejected_as_invalid_surrogate_pair(&bytes);
+
+            // NOTE: This is the high surrogate followed by another high
+            // surrogate, instead of a low one.
+            bytes[3..].copy_from_slice(&SURROGATE_PAIR[..3]);
+            bytes[..3].copy_from_slice(&[0xed, high, 0x80]);
+            assert_rejected_as_invalid_surrogate_pair(&bytes);
+        }
+    }
+}
+
+#[test]
+fn surrogate_pair_of_two_low_surrogates_is_rejected() {
+    assert_rejected_as_invalid_surrogate_pair(&[0xed, 0xb2, 0x96, 0xed, 0xb2, 0x96]);
+}
+
+#[test]
+fn bmp_characters_next_to_each_other_are_not_a_surrogate_pair() {
+    let bytes = [0xed, 0x9f, 0xbf, 0xed, 0x9f, 0xbf];
+    assert_eq!(
+        simd_cesu8::decode_strict(&bytes).unwrap(),
+        "\u{d7ff}\u{d7ff}"
+    );
+    assert_eq!(mutf8::decode_strict(&bytes).unwrap(), "\u{d7ff}\u{d7ff}");
+}
+
+#[test]
+fn overlong_encodings_are_rejected() {
+    let cases: [&[u8]; 4] = [&[0xc0, 0x80], &[0xc1, 0xbf], &[0xe0, 0x80, 0x80], &[
+        0xe0, 0x9f, 0xbf,
+    ]];
+
+    for bytes in cases {
+        assert_rejected_as_overlong(bytes);
+    }
+
+    // NOTE: `0xc0 0x80` is how MUTF-8 encodes null, so it's the one overlong
+    // encoding that MUTF-8 allows.
+    assert_eq!(mutf8::decode_strict(&[0xc0, 0x80]).unwrap(), "\0");
+}
+
+#[test]
+fn every_overlong_2_and_3_byte_encoding_is_rejected() {
+    for first in 0xc0..=0xc1 {
+        for second in 0x80..=0xbf {
+            if [first, second] != [0xc0, 0x80] {
+                assert_rejected_as_overlong(&[first, second]);
+            }
+        }
+    }
+
+    for second in 0x80..=0x9f {
+        for third in 0x80..=0xbf {
+            assert_rejected_as_overlong(&[0xe0, second, third]);
+        }
+    }
+}
+
+#[test]
+fn cesu8_rejects_2_byte_overlong_encodings_anywhere() {
+    // NOTE: Long runs of valid input are copied in bulk rather than stepped
+    // through, so the overlong encoding has to be caught wherever it lands,
+    // including `0xc0 0x80`, which only MUTF-8 accepts as null.
+    for prefix in ["", "a", &"Hello, world!".repeat(10), &"ȅ€💖".repeat(10)] {
+        for overlong in [[0xc0, 0x80], [0xc1, 0xbf]] {
+            let mut bytes = simd_cesu8::encode(prefix).into_owned();
+            let offset = bytes.len();
+            bytes.extend_from_slice(&overlong);
+            bytes.extend_from_slice(b"abc");
+
+            assert!(simd_cesu8::decode(&bytes).is_err(), "{bytes:x?}");
+            assert!(simd_cesu8::decode_strict(&bytes).is_err(), "{bytes:x?}");
+
+            let error = simd_cesu8::decode_verbose(&bytes).unwrap_err();
+            assert_eq!(error.offset(), offset, "{bytes:x?}");
+            assert_eq!(error.byte(), overlong[0], "{bytes:x?}");
+            assert_eq!(error.kind(), DecodingErrorKind::OverlongEncoding);
+
+            let config = DecodeConfig {
+                allow_utf8_4_byte: true,
+                ..DecodeConfig::new(Flavor::Cesu8)
+            };
+            assert!(
+                simd_cesu8::decode_opts(&bytes, config).is_err(),
+                "{bytes:x?}"
+            );
+
+            assert_eq!(
+                mutf8::decode(&bytes).is_ok(),
+                overlong == [0xc0, 0x80],
+                "{bytes:x?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn shortest_3_byte_encoding_is_accepted() {
+    let bytes = [0xe0, 0xa0, 0x80];
+    assert_eq!(simd_cesu8::decode_strict(&bytes).unwrap(), "\u{800}");
+    assert_eq!(mutf8::decode_strict(&bytes).unwrap(), "\u{800}");
+}
+
+fn assert_rejected_as_invalid_surrogate_pair(bytes: &[u8]) {
+    assert!(simd_cesu8::decode(bytes).is_err(), "{bytes:x?}");
+    assert!(simd_cesu8::decode_strict(bytes).is_err(), "{bytes:x?}");
+    assert!(!simd_cesu8::is_valid_cesu8(bytes), "{bytes:x?}");
+    assert_eq!(
+        simd_cesu8::decode_verbose(bytes).unwrap_err().kind(),
+        DecodingErrorKind::InvalidSurrogatePair,
+        "{bytes:x?}"
+    );
+    assert!(mutf8::decode(bytes).is_err(), "{bytes:x?}");
+    assert!(mutf8::decode_strict(bytes).is_err(), "{bytes:x?}");
+    assert!(!mutf8::is_valid_mutf8(bytes), "{bytes:x?}");
+}
+
+fn assert_rejected_as_overlong(bytes: &[u8]) {
+    let replaced = "\u{fffd}".repeat(bytes.len());
+
+    assert!(simd_cesu8::decode(bytes).is_err(), "{bytes:x?}");
+    assert!(simd_cesu8::decode_strict(bytes).is_err(), "{bytes:x?}");
+    assert!(!simd_cesu8::is_valid_cesu8(bytes), "{bytes:x?}");
+    assert_eq!(
+        simd_cesu8::decode_verbose(bytes).unwrap_err().kind(),
+        DecodingErrorKind::OverlongEncoding,
+        "{bytes:x?}"
+    );
+    assert_eq!(simd_cesu8::decode_lossy(bytes), replaced, "{bytes:x?}");
+
+    if bytes != [0xc0, 0x80] {
+        assert!(mutf8::decode(bytes).is_err(), "{bytes:x?}");
+        assert!(mutf8::decode_strict(bytes).is_err(), "{bytes:x?}");
+        assert!(!mutf8::is_valid_mutf8(bytes), "{bytes:x?}");
+        assert_eq!(
+            mutf8::decode_verbose(bytes).unwrap_err().kind(),
+            DecodingErrorKind::OverlongEncoding,
+            "{bytes:x?}"
+        );
+        assert_eq!(mutf8::decode_lossy(bytes), replaced, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn cesu8_null_is_a_single_byte() {
+    for value in ["\0", "a\0b", "\0\0\0", "\0💖\0"] {
+        let encoded = simd_cesu8::encode(value);
+        assert!(!encoded.contains(&0xc0));
+        assert_eq!(simd_cesu8::decode_strict(&encoded).unwrap(), value);
+    }
+
+    assert_eq!(simd_cesu8::encode("a\0b"), Cow::Borrowed(b"a\0b"));
+    assert_eq!(
+        simd_cesu8::encode("\0💖"),
+        Cow::<[u8]>::Owned(vec![0x00, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96])
+    );
+}
+
+#[test]
+fn cesu8_decodes_null_byte_as_null() {
+    let bytes = [b'a', 0x00, b'b'];
+    assert_eq!(simd_cesu8::decode(&bytes).unwrap(), Cow::Borrowed("a\0b"));
+    assert_eq!(simd_cesu8::decode_strict(&bytes).unwrap(), "a\0b");
+    assert_eq!(simd_cesu8::decode_lossy(&bytes), "a\0b");
+    assert_eq!(simd_cesu8::decode_lossy_strict(&bytes), "a\0b");
+
+    let mut bytes = vec![0x00];
+    bytes.extend_from_slice(&SURROGATE_PAIR);
+    bytes.push(0x00);
+    assert_eq!(simd_cesu8::decode(&bytes).unwrap(), "\0💖\0");
+    assert_eq!(simd_cesu8::decode_strict(&bytes).unwrap(), "\0💖\0");
+}
+
+#[test]
+fn cesu8_and_mutf8_disagree_on_null() {
+    assert_eq!(simd_cesu8::encode("\0").as_ref(), &[0x00]);
+    assert_eq!(mutf8::encode("\0").as_ref(), &[0xc0, 0x80]);
+
+    // NOTE: `0xc0 0x80` is not valid CESU-8, and a raw `0x00` is not valid
+    // MUTF-8, so each flavor rejects the other's null when decoding strictly.
+    assert!(simd_cesu8::decode_strict(&[0xc0, 0x80]).is_err());
+    assert!(mutf8::decode_strict(&[0x00]).is_err());
+}
+
+#[test]
+fn large_input_is_encoded_without_slack() {
+    let value: String = "a\0ȅ€💖".chars().cycle().take(100_000).collect();
+    assert!(value.len() > 64 * 1024);
+
+    let Cow::Owned(encoded) = simd_cesu8::encode(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_cesu8(&value));
+    assert_eq!(encoded.capacity(), encoded.len());
+
+    let Cow::Owned(encoded) = mutf8::encode(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_java_cesu8(&value));
+    assert_eq!(encoded.capacity(), encoded.len());
+}
+
+#[test]
+fn small_input_is_encoded_with_little_slack() {
+    // NOTE: The input has to be long enough that the slack is worth giving
+    // back, but short enough that it isn't counted exactly up front.
+    let value = format!("{}💖", "a".repeat(10_000));
+
+    let Cow::Owned(encoded) = simd_cesu8::encode(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_cesu8(&value));
+    assert!(encoded.capacity() - encoded.len() <= encoded.len() / 4);
+
+    let Cow::Owned(encoded) = mutf8::encode(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_java_cesu8(&value));
+    assert!(encoded.capacity() - encoded.len() <= encoded.len() / 4);
+}
+
+#[test]
+fn short_input_is_encoded_exactly_without_slack() {
+    let value = format!("{}💖", "a".repeat(100));
+
+    let Cow::Owned(encoded) = simd_cesu8::encode_exact(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_cesu8(&value));
+    assert_eq!(encoded.capacity(), encoded.len());
+
+    let Cow::Owned(encoded) = mutf8::encode_exact(&value) else {
+        panic!("expected an owned encoding");
+    };
+    assert_eq!(encoded, *cesu8::to_java_cesu8(&value));
+    assert_eq!(encoded.capacity(), encoded.len());
+}
+
+#[test]
+fn mutf8_surrogate_pair_then_null_round_trips() {
+    let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xc0, 0x80];
+    let value = "💖\0";
+
+    assert_eq!(mutf8::decode(&bytes).unwrap(), value);
+    assert_eq!(mutf8::decode_strict(&bytes).unwrap(), value);
+    assert_eq!(mutf8::decode_lossy(&bytes), value);
+    assert_eq!(mutf8::decode_lossy_strict(&bytes), value);
+    assert_eq!(mutf8::encode(value).as_ref(), bytes);
+
+    let decoded = mutf8::decode(&bytes).unwrap();
+    assert_eq!(decoded.chars().collect::<Vec<_>>(), ['💖', '\0']);
+}
+
+#[test]
+fn mutf8_null_then_surrogate_pair_round_trips() {
+    let bytes = [0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+    let value = "\0💖";
+
+    assert_eq!(mutf8::decode_strict(&bytes).unwrap(), value);
+    assert_eq!(mutf8::encode(value).as_ref(), bytes);
+}
+
+#[test]
+fn mutf8_alternating_surrogate_pairs_and_nulls_round_trip() {
+    let mut bytes = Vec::new();
+    let mut value = String::new();
+
+    for _ in 0..64 {
+        bytes.extend_from_slice(&SURROGATE_PAIR);
+        bytes.extend_from_slice(&[0xc0, 0x80, 0xc0, 0x80]);
+        bytes.extend_from_slice(&SURROGATE_PAIR);
+        value.push_str("💖\0\0💖");
+    }
+
+    assert_eq!(mutf8::decode_strict(&bytes).unwrap(), value);
+    assert_eq!(mutf8::encode(&value).as_ref(), bytes);
+
+    // NOTE: Truncating the input right after a surrogate pair must not affect
+    // how the pair itself decodes.
+    assert_eq!(mutf8::decode_lossy_strict(&bytes[..7]), "💖\u{fffd}");
+}
+
+#[test]
+fn ascii_runs_of_every_length_between_transformed_chars_round_trip() {
+    for len in 0..=80 {
+        let run = "a".repeat(len);
+        let value = format!("{run}💖{run}\0{run}ȅ{run}\0\0{run}");
+
+        let encoded = simd_cesu8::encode(&value);
+        assert_eq!(simd_cesu8::decode(&encoded).unwrap(), value, "{len}");
+
+        let encoded = mutf8::encode(&value);
+        assert_eq!(mutf8::decode(&encoded).unwrap(), value, "{len}");
+
+        // NOTE: An invalid byte in the middle of a run must still be found.
+        let mut bytes = run.clone().into_bytes();
+        bytes.push(0xff);
+        bytes.extend_from_slice(run.as_bytes());
+        let expected = format!("{run}\u{fffd}{run}");
+        assert_eq!(simd_cesu8::decode_lossy(&bytes), expected, "{len}");
+        assert_eq!(mutf8::decode_lossy(&bytes), expected, "{len}");
+    }
+}
+
+#[test]
+fn mutf8_encodes_ascii_runs_of_every_length_between_nulls() {
+    for len in 0..=80 {
+        let run = "a".repeat(len);
+        let value = format!("{run}\0{run}\0\0{run}");
+
+        let mut expected = Vec::new();
+
+        for (i, part) in value.split('\0').enumerate() {
+            if i != 0 {
+                expected.extend_from_slice(&[0xc0, 0x80]);
+            }
+
+            expected.extend_from_slice(part.as_bytes());
+        }
+
+        assert_eq!(mutf8::encode(&value).as_ref(), expected, "{len}");
+    }
+}
+
+#[test]
+fn empty_input_is_borrowed() {
+    let value = "";
+    let bytes: &[u8] = &[];
+
+    assert!(matches!(simd_cesu8::encode(value), Cow::Borrowed(b"")));
+    assert!(matches!(mutf8::encode(value), Cow::Borrowed(b"")));
+
+    assert!(matches!(simd_cesu8::decode(bytes), Ok(Cow::Borrowed(""))));
+    assert!(matches!(
+        simd_cesu8::decode_strict(bytes),
+        Ok(Cow::Borrowed(""))
+    ));
+    assert!(matches!(mutf8::decode(bytes), Ok(Cow::Borrowed(""))));
+    assert!(matches!(mutf8::decode_strict(bytes), Ok(Cow::Borrowed(""))));
+
+    assert!(matches!(simd_cesu8::decode_lossy(bytes), Cow::Borrowed("")));
+    assert!(matches!(
+        simd_cesu8::decode_lossy_strict(bytes),
+        Cow::Borrowed("")
+    ));
+    assert!(matches!(mutf8::decode_lossy(bytes), Cow::Borrowed("")));
+    assert!(matches!(
+        mutf8::decode_lossy_strict(bytes),
+        Cow::Borrowed("")
+    ));
+}
+
+#[test]
+fn encode_assume_no_astral_matches_encode() {
+    for value in ["", "Hello, world!", "a\0b", "ȅ€\u{fffd}\u{ffff}"] {
+        assert_eq!(
+            simd_cesu8::encode_assume_no_astral(value),
+            &*simd_cesu8::encode(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "4-byte character"]
+fn encode_assume_no_astral_checks_the_promise_in_debug_builds() {
+    let _ = simd_cesu8::encode_assume_no_astral("a💖");
+}
+
+// NOTE: `core::error::Error` is only stable from Rust 1.81, which the build
+// script detects, and this doesn't depend on the `std` feature.
+#[cfg(core_error)]
+#[test]
+fn decoding_error_implements_core_error() {
+    fn source<E: core::error::Error>(error: &E) -> Option<&(dyn core::error::Error + 'static)> {
+        error.source()
+    }
+
+    let error = simd_cesu8::decode(&[0xff]).unwrap_err();
+    assert!(source(&error).is_none());
+}
@@ -0,0 +1,32 @@
+//! Tests for the extension traits.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{mutf8, Cesu8BytesExt, Cesu8StrExt};
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn methods_match_the_free_functions() {
+    for value in VALUES {
+        assert_eq!(value.to_cesu8(), simd_cesu8::encode(value));
+        assert_eq!(value.to_mutf8(), mutf8::encode(value));
+
+        for bytes in [value.to_cesu8(), value.to_mutf8(), value.as_bytes().into()] {
+            assert_eq!(bytes.from_cesu8(), simd_cesu8::decode(&bytes));
+            assert_eq!(bytes.from_cesu8_lossy(), simd_cesu8::decode_lossy(&bytes));
+            assert_eq!(bytes.from_mutf8(), mutf8::decode(&bytes));
+            assert_eq!(bytes.from_mutf8_lossy(), mutf8::decode_lossy(&bytes));
+        }
+    }
+}
+
+#[test]
+fn methods_chain() {
+    let encoded = "a\0💖"
+        .to_mutf8()
+        .from_mutf8()
+        .unwrap()
+        .to_cesu8()
+        .into_owned();
+    assert_eq!(encoded, *simd_cesu8::encode("a\0💖"));
+}
@@ -0,0 +1,58 @@
+//! Tests for the encoding and decoding functions that never panic.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{mutf8, DecodingError, TryDecodeError};
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn try_encode_matches_encode() {
+    for value in VALUES {
+        assert_eq!(
+            simd_cesu8::try_encode(value).unwrap(),
+            simd_cesu8::encode(value)
+        );
+        assert_eq!(mutf8::try_encode(value).unwrap(), mutf8::encode(value));
+    }
+}
+
+#[test]
+fn try_decode_matches_decode() {
+    let inputs: [&[u8]; 6] = [
+        b"Hello, world!",
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+        &[b'a', 0xc0, 0x80],
+        &[b'a', 0x00],
+        &[0xed, 0xa0, 0xbd],
+        &[0xf0, 0x9f, 0x92, 0x96, 0xff],
+    ];
+
+    for bytes in inputs {
+        assert_eq!(
+            simd_cesu8::try_decode(bytes),
+            simd_cesu8::decode(bytes).map_err(TryDecodeError::from),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            mutf8::try_decode(bytes),
+            mutf8::decode(bytes).map_err(TryDecodeError::from),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn try_decode_error_displays_the_cause() {
+    let error = simd_cesu8::try_decode(&[0xff]).unwrap_err();
+    assert_eq!(
+        error,
+        TryDecodeError::Decoding(DecodingError::from(
+            simd_cesu8::decode_verbose(&[0xff]).unwrap_err()
+        ))
+    );
+    assert_eq!(error.to_string(), "invalid input");
+    assert_eq!(
+        TryDecodeError::Allocation.to_string(),
+        "failed to allocate the decoded output"
+    );
+}
@@ -0,0 +1,36 @@
+//! Tests for choosing the flavor at runtime.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{mutf8, Flavor};
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn encode_with_matches_the_flavor_functions() {
+    for value in VALUES {
+        assert_eq!(
+            simd_cesu8::encode_with(value, Flavor::Cesu8),
+            simd_cesu8::encode(value)
+        );
+        assert_eq!(
+            simd_cesu8::encode_with(value, Flavor::Mutf8),
+            mutf8::encode(value)
+        );
+    }
+}
+
+#[test]
+fn decode_with_matches_the_flavor_functions() {
+    for value in VALUES {
+        for bytes in [simd_cesu8::encode(value), mutf8::encode(value)] {
+            assert_eq!(
+                simd_cesu8::decode_with(&bytes, Flavor::Cesu8),
+                simd_cesu8::decode(&bytes)
+            );
+            assert_eq!(
+                simd_cesu8::decode_with(&bytes, Flavor::Mutf8),
+                mutf8::decode(&bytes)
+            );
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Tests for decoding in place, where the write cursor lags behind the read
+//! cursor.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+#[test]
+fn decodes_back_to_back_surrogate_pairs_and_nulls() {
+    let values = [
+        "💖💖💖",
+        "\0\0\0",
+        "\0💖\0💖",
+        "💖\0💖\0",
+        "a💖b\0c€dȅ",
+        "💖ȅ€abc\0\0💖",
+    ];
+
+    for value in values {
+        let mut bytes = simd_cesu8::encode(value).into_owned();
+        assert_eq!(simd_cesu8::decode_in_place(&mut bytes).unwrap(), value);
+
+        let mut bytes = mutf8::encode(value).into_owned();
+        assert_eq!(mutf8::decode_in_place(&mut bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn shrinks_to_the_decoded_length() {
+    let mut bytes = mutf8::encode("\0💖a").into_owned();
+    assert_eq!(bytes.len(), 2 + 6 + 1);
+
+    let decoded = mutf8::decode_in_place(&mut bytes).unwrap();
+    assert_eq!(decoded.len(), 1 + 4 + 1);
+}
+
+#[test]
+fn returns_utf8_as_is() {
+    let mut bytes = "a\0💖".as_bytes().to_vec();
+    let ptr = bytes.as_ptr();
+    let decoded = mutf8::decode_in_place(&mut bytes).unwrap();
+    assert_eq!(decoded, "a\0💖");
+    assert_eq!(decoded.as_ptr(), ptr);
+}
+
+#[test]
+fn leaves_invalid_input_untouched() {
+    let mut bytes: Vec<u8> = mutf8::encode("\0💖").into_owned();
+    bytes.push(0xed);
+    let original = bytes.clone();
+
+    assert!(simd_cesu8::decode_in_place(&mut bytes).is_err());
+    assert!(mutf8::decode_in_place(&mut bytes).is_err());
+    assert_eq!(bytes, original);
+}
+
+#[test]
+fn agrees_with_decode() {
+    let mut encoded = Vec::new();
+
+    for value in ["a", "\0", "💖", "€"] {
+        encoded.extend_from_slice(&mutf8::encode(value));
+    }
+
+    for start in 0..encoded.len() {
+        for end in start..=encoded.len() {
+            let slice = &encoded[start..end];
+            let expected = mutf8::decode(slice).ok();
+
+            let mut bytes = slice.to_vec();
+            let decoded = mutf8::decode_in_place(&mut bytes).ok();
+            assert_eq!(decoded.as_deref(), expected.as_deref(), "{slice:x?}");
+
+            let expected = simd_cesu8::decode(slice).ok();
+            let mut bytes = slice.to_vec();
+            let decoded = simd_cesu8::decode_in_place(&mut bytes).ok();
+            assert_eq!(decoded.as_deref(), expected.as_deref(), "{slice:x?}");
+        }
+    }
+}
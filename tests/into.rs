@@ -0,0 +1,210 @@
+//! Tests for encoding and decoding into buffers owned by the caller.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn encode_into_matches_encode() {
+    for value in VALUES {
+        let mut out = Vec::new();
+        simd_cesu8::encode_into(value, &mut out);
+        assert_eq!(out, *simd_cesu8::encode(value), "{value:?}");
+
+        let mut out = Vec::new();
+        mutf8::encode_into(value, &mut out);
+        assert_eq!(out, *mutf8::encode(value), "{value:?}");
+    }
+}
+
+#[test]
+fn encode_into_appends() {
+    let mut out = b"prefix".to_vec();
+    let mut expected = out.clone();
+
+    for value in VALUES {
+        simd_cesu8::encode_into(value, &mut out);
+        expected.extend_from_slice(&simd_cesu8::encode(value));
+    }
+
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn encode_into_reuses_capacity() {
+    let value = "a💖".repeat(100);
+    let mut out = Vec::with_capacity(value.len() * 2);
+    let ptr = out.as_ptr();
+
+    simd_cesu8::encode_into(&value, &mut out);
+    out.clear();
+    mutf8::encode_into(&value, &mut out);
+
+    assert_eq!(out.as_ptr(), ptr);
+}
+
+#[test]
+fn encode_into_reserves_the_encoded_len_when_appending() {
+    let value = "a💖".repeat(100);
+    let mut out = Vec::with_capacity(1 + simd_cesu8::encoded_len(&value));
+    out.push(b'a');
+    let ptr = out.as_ptr();
+
+    simd_cesu8::encode_into(&value, &mut out);
+    assert_eq!(out.as_ptr(), ptr);
+
+    let mut out = Vec::with_capacity(1 + mutf8::encoded_len(&value));
+    out.push(b'a');
+    let ptr = out.as_ptr();
+
+    mutf8::encode_into(&value, &mut out);
+    assert_eq!(out.as_ptr(), ptr);
+}
+
+#[test]
+fn encode_pool_spans_match_encode() {
+    let (pool, spans) = simd_cesu8::encode_pool(&VALUES);
+    assert_eq!(spans.len(), VALUES.len());
+    assert_eq!(pool.len(), spans.iter().map(|&(_, len)| len).sum::<usize>());
+
+    for (value, (offset, len)) in VALUES.into_iter().zip(spans) {
+        assert_eq!(
+            pool[offset..][..len],
+            *simd_cesu8::encode(value),
+            "{value:?}"
+        );
+    }
+
+    let (pool, spans) = mutf8::encode_pool(&VALUES);
+    assert_eq!(spans.len(), VALUES.len());
+
+    for (value, (offset, len)) in VALUES.into_iter().zip(spans) {
+        assert_eq!(pool[offset..][..len], *mutf8::encode(value), "{value:?}");
+    }
+}
+
+#[test]
+fn encode_pool_of_nothing_is_empty() {
+    let (pool, spans) = simd_cesu8::encode_pool(&[]);
+    assert!(pool.is_empty());
+    assert!(spans.is_empty());
+}
+
+#[test]
+fn decode_into_matches_decode() {
+    for value in VALUES {
+        let encoded = simd_cesu8::encode(value);
+        let mut out = String::new();
+        simd_cesu8::decode_into(&encoded, &mut out).unwrap();
+        assert_eq!(out, value);
+
+        let encoded = mutf8::encode(value);
+        let mut out = String::new();
+        mutf8::decode_into(&encoded, &mut out).unwrap();
+        assert_eq!(out, value);
+    }
+}
+
+#[test]
+fn decode_into_appends() {
+    let mut out = String::from("prefix");
+
+    for value in VALUES {
+        mutf8::decode_into(&mutf8::encode(value), &mut out).unwrap();
+    }
+
+    assert_eq!(
+        out,
+        ["prefix"].into_iter().chain(VALUES).collect::<String>()
+    );
+}
+
+#[test]
+fn decode_into_never_needs_more_room_than_the_input() {
+    let bytes = mutf8::encode(&"a\0💖".repeat(100)).into_owned();
+    let mut out = String::with_capacity(6 + bytes.len());
+    out.push_str("prefix");
+    let ptr = out.as_ptr();
+
+    mutf8::decode_into(&bytes, &mut out).unwrap();
+    assert_eq!(out.as_ptr(), ptr);
+}
+
+#[test]
+fn decoded_len_bound_bounds_decode() {
+    for value in VALUES {
+        for bytes in [simd_cesu8::encode(value), value.as_bytes().into()] {
+            let bound = simd_cesu8::decoded_len_bound(&bytes);
+            assert!(simd_cesu8::decode(&bytes).unwrap().len() <= bound);
+        }
+
+        for bytes in [mutf8::encode(value), value.as_bytes().into()] {
+            let bound = mutf8::decoded_len_bound(&bytes);
+            assert!(mutf8::decode(&bytes).unwrap().len() <= bound);
+        }
+    }
+}
+
+#[test]
+fn decode_into_accepts_utf8() {
+    let mut out = String::new();
+    simd_cesu8::decode_into("💖".as_bytes(), &mut out).unwrap();
+    mutf8::decode_into(b"\0", &mut out).unwrap();
+    assert_eq!(out, "💖\0");
+}
+
+#[test]
+fn decode_into_keeps_a_valid_prefix_on_error() {
+    let mut bytes = simd_cesu8::encode("a💖").into_owned();
+    bytes.push(0xed);
+
+    let mut out = String::from("prefix");
+    assert!(simd_cesu8::decode_into(&bytes, &mut out).is_err());
+    assert!("prefixa💖".starts_with(&out));
+    assert!(out.starts_with("prefix"));
+}
+
+#[test]
+fn encoded_len_matches_encode() {
+    let long = "a\0💖ȅ€".repeat(1000);
+    let values = VALUES.into_iter().chain([long.as_str()]);
+
+    for value in values {
+        assert_eq!(
+            simd_cesu8::encoded_len(value),
+            simd_cesu8::encode(value).len()
+        );
+        assert_eq!(mutf8::encoded_len(value), mutf8::encode(value).len());
+    }
+}
+
+#[test]
+fn counts_add_up_to_encoded_len() {
+    let long = "a\0💖ȅ€".repeat(1000);
+    let values = VALUES.into_iter().chain([long.as_str()]);
+
+    for value in values {
+        let surrogate_pairs = simd_cesu8::surrogate_header_count(value);
+        assert_eq!(
+            surrogate_pairs,
+            value.chars().filter(|c| c.len_utf8() == 4).count()
+        );
+        assert_eq!(mutf8::surrogate_header_count(value), surrogate_pairs);
+        assert_eq!(mutf8::null_count(value), value.matches('\0').count());
+
+        assert_eq!(
+            simd_cesu8::encoded_len(value),
+            value.len() + 2 * surrogate_pairs
+        );
+        assert_eq!(
+            mutf8::encoded_len(value),
+            value.len() + 2 * surrogate_pairs + mutf8::null_count(value)
+        );
+    }
+}
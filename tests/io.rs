@@ -0,0 +1,195 @@
+//! Tests for the `std::io` adapters.
+#![cfg(feature = "std")]
+// NOTE: Newer Clippy suggests `core::io::ErrorKind`, which is newer than our
+// minimum supported Rust version.
+#![allow(clippy::std_instead_of_core)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use std::io::{self, ErrorKind, Read, Write};
+
+use simd_cesu8::{mutf8, Cesu8Reader, Cesu8Writer};
+
+const VALUES: [&str; 5] = ["", "Hello, world!", "\0", "a\0💖ȅ€", "💖💖"];
+
+fn write_in_chunks(bytes: &[u8], size: usize) -> io::Result<Vec<u8>> {
+    let mut writer = Cesu8Writer::new(Vec::new());
+
+    for chunk in bytes.chunks(size) {
+        writer.write_all(chunk)?;
+    }
+
+    writer.finish()
+}
+
+#[test]
+fn writer_matches_encode_for_every_chunk_size() {
+    for value in VALUES {
+        let expected = simd_cesu8::encode(value);
+
+        for size in 1..=value.len().max(1) {
+            let encoded = write_in_chunks(value.as_bytes(), size).unwrap();
+            assert_eq!(encoded, *expected, "{value:?} {size}");
+        }
+    }
+}
+
+#[test]
+fn writer_holds_on_to_a_cut_off_character() {
+    let mut writer = Cesu8Writer::new(Vec::new());
+
+    for &byte in "💖".as_bytes() {
+        assert!(writer.get_ref().is_empty());
+        assert_eq!(writer.write(&[byte]).unwrap(), 1);
+    }
+
+    assert!(!writer.has_pending());
+    assert_eq!(writer.finish().unwrap(), [
+        0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96
+    ]);
+}
+
+#[test]
+fn writer_finish_rejects_a_dangling_character() {
+    let mut writer = Cesu8Writer::new(Vec::new());
+    writer.write_all(&"a💖".as_bytes()[..3]).unwrap();
+    assert!(writer.has_pending());
+
+    // NOTE: Flushing is fine, since the character might still be completed.
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref(), b"a");
+
+    let error = writer.finish().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn writer_writes_up_to_invalid_utf8() {
+    let mut writer = Cesu8Writer::new(Vec::new());
+    assert_eq!(writer.write(&[b'a', b'b', 0xff, b'c']).unwrap(), 2);
+
+    let error = writer.write(&[0xff, b'c']).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert_eq!(writer.get_ref(), b"ab");
+}
+
+#[test]
+fn writer_rejects_a_character_completed_with_invalid_utf8() {
+    let mut writer = Cesu8Writer::new(Vec::new());
+    writer.write_all(&[0xe2, 0x82]).unwrap();
+
+    let error = writer.write(b"a").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+/// A reader that returns at most `size` bytes at a time.
+struct Chunked<'a> {
+    bytes: &'a [u8],
+    size: usize,
+}
+
+impl Read for Chunked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.bytes.len().min(self.size).min(buf.len());
+        let (chunk, rest) = self.bytes.split_at(len);
+        buf[..len].copy_from_slice(chunk);
+        self.bytes = rest;
+        Ok(len)
+    }
+}
+
+fn read_in_chunks(bytes: &[u8], size: usize, buf_size: usize) -> io::Result<Vec<u8>> {
+    let mut reader = Cesu8Reader::new(Chunked { bytes, size });
+    let mut decoded = Vec::new();
+    let mut buf = vec![0; buf_size];
+
+    loop {
+        let len = reader.read(&mut buf)?;
+
+        if len == 0 {
+            return Ok(decoded);
+        }
+
+        decoded.extend_from_slice(&buf[..len]);
+    }
+}
+
+#[test]
+fn reader_matches_decode_for_every_chunk_and_buffer_size() {
+    for value in VALUES {
+        let encoded = simd_cesu8::encode(value);
+
+        for size in 1..=encoded.len().max(1) {
+            for buf_size in 1..=4 {
+                let decoded = read_in_chunks(&encoded, size, buf_size).unwrap();
+                assert_eq!(decoded, value.as_bytes(), "{value:?} {size} {buf_size}");
+            }
+        }
+    }
+}
+
+#[test]
+fn reader_works_with_io_copy() {
+    let value = "a\0💖ȅ€".repeat(4096);
+    let encoded = simd_cesu8::encode(&value);
+
+    let mut reader = Cesu8Reader::new(&*encoded);
+    let mut decoded = Vec::new();
+    io::copy(&mut reader, &mut decoded).unwrap();
+    assert_eq!(decoded, value.as_bytes());
+
+    // NOTE: Round-tripping through both adapters gives back the input.
+    let mut writer = Cesu8Writer::new(Vec::new());
+    io::copy(&mut Cesu8Reader::new(&*encoded), &mut writer).unwrap();
+    assert_eq!(writer.finish().unwrap(), *encoded);
+}
+
+#[test]
+fn reader_rejects_invalid_input() {
+    let cases: [&[u8]; 4] = [
+        &[b'a', 0xff],
+        &[0xf0, 0x9f, 0x92, 0x96],
+        &[0xed, 0xa0, 0xbd, b'a', b'b', b'c'],
+        &[b'a', 0xed, 0xa0, 0xbd, 0xed],
+    ];
+
+    for bytes in cases {
+        for size in 1..=bytes.len() {
+            let error = read_in_chunks(bytes, size, 8).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData, "{bytes:x?} {size}");
+        }
+    }
+}
+
+#[test]
+fn decoding_error_converts_to_invalid_data() {
+    let error = io::Error::from(simd_cesu8::decode(&[0xff]).unwrap_err());
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "invalid input");
+    assert!(error.get_ref().unwrap().is::<simd_cesu8::DecodingError>());
+}
+
+#[test]
+fn encode_to_writer_matches_encode() {
+    for value in VALUES {
+        let mut encoded = Vec::new();
+        simd_cesu8::encode_to_writer(&mut encoded, value).unwrap();
+        assert_eq!(encoded, *simd_cesu8::encode(value), "{value:?}");
+
+        let mut encoded = Vec::new();
+        mutf8::encode_to_writer(&mut encoded, value).unwrap();
+        assert_eq!(encoded, *mutf8::encode(value), "{value:?}");
+    }
+}
+
+#[test]
+fn encode_to_writer_stops_at_the_first_error() {
+    let mut buffer = [0; 4];
+    let mut writer = &mut buffer[..];
+
+    let error = simd_cesu8::encode_to_writer(&mut writer, "a💖b").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WriteZero);
+    // NOTE: The surrogate pair didn't fit, so only the first run was written.
+    assert_eq!(buffer, [b'a', 0xed, 0xa0, 0xbd]);
+}
@@ -0,0 +1,88 @@
+//! Tests for validating without decoding.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::{mutf8, Flavor};
+
+#[test]
+fn agrees_with_decode_strict() {
+    let mut bytes = Vec::new();
+
+    for value in ["a", "\0", "💖", "€", "ȅ"] {
+        bytes.extend_from_slice(&mutf8::encode(value));
+        bytes.extend_from_slice(&simd_cesu8::encode(value));
+        bytes.extend_from_slice(value.as_bytes());
+    }
+
+    for start in 0..bytes.len() {
+        for end in start..=bytes.len() {
+            let slice = &bytes[start..end];
+
+            assert_eq!(
+                simd_cesu8::is_valid_cesu8(slice),
+                simd_cesu8::decode_strict(slice).is_ok(),
+                "{slice:x?}"
+            );
+            assert_eq!(
+                mutf8::is_valid_mutf8(slice),
+                mutf8::decode_strict(slice).is_ok(),
+                "{slice:x?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_input_is_valid() {
+    assert!(simd_cesu8::is_valid_cesu8(b""));
+    assert!(mutf8::is_valid_mutf8(b""));
+}
+
+#[test]
+fn null_rule_only_applies_to_mutf8() {
+    assert!(simd_cesu8::is_valid_cesu8(b"\0"));
+    assert!(!simd_cesu8::is_valid_cesu8(&[0xc0, 0x80]));
+    assert!(!mutf8::is_valid_mutf8(b"\0"));
+    assert!(mutf8::is_valid_mutf8(&[0xc0, 0x80]));
+}
+
+#[test]
+fn first_invalid_index_splits_off_a_valid_prefix() {
+    let mut bytes = Vec::new();
+
+    for value in ["a", "\0", "💖", "€", "ȅ"] {
+        bytes.extend_from_slice(&mutf8::encode(value));
+        bytes.extend_from_slice(&simd_cesu8::encode(value));
+        bytes.extend_from_slice(value.as_bytes());
+    }
+
+    for start in 0..bytes.len() {
+        for end in start..=bytes.len() {
+            let slice = &bytes[start..end];
+
+            for (flavor, is_valid) in [
+                (
+                    Flavor::Cesu8,
+                    simd_cesu8::is_valid_cesu8 as fn(&[u8]) -> bool,
+                ),
+                (Flavor::Mutf8, mutf8::is_valid_mutf8),
+            ] {
+                let index = simd_cesu8::first_invalid_index(slice, flavor);
+                assert_eq!(index.is_none(), is_valid(slice), "{flavor:?} {slice:x?}");
+
+                if let Some(index) = index {
+                    let (clean, rest) = slice.split_at(index);
+                    assert!(is_valid(clean), "{flavor:?} {slice:x?}");
+                    assert_eq!(
+                        simd_cesu8::first_invalid_index(rest, flavor),
+                        Some(0),
+                        "{flavor:?} {slice:x?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Tests for the lazy encoding and decoding iterators.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+const INPUTS: [&[u8]; 12] = [
+    b"",
+    b"Hello, world!",
+    &[b'a', 0x00, b'b'],
+    &[b'a', 0xc0, 0x80, b'b'],
+    &[0xc8, 0x85, 0xe2, 0x82, 0xac],
+    &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'a'],
+    &[0xf0, 0x9f, 0x92, 0x96],
+    &[0xed, 0xa0, 0xbd],
+    &[0xed, 0xa0, 0xbd, b'a', b'b', b'c'],
+    &[0xed, 0xb2, 0x96],
+    &[b'a', 0xe2, 0x82],
+    &[b'a', 0xff, b'b'],
+];
+
+#[test]
+fn chars_match_decode_strict() {
+    for bytes in INPUTS {
+        assert_eq!(
+            simd_cesu8::decode_chars(bytes).collect::<Result<String, _>>(),
+            simd_cesu8::decode_strict(bytes).map(String::from),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            mutf8::decode_chars(bytes).collect::<Result<String, _>>(),
+            mutf8::decode_strict(bytes).map(String::from),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn chars_stop_after_an_error() {
+    for bytes in INPUTS {
+        let mut chars = simd_cesu8::decode_chars(bytes);
+
+        while let Some(char) = chars.next() {
+            if char.is_err() {
+                assert_eq!(chars.next(), None, "{bytes:x?}");
+                assert!(chars.as_bytes().is_empty(), "{bytes:x?}");
+            }
+        }
+
+        assert_eq!(chars.next(), None, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn chars_never_look_past_the_character_returned() {
+    let bytes = [0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xc0, 0x80, 0xff];
+    let mut chars = mutf8::decode_chars(&bytes);
+
+    assert_eq!(chars.next(), Some(Ok('💖')));
+    assert_eq!(chars.as_bytes(), [0xc0, 0x80, 0xff]);
+    assert_eq!(chars.next(), Some(Ok('\0')));
+    assert_eq!(chars.as_bytes(), [0xff]);
+    assert!(chars.next().unwrap().is_err());
+}
+
+#[test]
+fn size_hint_is_correct() {
+    for bytes in INPUTS {
+        let (lower, upper) = simd_cesu8::decode_chars(bytes).size_hint();
+        let count = simd_cesu8::decode_chars(bytes).count();
+        assert!(lower <= count && count <= upper.unwrap(), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn encoded_bytes_match_encode() {
+    for value in VALUES {
+        assert_eq!(
+            simd_cesu8::encode_bytes(value).collect::<Vec<_>>(),
+            *simd_cesu8::encode(value),
+            "{value:?}"
+        );
+        assert_eq!(
+            mutf8::encode_bytes(value).collect::<Vec<_>>(),
+            *mutf8::encode(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn encoded_bytes_know_how_many_are_left() {
+    for value in VALUES {
+        let mut bytes = mutf8::encode_bytes(value);
+        let mut len = mutf8::encoded_len(value);
+
+        loop {
+            assert_eq!(bytes.len(), len, "{value:?}");
+            assert_eq!(bytes.size_hint(), (len, Some(len)), "{value:?}");
+
+            if bytes.next().is_none() {
+                break;
+            }
+
+            len -= 1;
+        }
+
+        assert_eq!(len, 0, "{value:?}");
+        assert_eq!(bytes.next(), None, "{value:?}");
+    }
+}
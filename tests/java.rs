@@ -0,0 +1,85 @@
+//! Tests for the Java-style strings with a `u16` length prefix.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn java_strings_round_trip() {
+    for value in VALUES {
+        let mut buffer = Vec::new();
+        mutf8::encode_java_string(value, &mut buffer).unwrap();
+        assert_eq!(buffer[2..], *cesu8::to_java_cesu8(value));
+
+        buffer.push(0xff);
+        let (decoded, consumed) = mutf8::decode_java_string(&buffer).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, buffer.len() - 1);
+    }
+}
+
+#[test]
+fn java_strings_can_be_read_back_to_back() {
+    let mut buffer = Vec::new();
+
+    for value in VALUES {
+        mutf8::encode_java_string(value, &mut buffer).unwrap();
+    }
+
+    let mut rest = &buffer[..];
+
+    for value in VALUES {
+        let (decoded, consumed) = mutf8::decode_java_string(rest).unwrap();
+        assert_eq!(decoded, value);
+        rest = &rest[consumed..];
+    }
+
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn truncated_java_strings_are_rejected() {
+    let cases: [&[u8]; 4] = [&[], &[0x00], &[0x00, 0x02, b'a'], &[0x01, 0x00, b'a']];
+
+    for bytes in cases {
+        assert!(mutf8::decode_java_string(bytes).is_err(), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn invalid_java_strings_are_rejected() {
+    let cases: [&[u8]; 3] = [
+        &[0x00, 0x01, 0x00],
+        &[0x00, 0x04, 0xf0, 0x9f, 0x92, 0x96],
+        &[0x00, 0x03, 0xed, 0xa0, 0xbd],
+    ];
+
+    for bytes in cases {
+        assert!(mutf8::decode_java_string(bytes).is_err(), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn java_string_length_must_fit_in_u16() {
+    // NOTE: Each null is encoded as two bytes, so this is exactly `u16::MAX`
+    // bytes once encoded.
+    let mut value = "\0".repeat(32767);
+    value.push('a');
+
+    let mut buffer = Vec::new();
+    mutf8::encode_java_string(&value, &mut buffer).unwrap();
+    assert_eq!(buffer[..2], [0xff, 0xff]);
+    assert_eq!(mutf8::decode_java_string(&buffer).unwrap().0, value);
+
+    value.pop();
+    value.push('\0');
+
+    let mut buffer = Vec::new();
+    assert!(mutf8::encode_java_string(&value, &mut buffer).is_err());
+    assert!(buffer.is_empty());
+}
@@ -0,0 +1,97 @@
+//! Tests that borrowed results carry the lifetime of the input, so they can
+//! outlive every local in the function that produced them.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+/// A zero-copy view over records stored back to back in one buffer, like a
+/// memory-mapped file.
+struct View<'a> {
+    records: Vec<Cow<'a, str>>,
+}
+
+impl<'a> View<'a> {
+    fn new(buffer: &'a [u8], lengths: &[usize]) -> Self {
+        let mut records = Vec::new();
+        let mut rest = buffer;
+
+        for &len in lengths {
+            let (record, tail) = rest.split_at(len);
+            records.push(simd_cesu8::decode(record).unwrap());
+            rest = tail;
+        }
+
+        Self { records }
+    }
+}
+
+fn decode_first_word(bytes: &[u8]) -> Cow<'_, str> {
+    let end = bytes
+        .iter()
+        .position(|&byte| byte == b' ')
+        .unwrap_or(bytes.len());
+    mutf8::decode_lossy(&bytes[..end])
+}
+
+fn is_borrowed_from(string: &str, buffer: &[u8]) -> bool {
+    buffer.as_ptr_range().contains(&string.as_ptr())
+}
+
+#[test]
+fn borrowed_decode_outlives_the_call() {
+    let buffer = b"Hello, world!".to_vec();
+    let view = View::new(&buffer, &[5, 8]);
+
+    assert_eq!(view.records, ["Hello", ", world!"]);
+
+    for record in &view.records {
+        assert!(matches!(record, Cow::Borrowed(_)));
+        assert!(is_borrowed_from(record, &buffer));
+    }
+}
+
+#[test]
+fn mixed_view_only_allocates_records_that_need_it() {
+    let mut buffer = b"plain".to_vec();
+    buffer.extend_from_slice(&[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+    let view = View::new(&buffer, &[5, 6]);
+
+    assert_eq!(view.records, ["plain", "💖"]);
+    assert!(matches!(view.records[0], Cow::Borrowed(_)));
+    assert!(matches!(view.records[1], Cow::Owned(_)));
+}
+
+#[test]
+fn every_borrowing_function_borrows_from_its_input() {
+    let buffer = b"Hello world".to_vec();
+
+    let first = decode_first_word(&buffer);
+    assert_eq!(first, "Hello");
+    assert!(is_borrowed_from(&first, &buffer));
+
+    let decoded = [
+        simd_cesu8::decode(&buffer).unwrap(),
+        simd_cesu8::decode_strict(&buffer).unwrap(),
+        simd_cesu8::decode_lossy(&buffer),
+        simd_cesu8::decode_lossy_strict(&buffer),
+        mutf8::decode(&buffer).unwrap(),
+        mutf8::decode_strict(&buffer).unwrap(),
+        mutf8::decode_lossy(&buffer),
+        mutf8::decode_lossy_strict(&buffer),
+    ];
+
+    for string in &decoded {
+        assert!(is_borrowed_from(string, &buffer));
+    }
+
+    let value = String::from("Hello world");
+    for bytes in [simd_cesu8::encode(&value), mutf8::encode(&value)] {
+        assert_eq!(bytes.as_ptr(), value.as_ptr());
+    }
+}
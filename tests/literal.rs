@@ -0,0 +1,68 @@
+//! Tests for the `cesu8!` and `mutf8!` macros, and the other functions that
+//! can be evaluated in a constant.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{cesu8, mutf8};
+
+const EMPTY: &[u8] = cesu8!("");
+static MIXED: &[u8] = mutf8!("a\0💖ȅ€\0");
+
+#[test]
+fn literals_match_encode() {
+    assert_eq!(
+        cesu8!("Hello, world!"),
+        &*simd_cesu8::encode("Hello, world!")
+    );
+    assert_eq!(cesu8!("\0"), &*simd_cesu8::encode("\0"));
+    assert_eq!(cesu8!("💖"), &*simd_cesu8::encode("💖"));
+    assert_eq!(cesu8!("a\0💖ȅ€"), &*simd_cesu8::encode("a\0💖ȅ€"));
+    assert_eq!(cesu8!("💖💖"), &*simd_cesu8::encode("💖💖"));
+
+    assert_eq!(mutf8!("Hello, world!"), &*mutf8::encode("Hello, world!"));
+    assert_eq!(mutf8!("\0"), &*mutf8::encode("\0"));
+    assert_eq!(mutf8!("💖"), &*mutf8::encode("💖"));
+    assert_eq!(mutf8!("a\0💖ȅ€"), &*mutf8::encode("a\0💖ȅ€"));
+    assert_eq!(mutf8!("💖💖"), &*mutf8::encode("💖💖"));
+}
+
+#[test]
+fn literals_can_initialize_constants_and_statics() {
+    assert!(EMPTY.is_empty());
+    assert_eq!(MIXED, &*mutf8::encode("a\0💖ȅ€\0"));
+}
+
+#[test]
+fn literals_accept_constant_expressions() {
+    const VALUE: &str = "\u{10ffff}";
+    assert_eq!(cesu8!(VALUE), &*simd_cesu8::encode(VALUE));
+    assert_eq!(mutf8!(VALUE,), &*mutf8::encode(VALUE));
+}
+
+#[test]
+fn needs_encoded_const_matches_needs_encoded() {
+    // NOTE: This fails to compile if the constant is wrong.
+    const _: () = assert!(simd_cesu8::needs_encoded_const("a💖"));
+    const _: () = assert!(!simd_cesu8::needs_encoded_const("a\0"));
+    const _: () = assert!(simd_cesu8::mutf8::needs_encoded_const("a\0"));
+
+    for value in [
+        "",
+        "Hello, world!",
+        "\0",
+        "ȅ€\u{ffff}",
+        "💖",
+        "a\0💖",
+        "\u{10ffff}",
+    ] {
+        assert_eq!(
+            simd_cesu8::needs_encoded_const(value),
+            simd_cesu8::needs_encoded(value),
+            "{value:?}"
+        );
+        assert_eq!(
+            simd_cesu8::mutf8::needs_encoded_const(value),
+            simd_cesu8::mutf8::needs_encoded(value),
+            "{value:?}"
+        );
+    }
+}
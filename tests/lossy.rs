@@ -0,0 +1,148 @@
+//! Tests that lossy decoding replaces invalid input the same way as
+//! `String::from_utf8_lossy`, wherever CESU-8 and MUTF-8 agree with UTF-8.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+/// A deterministic stream of pseudo-random numbers, so that every failure can
+/// be reproduced.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        // NOTE: This is xorshift64, which is plenty for spreading bits around.
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        usize::try_from(self.next() % bound as u64).unwrap()
+    }
+}
+
+/// Returns whether `byte` means the same thing in UTF-8, CESU-8, and MUTF-8.
+///
+/// NOTE: This leaves out null and `0xc0`, which MUTF-8 uses for null,
+/// `0xed`, which starts a surrogate, and the bytes that start a 4-byte
+/// character.
+fn is_shared(byte: u8) -> bool {
+    !matches!(byte, 0x00 | 0xc0 | 0xed | 0xf0..=0xf4)
+}
+
+/// Returns short random inputs, made of fragments of valid characters and
+/// stray bytes, that only use bytes every encoding agrees on.
+fn inputs() -> impl Iterator<Item = Vec<u8>> {
+    const PIECES: [&[u8]; 10] = [
+        b"a",
+        &[0xc8, 0x85],
+        &[0xe2, 0x82, 0xac],
+        &[0xe0, 0xa0, 0x80],
+        &[0xef, 0xbf, 0xbd],
+        &[0x80],
+        &[0xbf],
+        &[0xc1],
+        &[0xe0, 0x80],
+        &[0xf8, 0xff],
+    ];
+
+    let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+
+    (0..20_000).map(move |_| {
+        let mut bytes = Vec::new();
+
+        for _ in 0..rng.below(8) {
+            let piece = PIECES[rng.below(PIECES.len())];
+            // NOTE: Cutting pieces short is what makes truncated sequences.
+            let len = 1 + rng.below(piece.len());
+            bytes.extend_from_slice(&piece[..len]);
+        }
+
+        debug_assert!(bytes.iter().all(|&byte| is_shared(byte)));
+        bytes
+    })
+}
+
+#[test]
+fn lossy_matches_from_utf8_lossy() {
+    for bytes in inputs() {
+        let expected = String::from_utf8_lossy(&bytes);
+
+        assert_eq!(simd_cesu8::decode_lossy(&bytes), expected, "{bytes:x?}");
+        assert_eq!(
+            simd_cesu8::decode_lossy_strict(&bytes),
+            expected,
+            "{bytes:x?}"
+        );
+        assert_eq!(mutf8::decode_lossy(&bytes), expected, "{bytes:x?}");
+        assert_eq!(mutf8::decode_lossy_strict(&bytes), expected, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn lossy_counts_one_replacement_per_invalid_sequence() {
+    for bytes in inputs() {
+        let replaced = String::from_utf8_lossy(&bytes).matches('\u{fffd}').count();
+        // NOTE: A U+FFFD that was genuinely part of the input isn't counted.
+        let genuine = bytes
+            .windows(3)
+            .filter(|window| *window == [0xef, 0xbf, 0xbd])
+            .count();
+
+        let (_, replacements) = simd_cesu8::decode_lossy_counted(&bytes);
+        assert_eq!(replacements, replaced - genuine, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn truncated_sequences_are_replaced_as_a_whole() {
+    let cases: [(&[u8], &str); 5] = [
+        (&[0xe2, 0x82, b'a'], "\u{fffd}a"),
+        (&[0xe2, 0x82], "\u{fffd}"),
+        (&[0xc8, 0xc8, 0x85], "\u{fffd}ȅ"),
+        (&[0xe2, 0x82, 0xe2, 0x82, 0xac], "\u{fffd}€"),
+        (&[0x80, 0x80, 0xbf], "\u{fffd}\u{fffd}\u{fffd}"),
+    ];
+
+    for (bytes, expected) in cases {
+        assert_eq!(simd_cesu8::decode_lossy(bytes), expected, "{bytes:x?}");
+        assert_eq!(mutf8::decode_lossy(bytes), expected, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn lone_surrogates_are_replaced_as_a_whole() {
+    let cases: [(&[u8], &str); 5] = [
+        (&[0xed, 0xa0, 0xbd, b'a'], "\u{fffd}a"),
+        (&[0xed, 0xb2, 0x96, b'a'], "\u{fffd}a"),
+        (&[0xed, 0xb2, 0x96, 0xed, 0xa0, 0xbd], "\u{fffd}\u{fffd}"),
+        (
+            &[0xed, 0xa0, 0xbd, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+            "\u{fffd}💖",
+        ),
+        (&[0xed, 0xa0], "\u{fffd}"),
+    ];
+
+    for (bytes, expected) in cases {
+        assert_eq!(simd_cesu8::decode_lossy(bytes), expected, "{bytes:x?}");
+        assert_eq!(mutf8::decode_lossy(bytes), expected, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn rejected_4_byte_chars_are_replaced_as_a_whole() {
+    let bytes = [b'a', 0xf0, 0x9f, 0x92, 0x96, b'b'];
+    assert_eq!(simd_cesu8::decode_lossy_strict(&bytes), "a\u{fffd}b");
+    assert_eq!(mutf8::decode_lossy_strict(&bytes), "a\u{fffd}b");
+
+    // NOTE: A cut-off character is replaced as a whole, just like in UTF-8.
+    let bytes = [b'a', 0xf0, 0x9f, 0x92, b'b'];
+    assert_eq!(simd_cesu8::decode_lossy(&bytes), "a\u{fffd}b");
+    assert_eq!(mutf8::decode_lossy(&bytes), "a\u{fffd}b");
+}
@@ -0,0 +1,57 @@
+//! Tests for decoding with the `memchr` feature, which copies the runs between
+//! possible surrogate pairs in bulk.
+#![cfg(feature = "memchr")]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use simd_cesu8::mutf8;
+
+#[test]
+fn bmp_heavy_input_matches_cesu8() {
+    let value: String = "中文ȅ€💖\0a".chars().cycle().take(10_000).collect();
+
+    let encoded = cesu8::to_cesu8(&value);
+    assert_eq!(simd_cesu8::decode(&encoded).unwrap(), value);
+
+    let encoded = cesu8::to_java_cesu8(&value);
+    assert_eq!(mutf8::decode(&encoded).unwrap(), value);
+}
+
+#[test]
+fn invalid_runs_are_replaced_per_maximal_subpart() {
+    let inputs: [(&[u8], &str); 6] = [
+        // NOTE: A lone `0xed` between 3-byte characters, and one that starts a
+        // 3-byte character that isn't a surrogate.
+        (&[0xe4, 0xb8, 0xad, 0xed, 0xe4, 0xb8, 0xad], "中\u{fffd}中"),
+        (&[0xe4, 0xb8, 0xad, 0xed, 0x9f, 0xbf], "中\u{d7ff}"),
+        // NOTE: Continuation bytes and a 4-byte UTF-8 character before a pair.
+        (
+            &[0xe4, 0xb8, 0xbf, 0xbf, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+            "丿\u{fffd}💖",
+        ),
+        (
+            &[0xc8, 0x85, 0xf0, 0x9f, 0x92, 0x96, 0xed, 0xa0, 0xbd],
+            "ȅ\u{fffd}\u{fffd}",
+        ),
+        // NOTE: A high surrogate cut off by the end of a 3-byte character.
+        (&[0xed, 0xa0, 0xbd, 0xe4, 0xb8, 0xad], "\u{fffd}中"),
+        // NOTE: A truncated character at the end of a run.
+        (&[0xe4, 0xb8, 0xad, 0xe4, 0xb8], "中\u{fffd}"),
+    ];
+
+    for (bytes, expected) in inputs {
+        assert_eq!(simd_cesu8::decode_lossy(bytes), expected, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn mutf8_nulls_end_a_run() {
+    let bytes = [
+        0xe4, 0xb8, 0xad, 0xc0, 0x80, 0xe4, 0xb8, 0xad, 0x00, 0xc8, 0x85,
+    ];
+    assert_eq!(mutf8::decode_lossy(&bytes), "中\0中\u{fffd}ȅ");
+    assert!(mutf8::decode_strict(&bytes).is_err());
+    assert_eq!(mutf8::decode_strict(&bytes[..8]).unwrap(), "中\0中");
+}
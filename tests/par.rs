@@ -0,0 +1,89 @@
+//! Tests that decoding in parallel matches decoding serially, wherever the
+//! chunk boundaries fall.
+#![cfg(feature = "rayon")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use rayon::ThreadPoolBuilder;
+use simd_cesu8::mutf8;
+
+/// Characters of every encoded length, including a surrogate pair and an
+/// encoded null, which are the ones a bad split would cut in two.
+const PIECES: [&str; 5] = ["a", "ȅ", "€", "💖", "\0"];
+
+/// The numbers of threads to decode with. Each splits the input at different
+/// offsets, so together they land on every byte of every piece.
+const THREADS: [usize; 4] = [2, 3, 7, 16];
+
+/// Returns a megabyte of pieces, starting `offset` bytes into the
+/// pattern, so that each input puts the chunk boundaries somewhere else.
+fn input(offset: usize) -> String {
+    let mut value = "a".repeat(offset);
+
+    while value.len() < 1024 * 1024 {
+        for (index, piece) in PIECES.iter().enumerate() {
+            for _ in 0..=index {
+                value.push_str(piece);
+            }
+        }
+    }
+
+    value
+}
+
+fn assert_matches_serial(bytes: &[u8]) {
+    let cesu8 = simd_cesu8::decode(bytes).ok().map(Cow::into_owned);
+    let mutf8 = mutf8::decode(bytes).ok().map(Cow::into_owned);
+
+    for threads in THREADS {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+
+        assert!(
+            pool.install(|| simd_cesu8::decode_par(bytes)).ok() == cesu8,
+            "{threads}"
+        );
+        assert!(
+            pool.install(|| mutf8::decode_par(bytes)).ok() == mutf8,
+            "{threads}"
+        );
+    }
+}
+
+#[test]
+fn decode_par_matches_decode() {
+    for offset in 0..3 {
+        let value = input(offset);
+        assert_matches_serial(&simd_cesu8::encode(&value));
+        assert_matches_serial(&mutf8::encode(&value));
+    }
+}
+
+#[test]
+fn decode_par_matches_decode_for_utf8() {
+    assert_matches_serial(input(0).as_bytes());
+}
+
+#[test]
+fn decode_par_rejects_invalid_input_in_any_chunk() {
+    let value = input(0);
+    let value = mutf8::encode(&value);
+
+    for index in [0, value.len() / 3, value.len() / 2, value.len() - 1] {
+        let mut bytes = Vec::from(&*value);
+        bytes[index] = 0xff;
+        assert_matches_serial(&bytes);
+
+        // NOTE: A 4-byte UTF-8 character makes the whole input invalid, even
+        // though the chunk it ends up in could be valid UTF-8 on its own.
+        let mut bytes = Vec::from(&*value);
+        bytes.splice(index..index, "💖".bytes());
+        assert_matches_serial(&bytes);
+    }
+}
@@ -0,0 +1,65 @@
+//! Tests for decoding a prefix of the input up to a maximum length.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::mutf8;
+
+const VALUE: &str = "a\0ȅ€💖b💖💖";
+
+#[test]
+fn decode_prefix_never_splits_a_character() {
+    let bytes = mutf8::encode(VALUE);
+
+    for max_len in 0..bytes.len() + 2 {
+        let (decoded, consumed) = mutf8::decode_prefix(&bytes, max_len).unwrap();
+        assert!(consumed <= max_len, "{max_len}");
+        assert!(
+            max_len - consumed < 6 || consumed == bytes.len(),
+            "{max_len}"
+        );
+
+        let rest = mutf8::decode(&bytes[consumed..]).unwrap();
+        assert_eq!(decoded + rest, VALUE, "{max_len}");
+    }
+
+    assert_eq!(
+        mutf8::decode_prefix(&bytes, usize::MAX).unwrap().1,
+        bytes.len()
+    );
+}
+
+#[test]
+fn decode_prefix_matches_decode() {
+    let bytes = simd_cesu8::encode(VALUE);
+
+    for max_len in 0..bytes.len() {
+        let (decoded, consumed) = simd_cesu8::decode_prefix(&bytes, max_len).unwrap();
+        assert_eq!(
+            decoded,
+            simd_cesu8::decode(&bytes[..consumed]).unwrap(),
+            "{max_len}"
+        );
+    }
+}
+
+#[test]
+fn decode_prefix_ignores_the_rest_of_the_input() {
+    let bytes = [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xff, 0xff];
+    let (decoded, consumed) = simd_cesu8::decode_prefix(&bytes, 7).unwrap();
+    assert_eq!(decoded, "a💖");
+    assert_eq!(consumed, 7);
+
+    assert!(simd_cesu8::decode_prefix(&bytes, 8).is_err());
+}
+
+#[test]
+fn decode_prefix_does_not_split_utf8() {
+    let bytes = "a💖".as_bytes();
+
+    for max_len in 1..5 {
+        let (decoded, consumed) = simd_cesu8::decode_prefix(bytes, max_len).unwrap();
+        assert_eq!(decoded, "a");
+        assert_eq!(consumed, 1);
+    }
+
+    assert_eq!(simd_cesu8::decode_prefix(bytes, 5).unwrap().0, "a💖");
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "alloc")]
+
 use dev_util::Bucket;
 
 #[test]
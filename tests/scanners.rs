@@ -0,0 +1,242 @@
+//! Tests that every implementation of the scanners gives the same answers as
+//! the fallback, for random slices of every length and alignment.
+#![cfg(feature = "bench")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+use simd_cesu8::implementation::aarch64;
+#[cfg(feature = "nightly")]
+use simd_cesu8::implementation::simd;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use simd_cesu8::implementation::wasm32;
+use simd_cesu8::implementation::{fallback, word};
+
+/// The bytes that the scanners look for, or that sit right next to them.
+const SPECIAL: [u8; 8] = [0x00, 0x01, 0x7f, 0x80, 0xef, 0xf0, 0xf7, 0xff];
+
+/// A deterministic stream of pseudo-random numbers, so that every failure can
+/// be reproduced.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        // NOTE: This is xorshift64, which is plenty for spreading bits around.
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        usize::try_from(self.next() % bound as u64).unwrap()
+    }
+}
+
+/// Returns random slices of every length up to a few words, mostly made of
+/// ASCII with an occasional special byte, each starting at every offset within
+/// a word of a larger buffer.
+fn slices() -> impl Iterator<Item = (Vec<u8>, usize)> {
+    let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+
+    (0..4096).map(move |_| {
+        let offset = rng.below(32);
+        let len = rng.below(300);
+        // NOTE: The denser the special bytes, the earlier the scanners stop, so
+        // the density varies too.
+        let density = 1 + rng.below(64);
+
+        let buffer = (0..offset + len)
+            .map(|_| {
+                if rng.below(density) == 0 {
+                    SPECIAL[rng.below(SPECIAL.len())]
+                } else {
+                    0x20 + u8::try_from(rng.below(0x5f)).unwrap()
+                }
+            })
+            .collect();
+
+        (buffer, offset)
+    })
+}
+
+fn assert_matches_fallback<T: Debug + PartialEq>(
+    test: impl Fn(&[u8]) -> T,
+    reference: fn(&[u8]) -> T,
+) {
+    for (buffer, offset) in slices() {
+        let bytes = &buffer[offset..];
+        assert_eq!(test(bytes), reference(bytes), "{offset} {bytes:02x?}");
+    }
+}
+
+#[test]
+fn word_contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        word::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[test]
+fn word_contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        word::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[test]
+fn word_count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(word::count_leading_ascii, fallback::count_leading_ascii);
+}
+
+#[test]
+fn word_count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        word::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn simd_contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        simd::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn simd_contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        simd::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn simd_count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(simd::count_leading_ascii, fallback::count_leading_ascii);
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn simd_count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        simd::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[test]
+fn wasm32_contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        wasm32::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[test]
+fn wasm32_contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        wasm32::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[test]
+fn wasm32_count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(wasm32::count_leading_ascii, fallback::count_leading_ascii);
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[test]
+fn wasm32_count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        wasm32::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn neon_contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        // SAFETY: NEON is part of the aarch64 baseline.
+        |bytes| unsafe { aarch64::neon::contains_null_or_utf8_4_byte_char_header(bytes) },
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn neon_contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        // SAFETY: NEON is part of the aarch64 baseline.
+        |bytes| unsafe { aarch64::neon::contains_utf8_4_byte_char_header(bytes) },
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn neon_count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(
+        // SAFETY: NEON is part of the aarch64 baseline.
+        |bytes| unsafe { aarch64::neon::count_leading_ascii(bytes) },
+        fallback::count_leading_ascii,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn neon_count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        // SAFETY: NEON is part of the aarch64 baseline.
+        |bytes| unsafe { aarch64::neon::count_leading_non_null_ascii(bytes) },
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn aarch64_contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        aarch64::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn aarch64_contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        aarch64::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn aarch64_count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(aarch64::count_leading_ascii, fallback::count_leading_ascii);
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[test]
+fn aarch64_count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        aarch64::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}
@@ -0,0 +1,156 @@
+//! Tests for decoding with a per-kind recovery policy.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::{mutf8, DecodingErrorKind};
+
+const SAMPLES: [&[u8]; 10] = [
+    b"Hello, world!",
+    &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    &[0xf0, 0x9f, 0x92, 0x96],
+    &[0xc0, 0x80],
+    &[b'a', 0x00, b'b'],
+    &[0xed, 0xa0, 0xbd],
+    &[0xed, 0xb2, 0x96, b'a'],
+    &[0xe0, 0x80, 0x80],
+    &[0x80, 0xff, 0xc2],
+    &[0xe2, 0x82, b'a'],
+];
+
+/// Returns the kind of every error `decode` reports when it recovers from all
+/// of them.
+fn kinds(decode: impl Fn(&mut dyn FnMut(DecodingErrorKind) -> bool)) -> Vec<DecodingErrorKind> {
+    let mut kinds = Vec::new();
+    decode(&mut |kind| {
+        kinds.push(kind);
+        true
+    });
+    kinds
+}
+
+fn cesu8_kinds(bytes: &[u8]) -> Vec<DecodingErrorKind> {
+    kinds(|recover| {
+        simd_cesu8::decode_selective_lossy(bytes, recover).unwrap();
+    })
+}
+
+fn mutf8_kinds(bytes: &[u8]) -> Vec<DecodingErrorKind> {
+    kinds(|recover| {
+        mutf8::decode_selective_lossy(bytes, recover).unwrap();
+    })
+}
+
+#[test]
+fn recovering_from_everything_matches_decode_lossy_strict() {
+    for bytes in SAMPLES {
+        assert_eq!(
+            simd_cesu8::decode_selective_lossy(bytes, |_| true).unwrap(),
+            simd_cesu8::decode_lossy_strict(bytes)
+        );
+        assert_eq!(
+            mutf8::decode_selective_lossy(bytes, |_| true).unwrap(),
+            mutf8::decode_lossy_strict(bytes)
+        );
+    }
+}
+
+#[test]
+fn recovering_from_nothing_matches_decode_strict() {
+    for bytes in SAMPLES {
+        assert_eq!(
+            simd_cesu8::decode_selective_lossy(bytes, |_| false),
+            simd_cesu8::decode_strict(bytes)
+        );
+        assert_eq!(
+            mutf8::decode_selective_lossy(bytes, |_| false),
+            mutf8::decode_strict(bytes)
+        );
+    }
+}
+
+#[test]
+fn reports_kind_of_each_error() {
+    use DecodingErrorKind::{
+        ExpectedContinuation,
+        InvalidByte,
+        InvalidSurrogatePair,
+        OverlongEncoding,
+        UnexpectedContinuation,
+        UnexpectedEof,
+        Utf8NotCesu8,
+    };
+
+    assert_eq!(cesu8_kinds(b"Hello, world!"), []);
+    assert_eq!(cesu8_kinds(&[0x80]), [UnexpectedContinuation]);
+    assert_eq!(cesu8_kinds(&[0xff, 0xf5]), [InvalidByte, InvalidByte]);
+    assert_eq!(cesu8_kinds(&[0xc2]), [UnexpectedEof]);
+    assert_eq!(cesu8_kinds(&[0xc2, b'a']), [ExpectedContinuation]);
+    assert_eq!(cesu8_kinds(&[0xe2, 0x82, b'a']), [ExpectedContinuation]);
+    assert_eq!(cesu8_kinds(&[0xc0, 0x80]), [
+        OverlongEncoding,
+        UnexpectedContinuation
+    ]);
+    assert_eq!(cesu8_kinds(&[0xe0, 0x80, 0x80]), [
+        OverlongEncoding,
+        UnexpectedContinuation,
+        UnexpectedContinuation
+    ]);
+    assert_eq!(cesu8_kinds(&[0xf0, 0x9f, 0x92, 0x96]), [Utf8NotCesu8]);
+    assert_eq!(cesu8_kinds(&[0xed, 0xb2, 0x96]), [InvalidSurrogatePair]);
+    assert_eq!(cesu8_kinds(&[0xed, 0xa0, 0xbd, b'a', b'b', b'c']), [
+        InvalidSurrogatePair
+    ]);
+    assert_eq!(cesu8_kinds(&[0xed, 0xa0, 0xbd, 0xed]), [
+        UnexpectedEof,
+        UnexpectedEof
+    ]);
+    assert_eq!(
+        cesu8_kinds(&[0xed, 0xa0, b'a', 0xed, 0xb0, 0x80])[0],
+        ExpectedContinuation
+    );
+
+    assert_eq!(mutf8_kinds(&[b'a', 0x00]), [Utf8NotCesu8]);
+    assert_eq!(mutf8_kinds(&[0xc0, 0x80]), []);
+    assert_eq!(mutf8_kinds(&[0xc0, b'a']), [OverlongEncoding]);
+    assert_eq!(mutf8_kinds(&[0xc0]), [UnexpectedEof]);
+}
+
+#[test]
+fn stops_at_first_rejected_kind() {
+    let bytes = [0x80, b'a', 0xed, 0xb2, 0x96];
+    let policy = |kind| kind == DecodingErrorKind::UnexpectedContinuation;
+
+    assert!(simd_cesu8::decode_selective_lossy(&bytes, policy).is_err());
+    assert!(mutf8::decode_selective_lossy(&bytes, policy).is_err());
+    assert_eq!(
+        simd_cesu8::decode_selective_lossy(&bytes[..2], policy).unwrap(),
+        "\u{fffd}a"
+    );
+}
+
+#[test]
+fn decode_lossy_counted_counts_each_recovered_error() {
+    for bytes in SAMPLES {
+        let (decoded, replacements) = simd_cesu8::decode_lossy_counted(bytes);
+        assert_eq!(decoded, simd_cesu8::decode_lossy(bytes), "{bytes:x?}");
+        // NOTE: Valid UTF-8 is borrowed without looking for errors.
+        let expected = if core::str::from_utf8(bytes).is_ok() {
+            0
+        } else {
+            cesu8_kinds(bytes).len()
+        };
+        assert_eq!(replacements, expected, "{bytes:x?}");
+
+        let (decoded, replacements) = mutf8::decode_lossy_counted(bytes);
+        assert_eq!(decoded, mutf8::decode_lossy(bytes), "{bytes:x?}");
+        let expected = if core::str::from_utf8(bytes).is_ok() {
+            0
+        } else {
+            mutf8_kinds(bytes).len()
+        };
+        assert_eq!(replacements, expected, "{bytes:x?}");
+    }
+}
@@ -0,0 +1,103 @@
+//! Tests for serializing strings as CESU-8 and MUTF-8 bytes.
+#![cfg(feature = "serde")]
+
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+use simd_cesu8::mutf8::Mutf8String;
+use simd_cesu8::Cesu8String;
+
+const CESU8: &[u8] = &[b'a', 0x00, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+const MUTF8: &[u8] = &[b'a', 0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+
+#[test]
+fn cesu8_string_is_serialized_as_cesu8_bytes() {
+    let value = Cesu8String::from("a\0💖");
+    assert_tokens(&value, &[Token::Bytes(CESU8)]);
+    assert_de_tokens(&value, &[Token::ByteBuf(CESU8)]);
+    assert_de_tokens(&value, &[
+        Token::Seq { len: Some(8) },
+        Token::U8(b'a'),
+        Token::U8(0x00),
+        Token::U8(0xed),
+        Token::U8(0xa0),
+        Token::U8(0xbd),
+        Token::U8(0xed),
+        Token::U8(0xb2),
+        Token::U8(0x96),
+        Token::SeqEnd,
+    ]);
+}
+
+#[test]
+fn mutf8_string_is_serialized_as_mutf8_bytes() {
+    let value = Mutf8String::from("a\0💖");
+    assert_tokens(&value, &[Token::Bytes(MUTF8)]);
+    assert_de_tokens(&value, &[Token::ByteBuf(MUTF8)]);
+}
+
+#[test]
+fn deserializing_accepts_utf8_byte_bufs() {
+    let value = Cesu8String::from("Hello, world!");
+    assert_de_tokens(&value, &[Token::ByteBuf(b"Hello, world!")]);
+}
+
+#[test]
+fn deserializing_rejects_invalid_bytes() {
+    // NOTE: This is a valid UTF-8 string, but not valid CESU-8.
+    assert_de_tokens_error::<Cesu8String>(
+        &[Token::Bytes(&[0xf0, 0x9f, 0x92, 0x96])],
+        "invalid value: byte array, expected CESU-8 bytes",
+    );
+    assert_de_tokens_error::<Mutf8String>(
+        &[Token::Bytes(b"a\0")],
+        "invalid value: byte array, expected MUTF-8 bytes",
+    );
+    assert_de_tokens_error::<Cesu8String>(
+        &[Token::ByteBuf(&CESU8[..5])],
+        "invalid value: byte array, expected CESU-8 bytes",
+    );
+}
+
+#[test]
+fn newtypes_act_like_strings() {
+    let value = Cesu8String::new("💖".into());
+    assert_eq!(&*value, "💖");
+    assert_eq!(value.to_string(), "💖");
+    assert_eq!(String::from(value), "💖");
+
+    let value = Mutf8String::new("\0".into());
+    assert_eq!(value.as_ref(), "\0");
+    assert_eq!(value.into_string(), "\0");
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn newtypes_hash_like_strings() {
+    let value = String::from("💖");
+    assert_eq!(hash(&Cesu8String::from("💖")), hash(&value));
+    assert_eq!(hash(&Mutf8String::from("💖")), hash(&value));
+}
+
+#[test]
+fn newtypes_order_like_strings_rather_than_their_bytes() {
+    let (astral, bmp) = ("💖", "\u{ffff}");
+
+    // NOTE: A surrogate pair starts with `0xed`, so the encoded bytes of the
+    // astral character sort before the ones of the BMP character.
+    assert!(simd_cesu8::encode(astral) < simd_cesu8::encode(bmp));
+    assert!(astral > bmp);
+
+    assert!(Cesu8String::from(astral) > Cesu8String::from(bmp));
+    assert!(Mutf8String::from(astral) > Mutf8String::from(bmp));
+
+    let mut values = ["b", astral, "a", bmp].map(Cesu8String::from);
+    values.sort();
+    assert_eq!(values.map(String::from), ["a", "b", bmp, astral]);
+}
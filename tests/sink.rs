@@ -0,0 +1,95 @@
+//! Tests for encoding and decoding through a sink instead of a buffer.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+fn long_values() -> impl Iterator<Item = String> {
+    // NOTE: These are long enough to fill the stack buffer used when decoding
+    // several times over, with characters straddling the point where it fills.
+    [1, 255, 256, 257, 4096]
+        .into_iter()
+        .map(|len| "a\0ȅ€💖".chars().cycle().take(len).collect())
+}
+
+#[test]
+fn encode_to_sink_matches_encode() {
+    let values = VALUES.map(String::from).into_iter().chain(long_values());
+
+    for value in values {
+        let mut encoded = Vec::new();
+        simd_cesu8::encode_to_sink(&value, |run| encoded.extend_from_slice(run));
+        assert_eq!(encoded, *simd_cesu8::encode(&value));
+
+        let mut encoded = Vec::new();
+        mutf8::encode_to_sink(&value, |run| encoded.extend_from_slice(run));
+        assert_eq!(encoded, *mutf8::encode(&value));
+    }
+}
+
+#[test]
+fn encode_to_sink_borrows_unchanged_runs() {
+    let value = "abc💖def";
+    let mut runs = Vec::new();
+    simd_cesu8::encode_to_sink(value, |run| runs.push(run.as_ptr_range()));
+
+    let bytes = value.as_bytes().as_ptr_range();
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[0].start, bytes.start);
+    assert_eq!(runs[2].end, bytes.end);
+}
+
+#[test]
+fn encode_to_sink_never_calls_sink_for_empty_input() {
+    simd_cesu8::encode_to_sink("", |_| panic!("sink was called"));
+    mutf8::encode_to_sink("", |_| panic!("sink was called"));
+}
+
+#[test]
+fn decode_to_sink_matches_decode() {
+    let values = VALUES.map(String::from).into_iter().chain(long_values());
+
+    for value in values {
+        let encoded = simd_cesu8::encode(&value);
+        let mut decoded = String::new();
+        simd_cesu8::decode_to_sink(&encoded, |run| decoded.push_str(run)).unwrap();
+        assert_eq!(decoded, value);
+
+        let encoded = mutf8::encode(&value);
+        let mut decoded = String::new();
+        mutf8::decode_to_sink(&encoded, |run| decoded.push_str(run)).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
+
+#[test]
+fn decode_to_sink_passes_utf8_as_a_single_run() {
+    let bytes = "a💖b".as_bytes();
+    let mut runs = 0;
+    simd_cesu8::decode_to_sink(bytes, |run| {
+        assert_eq!(run, "a💖b");
+        runs += 1;
+    })
+    .unwrap();
+    assert_eq!(runs, 1);
+}
+
+#[test]
+fn decode_to_sink_rejects_invalid_input() {
+    let mut bytes = simd_cesu8::encode(&"💖".repeat(100)).into_owned();
+    bytes.push(0xed);
+
+    let mut decoded = String::new();
+    let result = simd_cesu8::decode_to_sink(&bytes, |run| decoded.push_str(run));
+    assert!(result.is_err());
+    assert!("💖".repeat(100).starts_with(&decoded));
+
+    let result = mutf8::decode_to_sink(&[b'a', 0xc0, 0x81], |_| {});
+    assert!(result.is_err());
+}
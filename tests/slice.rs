@@ -0,0 +1,108 @@
+//! Tests for the encoding and decoding functions that write into a slice.
+#![cfg(feature = "alloc")]
+
+use simd_cesu8::{mutf8, DecodeToSliceError};
+
+const VALUES: [&str; 6] = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+#[test]
+fn encode_to_slice_matches_encode() {
+    for value in VALUES {
+        let mut out = [0; 32];
+
+        let len = simd_cesu8::encode_to_slice(value, &mut out).unwrap();
+        assert_eq!(out[..len], *simd_cesu8::encode(value), "{value:?}");
+
+        let len = mutf8::encode_to_slice(value, &mut out).unwrap();
+        assert_eq!(out[..len], *mutf8::encode(value), "{value:?}");
+    }
+}
+
+#[test]
+fn encode_to_slice_needs_exactly_encoded_len() {
+    for value in VALUES {
+        let len = simd_cesu8::encoded_len(value);
+        assert!(simd_cesu8::encode_to_slice(value, &mut vec![0; len]).is_ok());
+
+        if len != 0 {
+            let mut out = vec![0xaa; len - 1];
+            assert!(simd_cesu8::encode_to_slice(value, &mut out).is_err());
+            assert!(out.iter().all(|&byte| byte == 0xaa), "{value:?}");
+        }
+
+        let len = mutf8::encoded_len(value);
+        assert!(mutf8::encode_to_slice(value, &mut vec![0; len]).is_ok());
+
+        if len != 0 {
+            assert!(mutf8::encode_to_slice(value, &mut vec![0; len - 1]).is_err());
+        }
+    }
+}
+
+#[test]
+fn decode_to_slice_matches_decode() {
+    let inputs: [&[u8]; 7] = [
+        b"",
+        b"Hello, world!",
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+        &[b'a', 0xc0, 0x80],
+        &[b'a', 0x00],
+        &[0xed, 0xa0, 0xbd],
+        &[0xf0, 0x9f, 0x92, 0x96, 0xff],
+    ];
+
+    for bytes in inputs {
+        let mut out = [0; 32];
+
+        assert_eq!(
+            simd_cesu8::decode_to_slice(bytes, &mut out).ok(),
+            simd_cesu8::decode(bytes).ok().as_deref(),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            mutf8::decode_to_slice(bytes, &mut out).ok(),
+            mutf8::decode(bytes).ok().as_deref(),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn decode_to_slice_needs_exactly_decoded_len() {
+    let inputs: [&[u8]; 3] = [
+        b"Hello, world!",
+        &[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, b'b'],
+        &[0xc0, 0x80, 0xc0, 0x80, b'a'],
+    ];
+
+    for bytes in inputs {
+        let decoded = mutf8::decode(bytes).unwrap();
+        let mut out = vec![0; decoded.len()];
+        assert_eq!(mutf8::decode_to_slice(bytes, &mut out).unwrap(), decoded);
+
+        for len in 0..decoded.len() {
+            assert!(
+                matches!(
+                    mutf8::decode_to_slice(bytes, &mut vec![0; len]),
+                    Err(DecodeToSliceError::BufferTooSmall(_))
+                ),
+                "{bytes:x?} {len}"
+            );
+        }
+    }
+}
+
+#[test]
+fn decode_to_slice_reports_invalid_input_before_a_small_buffer() {
+    let bytes = [b'a', b'b', b'c', 0xed, 0xa0, 0xbd];
+
+    for len in 0..=bytes.len() {
+        assert!(
+            matches!(
+                simd_cesu8::decode_to_slice(&bytes, &mut vec![0; len]),
+                Err(DecodeToSliceError::Decoding(_))
+            ),
+            "{len}"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Tests for decoding CESU-8 and MUTF-8 in chunks.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8::{self, Mutf8Decoder};
+use simd_cesu8::Cesu8Decoder;
+
+fn decode_in_chunks(bytes: &[u8], size: usize) -> Result<String, simd_cesu8::DecodingError> {
+    let mut stream = Mutf8Decoder::new();
+    let mut decoded = String::new();
+
+    for chunk in bytes.chunks(size) {
+        stream.feed(chunk, &mut decoded)?;
+    }
+
+    stream.finish()?;
+    Ok(decoded)
+}
+
+#[test]
+fn decodes_every_chunk_size() {
+    let value = "a\0💖ȅ\0€💖💖\0";
+    let encoded = mutf8::encode(value);
+
+    for size in 1..=encoded.len() {
+        assert_eq!(decode_in_chunks(&encoded, size).unwrap(), value, "{size}");
+    }
+}
+
+#[test]
+fn decodes_every_split_point() {
+    let value = "\0💖\0";
+    let encoded = mutf8::encode(value);
+
+    for first in 0..=encoded.len() {
+        for second in first..=encoded.len() {
+            let mut stream = Mutf8Decoder::new();
+            let mut decoded = String::new();
+
+            stream.feed(&encoded[..first], &mut decoded).unwrap();
+            stream.feed(&encoded[first..second], &mut decoded).unwrap();
+            stream.feed(&encoded[second..], &mut decoded).unwrap();
+            stream.finish().unwrap();
+
+            assert_eq!(decoded, value, "{first} {second}");
+        }
+    }
+}
+
+#[test]
+fn only_emits_complete_characters() {
+    let mut stream = Mutf8Decoder::new();
+    let mut decoded = String::new();
+
+    stream
+        .feed(&[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2], &mut decoded)
+        .unwrap();
+    assert_eq!(decoded, "a");
+    assert!(stream.has_pending());
+
+    stream.feed(&[0x96], &mut decoded).unwrap();
+    assert_eq!(decoded, "a💖");
+    assert!(!stream.has_pending());
+}
+
+#[test]
+fn finish_rejects_a_dangling_sequence() {
+    let encoded = mutf8::encode("\0💖");
+
+    for len in 1..encoded.len() {
+        if len == 2 {
+            continue;
+        }
+
+        let mut stream = Mutf8Decoder::new();
+        let mut decoded = String::new();
+        stream.feed(&encoded[..len], &mut decoded).unwrap();
+        assert!(stream.finish().is_err(), "{len}");
+    }
+}
+
+#[test]
+fn rejects_invalid_input_across_chunks() {
+    let cases: [&[u8]; 5] = [
+        &[0xc0, 0x81],
+        &[0x00],
+        &[0xf0, 0x9f, 0x92, 0x96],
+        &[0xed, 0xa0, 0xbd, b'a', b'b', b'c'],
+        &[0xed, 0xb2, 0x96],
+    ];
+
+    for bytes in cases {
+        for size in 1..=bytes.len() {
+            assert!(decode_in_chunks(bytes, size).is_err(), "{bytes:x?} {size}");
+        }
+    }
+}
+
+#[test]
+fn agrees_with_decode_strict() {
+    let mut bytes = Vec::new();
+
+    for value in ["a", "\0", "💖", "€"] {
+        bytes.extend_from_slice(&mutf8::encode(value));
+    }
+
+    for len in 0..=bytes.len() {
+        let prefix = &bytes[..len];
+        let expected = mutf8::decode_strict(prefix).ok();
+
+        for size in 1..=len.max(1) {
+            let decoded = decode_in_chunks(prefix, size).ok();
+            assert_eq!(
+                decoded.as_deref(),
+                expected.as_deref(),
+                "{prefix:x?} {size}"
+            );
+        }
+    }
+}
+
+#[test]
+fn push_returns_each_chunk_decoded() {
+    let value = "a\0💖ȅ\0€💖💖\0";
+    let encoded = mutf8::encode(value);
+
+    for size in 1..=encoded.len() {
+        let mut stream = Mutf8Decoder::new();
+        let mut decoded = String::new();
+
+        for chunk in encoded.chunks(size) {
+            decoded.push_str(stream.push(chunk).unwrap());
+        }
+
+        stream.finish().unwrap();
+        assert_eq!(decoded, value, "{size}");
+    }
+}
+
+#[test]
+fn push_holds_on_to_a_null_waiting_for_its_second_byte() {
+    let mut stream = Mutf8Decoder::new();
+    assert_eq!(stream.push(&[b'a', 0xc0]).unwrap(), "a");
+    assert!(stream.has_pending());
+    assert_eq!(stream.push(&[0x80]).unwrap(), "\0");
+    assert!(!stream.has_pending());
+    stream.finish().unwrap();
+}
+
+#[test]
+fn push_holds_on_to_a_surrogate_pair_split_in_half() {
+    let mut stream = Mutf8Decoder::new();
+    assert_eq!(stream.push(&[0xed, 0xa0, 0xbd]).unwrap(), "");
+    assert_eq!(stream.push(&[0xed, 0xb2, 0x96]).unwrap(), "💖");
+    stream.finish().unwrap();
+
+    let mut stream = Cesu8Decoder::new();
+    assert_eq!(stream.push(&[0xed, 0xa0, 0xbd]).unwrap(), "");
+    assert_eq!(stream.push(&[0xed, 0xb2, 0x96]).unwrap(), "💖");
+    stream.finish().unwrap();
+}
+
+#[test]
+fn cesu8_decodes_every_split_point() {
+    let value = "\0💖€\0";
+    let encoded = simd_cesu8::encode(value);
+
+    for split in 0..=encoded.len() {
+        let mut stream = Cesu8Decoder::new();
+        let mut decoded = String::from(stream.push(&encoded[..split]).unwrap());
+        decoded.push_str(stream.push(&encoded[split..]).unwrap());
+        stream.finish().unwrap();
+
+        assert_eq!(decoded, value, "{split}");
+    }
+}
+
+#[test]
+fn cesu8_agrees_with_decode_strict() {
+    let cases: [&[u8]; 6] = [
+        &[0x00],
+        &[0xc0, 0x80],
+        &[0xf0, 0x9f, 0x92, 0x96],
+        &[0xed, 0xa0, 0xbd, b'a', b'b', b'c'],
+        &[0xed, 0xb2, 0x96],
+        &[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    ];
+
+    for bytes in cases {
+        let expected = simd_cesu8::decode_strict(bytes).ok();
+
+        for size in 1..=bytes.len() {
+            let mut stream = Cesu8Decoder::new();
+            let mut decoded = String::new();
+            let result = bytes
+                .chunks(size)
+                .try_for_each(|chunk| stream.feed(chunk, &mut decoded))
+                .and_then(|()| stream.finish());
+
+            assert_eq!(
+                result.ok().map(|()| decoded).as_deref(),
+                expected.as_deref(),
+                "{bytes:x?} {size}"
+            );
+        }
+    }
+}
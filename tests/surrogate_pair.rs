@@ -0,0 +1,67 @@
+//! Tests for scanning for surrogate pairs without decoding.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+#[test]
+fn agrees_with_decoding() {
+    let mut bytes = Vec::new();
+
+    for value in ["a", "\0", "💖", "€", "ȅ"] {
+        bytes.extend_from_slice(&simd_cesu8::encode(value));
+    }
+
+    for start in 0..bytes.len() {
+        for end in start..=bytes.len() {
+            let slice = &bytes[start..end];
+
+            if let Ok(decoded) = simd_cesu8::decode_strict(slice) {
+                let expected = decoded.chars().any(|c| c.len_utf8() == 4);
+                assert_eq!(
+                    simd_cesu8::contains_surrogate_pair(slice),
+                    expected,
+                    "{slice:x?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn finds_a_pair_after_every_amount_of_ascii() {
+    let pair = mutf8::encode("💖");
+
+    for len in 0..100 {
+        let mut bytes = b"a".repeat(len);
+        assert!(!mutf8::contains_surrogate_pair(&bytes), "{len}");
+
+        bytes.extend_from_slice(&pair);
+        assert!(mutf8::contains_surrogate_pair(&bytes), "{len}");
+
+        bytes.truncate(bytes.len() - 1);
+        assert!(!mutf8::contains_surrogate_pair(&bytes), "{len}");
+    }
+}
+
+#[test]
+fn unpaired_surrogates_are_not_pairs() {
+    let inputs: [&[u8]; 4] = [
+        &[0xed, 0xa0, 0xbd],
+        &[0xed, 0xb2, 0x96],
+        &[0xed, 0xb2, 0x96, 0xed, 0xa0, 0xbd],
+        &[0xed, 0xa0, 0xbd, b'a', 0xed, 0xb2, 0x96],
+    ];
+
+    for bytes in inputs {
+        assert!(!simd_cesu8::contains_surrogate_pair(bytes), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn utf8_4_byte_characters_are_not_pairs() {
+    assert!(!simd_cesu8::contains_surrogate_pair("💖".as_bytes()));
+}
@@ -0,0 +1,67 @@
+//! Tests for converting between CESU-8 and MUTF-8 without decoding.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 7] = [
+    "",
+    "Hello, world!",
+    "\0",
+    "💖",
+    "a\0💖ȅ€",
+    "\0\0💖\0",
+    "ÀÀ\0À",
+];
+
+#[test]
+fn transcoding_matches_decoding_and_encoding() {
+    for value in VALUES {
+        let cesu8 = simd_cesu8::encode(value);
+        let mutf8 = mutf8::encode(value);
+
+        assert_eq!(
+            simd_cesu8::cesu8_to_mutf8(&cesu8).unwrap(),
+            mutf8,
+            "{value:?}"
+        );
+        assert_eq!(
+            simd_cesu8::mutf8_to_cesu8(&mutf8).unwrap(),
+            cesu8,
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn transcoding_borrows_when_there_are_no_nulls() {
+    let bytes = simd_cesu8::encode("aÀ💖").into_owned();
+
+    let transcoded = simd_cesu8::cesu8_to_mutf8(&bytes).unwrap();
+    assert!(matches!(transcoded, Cow::Borrowed(_)));
+
+    let transcoded = simd_cesu8::mutf8_to_cesu8(&bytes).unwrap();
+    assert!(matches!(transcoded, Cow::Borrowed(_)));
+}
+
+#[test]
+fn transcoding_rejects_invalid_input() {
+    let cases: [&[u8]; 4] = [
+        &[0xf0, 0x9f, 0x92, 0x96],
+        &[0xed, 0xa0, 0xbd],
+        &[b'a', 0xc0],
+        &[0xff],
+    ];
+
+    for bytes in cases {
+        assert!(simd_cesu8::cesu8_to_mutf8(bytes).is_err(), "{bytes:x?}");
+        assert!(simd_cesu8::mutf8_to_cesu8(bytes).is_err(), "{bytes:x?}");
+    }
+
+    // NOTE: Each flavor rejects the other's encoding of null.
+    assert!(simd_cesu8::cesu8_to_mutf8(&[0xc0, 0x80]).is_err());
+    assert!(simd_cesu8::mutf8_to_cesu8(&[0x00]).is_err());
+}
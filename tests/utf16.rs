@@ -0,0 +1,171 @@
+//! Tests for encoding from and decoding to UTF-16 code units.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use simd_cesu8::mutf8;
+
+const VALUES: [&str; 8] = [
+    "",
+    "Hello, world!",
+    "\0",
+    "💖",
+    "a\0💖ȅ€",
+    "💖💖",
+    "\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}",
+    "\u{10000}\u{10ffff}",
+];
+
+fn utf16(value: &str) -> Vec<u16> {
+    value.encode_utf16().collect()
+}
+
+#[test]
+fn encode_utf16_matches_encode() {
+    for value in VALUES {
+        let units = utf16(value);
+        assert_eq!(
+            simd_cesu8::encode_utf16(&units),
+            *simd_cesu8::encode(value),
+            "{value:?}"
+        );
+        assert_eq!(
+            mutf8::encode_utf16(&units),
+            *mutf8::encode(value),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn encode_utf16_encodes_lone_surrogates() {
+    let units = [u16::from(b'a'), 0xd83d, u16::from(b'b'), 0xdc96];
+    let expected = [b'a', 0xed, 0xa0, 0xbd, b'b', 0xed, 0xb2, 0x96];
+
+    assert_eq!(simd_cesu8::encode_utf16(&units), expected);
+    assert_eq!(mutf8::encode_utf16(&units), expected);
+    assert!(simd_cesu8::decode(&expected).is_err());
+    assert_eq!(
+        simd_cesu8::decode_to_utf16_lossless(&expected).unwrap(),
+        units
+    );
+    assert_eq!(mutf8::decode_to_utf16_lossless(&expected).unwrap(), units);
+}
+
+#[test]
+fn decode_to_utf16_matches_decode() {
+    for value in VALUES {
+        let units = utf16(value);
+
+        for bytes in [simd_cesu8::encode(value), value.as_bytes().into()] {
+            assert_eq!(
+                simd_cesu8::decode_to_utf16(&bytes).unwrap(),
+                units,
+                "{value:?}"
+            );
+        }
+
+        for bytes in [mutf8::encode(value), value.as_bytes().into()] {
+            assert_eq!(mutf8::decode_to_utf16(&bytes).unwrap(), units, "{value:?}");
+        }
+    }
+}
+
+#[test]
+fn decode_to_utf16_rejects_what_decode_rejects() {
+    let cases: [&[u8]; 4] = [
+        &[0xed, 0xa0, 0xbd],
+        &[b'a', 0xff],
+        &[0xed, 0xb2, 0x96, 0xf0, 0x9f, 0x92, 0x96],
+        &[0xc0, 0x80, 0xc0],
+    ];
+
+    for bytes in cases {
+        assert!(simd_cesu8::decode(bytes).is_err(), "{bytes:x?}");
+        assert!(simd_cesu8::decode_to_utf16(bytes).is_err(), "{bytes:x?}");
+        assert!(mutf8::decode(bytes).is_err(), "{bytes:x?}");
+        assert!(mutf8::decode_to_utf16(bytes).is_err(), "{bytes:x?}");
+    }
+}
+
+#[test]
+fn utf16_round_trips() {
+    for value in VALUES {
+        let units = utf16(value);
+
+        let encoded = simd_cesu8::encode_utf16(&units);
+        assert_eq!(simd_cesu8::decode_to_utf16(&encoded).unwrap(), units);
+
+        let encoded = mutf8::encode_utf16(&units);
+        assert_eq!(mutf8::decode_to_utf16(&encoded).unwrap(), units);
+    }
+}
+
+#[test]
+fn decode_to_utf16_lossless_matches_decode_to_utf16() {
+    for value in VALUES {
+        for bytes in [simd_cesu8::encode(value), value.as_bytes().into()] {
+            assert_eq!(
+                simd_cesu8::decode_to_utf16_lossless(&bytes).unwrap(),
+                simd_cesu8::decode_to_utf16(&bytes).unwrap(),
+                "{value:?}"
+            );
+        }
+
+        let bytes = mutf8::encode(value);
+        assert_eq!(
+            mutf8::decode_to_utf16_lossless(&bytes).unwrap(),
+            mutf8::decode_to_utf16(&bytes).unwrap(),
+            "{value:?}"
+        );
+    }
+}
+
+#[test]
+fn lone_surrogates_round_trip_losslessly() {
+    let cases: [&[u16]; 7] = [
+        &[0xd83d],
+        &[0xdc96],
+        &[0xdc96, 0xd83d],
+        &[0xd83d, 0xd83d, 0xdc96],
+        &[0xd83d, 0xdc96, 0xdc96],
+        &[0x00, 0xdbff, u16::from(b'a'), 0xdfff],
+        &[0xdfff, 0xd800],
+    ];
+
+    for units in cases {
+        let encoded = simd_cesu8::encode_utf16(units);
+        assert_eq!(
+            simd_cesu8::decode_to_utf16_lossless(&encoded).unwrap(),
+            units
+        );
+
+        let encoded = mutf8::encode_utf16(units);
+        assert_eq!(mutf8::decode_to_utf16_lossless(&encoded).unwrap(), units);
+    }
+}
+
+#[test]
+fn decode_to_utf16_lossless_rejects_everything_else() {
+    let cases: [&[u8]; 6] = [
+        &[0xed, 0xa0],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2],
+        &[0xed, 0xa0, 0xbd, 0xff],
+        &[0xed, 0xa0, b'a'],
+        &[0xed, 0xb2, 0x96, 0xf0, 0x9f, 0x92, 0x96],
+        &[0xed, 0xb2, 0x96, 0xc0, 0x80, 0xc0],
+    ];
+
+    for bytes in cases {
+        assert!(
+            simd_cesu8::decode_to_utf16_lossless(bytes).is_err(),
+            "{bytes:x?}"
+        );
+        assert!(
+            mutf8::decode_to_utf16_lossless(bytes).is_err(),
+            "{bytes:x?}"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Tests for the validated `Cesu8Str` and `Mutf8Str` wrappers.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash};
+use std::hash::DefaultHasher;
+
+use simd_cesu8::mutf8::{self, Mutf8Buf, Mutf8Str};
+use simd_cesu8::{Cesu8Buf, Cesu8Str};
+
+const SAMPLES: [&[u8]; 9] = [
+    b"",
+    b"Hello, world!",
+    b"a\0b",
+    &[0xc0, 0x80],
+    &[0xc8, 0x85, 0xe2, 0x82, 0xac],
+    &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    &[0xf0, 0x9f, 0x92, 0x96],
+    &[0xed, 0xa0, 0xbd],
+    &[0xff],
+];
+
+#[test]
+fn cesu8_str_agrees_with_decode_strict() {
+    for bytes in SAMPLES {
+        let expected = simd_cesu8::decode_strict(bytes);
+        let Ok(value) = Cesu8Str::from_bytes(bytes) else {
+            assert!(expected.is_err());
+            continue;
+        };
+
+        let expected = expected.unwrap();
+        assert_eq!(value.as_bytes(), bytes);
+        assert_eq!(value.to_str(), expected);
+        assert_eq!(
+            value.as_utf8().is_some(),
+            matches!(expected, Cow::Borrowed(_))
+        );
+    }
+}
+
+#[test]
+fn mutf8_str_agrees_with_decode_strict() {
+    for bytes in SAMPLES {
+        let expected = mutf8::decode_strict(bytes);
+        let Ok(value) = Mutf8Str::from_bytes(bytes) else {
+            assert!(expected.is_err());
+            continue;
+        };
+
+        let expected = expected.unwrap();
+        assert_eq!(value.as_bytes(), bytes);
+        assert_eq!(value.to_str(), expected);
+        assert_eq!(
+            value.as_utf8().is_some(),
+            matches!(expected, Cow::Borrowed(_))
+        );
+    }
+}
+
+// NOTE: The hashes only agree with the decoded `str` because the default
+// hasher is a streaming one, as documented on `Cesu8Str`.
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(value)
+}
+
+#[test]
+fn cesu8_str_eq_and_hash_agree_with_the_decoded_str() {
+    for bytes in SAMPLES {
+        let Ok(value) = Cesu8Str::from_bytes(bytes) else {
+            continue;
+        };
+
+        let expected = value.to_str();
+        assert!(*value == *expected, "{bytes:x?}");
+        assert!(*expected == *value, "{bytes:x?}");
+        assert_eq!(hash_of(value), hash_of(&*expected), "{bytes:x?}");
+
+        for other in ["", "a", "Hello, world?", "💖💖", "\0"] {
+            assert_eq!(*value == *other, expected == other, "{bytes:x?} {other:?}");
+        }
+    }
+}
+
+#[test]
+fn mutf8_str_eq_and_hash_agree_with_the_decoded_str() {
+    for bytes in SAMPLES {
+        let Ok(value) = Mutf8Str::from_bytes(bytes) else {
+            continue;
+        };
+
+        let expected = value.to_str();
+        assert!(*value == *expected, "{bytes:x?}");
+        assert!(*expected == *value, "{bytes:x?}");
+        assert_eq!(hash_of(value), hash_of(&*expected), "{bytes:x?}");
+
+        for other in ["", "a", "\0", "\0\0", "💖"] {
+            assert_eq!(*value == *other, expected == other, "{bytes:x?} {other:?}");
+        }
+    }
+}
+
+#[test]
+fn eq_stops_at_a_prefix() {
+    let bytes = [b'a', 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xc0, 0x80, b'b'];
+    let value = Mutf8Str::from_bytes(&bytes).unwrap();
+
+    assert!(*value == *"a💖\0b");
+    assert!(*value != *"a💖\0");
+    assert!(*value != *"a💖");
+    assert!(*value != *"a💖\0bc");
+}
+
+#[test]
+fn debug_matches_the_decoded_str() {
+    for value in [
+        "",
+        "a'b\"c\n\\",
+        "\u{301}e\u{301}",
+        "a\0💖\u{7f}",
+        "\u{feff}💖💖",
+    ] {
+        let bytes = simd_cesu8::encode(value);
+        let cesu8 = Cesu8Str::from_bytes(&bytes).unwrap();
+        assert_eq!(format!("{cesu8:?}"), format!("{value:?}"));
+
+        let bytes = mutf8::encode(value);
+        let mutf8 = Mutf8Str::from_bytes(&bytes).unwrap();
+        assert_eq!(format!("{mutf8:?}"), format!("{value:?}"));
+    }
+}
+
+#[test]
+fn collecting_chars_matches_encode() {
+    for value in ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"] {
+        let cesu8: Cesu8Buf = value.chars().collect();
+        assert_eq!(*cesu8.as_bytes(), *simd_cesu8::encode(value), "{value:?}");
+        assert_eq!(cesu8.to_str(), value);
+
+        let mutf8: Mutf8Buf = value.chars().collect();
+        assert_eq!(*mutf8.as_bytes(), *mutf8::encode(value), "{value:?}");
+        assert_eq!(mutf8.to_str(), value);
+    }
+}
+
+#[test]
+fn extend_appends_to_the_buffer() {
+    let mut value = Mutf8Buf::new();
+    value.push('a');
+    value.extend("\0💖".chars());
+
+    assert_eq!(value.into_bytes(), *mutf8::encode("a\0💖"));
+}
@@ -0,0 +1,108 @@
+//! Tests for reading and writing strings with a varint length prefix.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use simd_cesu8::{mutf8, VarintStringError};
+
+#[test]
+fn round_trips_across_prefix_widths() {
+    for len in [0, 1, 127, 128, 300, 16_383, 16_384] {
+        let value: String = "a\0💖".chars().cycle().take(len).collect();
+
+        let mut buffer = Vec::new();
+        simd_cesu8::write_varint_string(&value, &mut buffer);
+        buffer.push(0xff);
+        let (decoded, consumed) = simd_cesu8::read_varint_string(&buffer).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, buffer.len() - 1);
+
+        let mut buffer = Vec::new();
+        mutf8::write_varint_string(&value, &mut buffer);
+        buffer.push(0xff);
+        let (decoded, consumed) = mutf8::read_varint_string(&buffer).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, buffer.len() - 1);
+    }
+}
+
+#[test]
+fn writes_minimal_prefix() {
+    let mut buffer = Vec::new();
+    simd_cesu8::write_varint_string(&"a".repeat(300), &mut buffer);
+    assert_eq!(&buffer[..2], &[0xac, 0x02]);
+    assert_eq!(buffer.len(), 302);
+}
+
+#[test]
+fn accepts_non_minimal_prefix() {
+    let bytes = [0x81, 0x80, 0x00, b'a'];
+    let (decoded, consumed) = simd_cesu8::read_varint_string(&bytes).unwrap();
+    assert_eq!(decoded, "a");
+    assert_eq!(consumed, 4);
+}
+
+#[test]
+fn rejects_truncated_prefix() {
+    for bytes in [&[][..], &[0x80], &[0xff; 9]] {
+        assert_eq!(
+            simd_cesu8::read_varint_string(bytes),
+            Err(VarintStringError::UnexpectedEnd)
+        );
+        assert_eq!(
+            mutf8::read_varint_string(bytes),
+            Err(VarintStringError::UnexpectedEnd)
+        );
+    }
+}
+
+#[test]
+fn rejects_overlong_prefix() {
+    let mut eleven_bytes = [0x80; 11];
+    eleven_bytes[10] = 0x00;
+
+    let mut overflow = [0xff; 10];
+    overflow[9] = 0x02;
+
+    for bytes in [&eleven_bytes[..], &overflow] {
+        assert_eq!(
+            simd_cesu8::read_varint_string(bytes),
+            Err(VarintStringError::InvalidLength)
+        );
+        assert_eq!(
+            mutf8::read_varint_string(bytes),
+            Err(VarintStringError::InvalidLength)
+        );
+    }
+}
+
+#[test]
+fn rejects_length_past_end() {
+    let bytes = [0x04, b'a', b'b', b'c'];
+    assert_eq!(
+        simd_cesu8::read_varint_string(&bytes),
+        Err(VarintStringError::UnexpectedEnd)
+    );
+    assert_eq!(
+        mutf8::read_varint_string(&bytes),
+        Err(VarintStringError::UnexpectedEnd)
+    );
+}
+
+#[test]
+fn reports_decoding_errors_separately() {
+    let bytes = [0x04, 0xf0, 0x9f, 0x92, 0x96];
+    assert!(matches!(
+        simd_cesu8::read_varint_string(&bytes),
+        Err(VarintStringError::Decoding(_))
+    ));
+
+    let bytes = [0x01, 0x00];
+    assert!(matches!(
+        mutf8::read_varint_string(&bytes),
+        Err(VarintStringError::Decoding(_))
+    ));
+}
@@ -0,0 +1,170 @@
+//! Tests for decoding with errors that report where the input is invalid.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+
+use simd_cesu8::{mutf8, DecodingError, DecodingErrorKind, Flavor};
+
+#[test]
+fn decode_verbose_matches_decode_on_valid_input() {
+    let values = ["", "Hello, world!", "\0", "💖", "a\0💖ȅ€", "💖💖"];
+
+    for value in values {
+        let encoded = simd_cesu8::encode(value);
+        assert_eq!(simd_cesu8::decode_verbose(&encoded).unwrap(), value);
+
+        let encoded = mutf8::encode(value);
+        assert_eq!(mutf8::decode_verbose(&encoded).unwrap(), value);
+    }
+}
+
+#[test]
+fn decode_verbose_borrows_utf8() {
+    let value = "Hello, world!";
+    let decoded = simd_cesu8::decode_verbose(value.as_bytes()).unwrap();
+    assert!(matches!(decoded, Cow::Borrowed(_)));
+}
+
+#[test]
+fn decode_verbose_reports_where_the_error_begins() {
+    let cases: [(&[u8], usize, u8, DecodingErrorKind); 6] = [
+        (
+            &[b'a', 0x80],
+            1,
+            0x80,
+            DecodingErrorKind::UnexpectedContinuation,
+        ),
+        (
+            &[b'a', 0xc3, b'b', 0xed],
+            1,
+            0xc3,
+            DecodingErrorKind::ExpectedContinuation,
+        ),
+        (
+            &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96, 0xed, 0xa0],
+            6,
+            0xed,
+            DecodingErrorKind::UnexpectedEof,
+        ),
+        (
+            &[b'a', b'b', 0xed, 0xb2, 0x96, 0xff],
+            2,
+            0xed,
+            DecodingErrorKind::InvalidSurrogatePair,
+        ),
+        (
+            &[0xe0, 0x80, 0x80, 0xff],
+            0,
+            0xe0,
+            DecodingErrorKind::OverlongEncoding,
+        ),
+        (
+            &[b'a', b'b', b'c', 0xff],
+            3,
+            0xff,
+            DecodingErrorKind::InvalidByte,
+        ),
+    ];
+
+    for (bytes, offset, byte, kind) in cases {
+        let error = simd_cesu8::decode_verbose(bytes).unwrap_err();
+        assert_eq!(error.offset(), offset, "{bytes:x?}");
+        assert_eq!(error.byte(), byte, "{bytes:x?}");
+        assert_eq!(error.kind(), kind, "{bytes:x?}");
+
+        let error = mutf8::decode_verbose(bytes).unwrap_err();
+        assert_eq!(error.offset(), offset, "{bytes:x?}");
+        assert_eq!(error.kind(), kind, "{bytes:x?}");
+    }
+}
+
+#[test]
+fn decode_verbose_reports_offsets_past_the_first_chunk() {
+    let mut bytes = simd_cesu8::encode(&"a💖".repeat(100)).into_owned();
+    let offset = bytes.len();
+    bytes.extend_from_slice(&[0xed, 0xa0, 0xbd, b'a']);
+
+    let error = simd_cesu8::decode_verbose(&bytes).unwrap_err();
+    assert_eq!(error.offset(), offset);
+    assert_eq!(error.kind(), DecodingErrorKind::InvalidSurrogatePair);
+}
+
+#[test]
+fn mutf8_decode_verbose_reports_raw_nulls() {
+    let bytes = [b'a', 0xc0, 0x80, 0x00, 0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96];
+    let error = mutf8::decode_verbose(&bytes).unwrap_err();
+    assert_eq!(error.offset(), 3);
+    assert_eq!(error.byte(), 0x00);
+    assert_eq!(error.kind(), DecodingErrorKind::Utf8NotCesu8);
+}
+
+#[test]
+fn decode_verbose_agrees_with_decode() {
+    let inputs: [&[u8]; 5] = [
+        &[0xed],
+        &[0xc0, 0x80],
+        &[0xf0, 0x9f, 0x92, 0x96, 0xed],
+        &[b'a', 0xed, 0xa0, 0xbd, 0xed, 0xa0, 0xbd],
+        &[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96],
+    ];
+
+    for bytes in inputs {
+        assert_eq!(
+            simd_cesu8::decode_verbose(bytes).map_err(DecodingError::from),
+            simd_cesu8::decode(bytes),
+            "{bytes:x?}"
+        );
+        assert_eq!(
+            mutf8::decode_verbose(bytes).map_err(DecodingError::from),
+            mutf8::decode(bytes),
+            "{bytes:x?}"
+        );
+    }
+}
+
+#[test]
+fn decode_verbose_resumes_after_the_valid_utf8_prefix() {
+    // NOTE: Each prefix is valid UTF-8, so the validation stops right at the
+    // surrogate pair, but only some of them are valid CESU-8 or MUTF-8 too.
+    let prefixes = ["Hello, world!", "a\0€", "a💖"].map(|prefix| prefix.repeat(10));
+
+    for prefix in prefixes {
+        let mut bytes = prefix.clone().into_bytes();
+        bytes.extend_from_slice(&[0xed, 0xa0, 0xbd, 0xed, 0xb2, 0x96]);
+
+        assert_eq!(
+            simd_cesu8::decode_verbose(&bytes).ok(),
+            simd_cesu8::decode(&bytes).ok(),
+            "{prefix:?}"
+        );
+        assert_eq!(
+            mutf8::decode_verbose(&bytes).ok(),
+            mutf8::decode(&bytes).ok(),
+            "{prefix:?}"
+        );
+
+        bytes.push(0xff);
+
+        let error = simd_cesu8::decode_verbose(&bytes).unwrap_err();
+        assert_eq!(
+            Some(error.offset()),
+            simd_cesu8::first_invalid_index(&bytes, Flavor::Cesu8),
+            "{prefix:?}"
+        );
+
+        let error = mutf8::decode_verbose(&bytes).unwrap_err();
+        assert_eq!(
+            Some(error.offset()),
+            simd_cesu8::first_invalid_index(&bytes, Flavor::Mutf8),
+            "{prefix:?}"
+        );
+    }
+}
+
+#[test]
+fn decoding_error_at_displays_the_offset_and_byte() {
+    let error = simd_cesu8::decode_verbose(&[b'a', 0xff]).unwrap_err();
+    assert_eq!(error.to_string(), "invalid byte at offset 1 (byte 0xff)");
+}
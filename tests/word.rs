@@ -0,0 +1,217 @@
+//! Tests that the word-at-a-time (SWAR) helpers give the same answers as a
+//! byte-at-a-time check, regardless of the byte order of the target.
+#![cfg(feature = "bench")]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
+use simd_cesu8::implementation::{fallback, word};
+
+const USIZE_SIZE: usize = mem::size_of::<usize>();
+
+/// Returns every interesting arrangement of a word's bytes: each special byte
+/// alone in each position, surrounded by each filler byte, plus a spread of
+/// pseudo-random words.
+fn words() -> impl Iterator<Item = [u8; USIZE_SIZE]> {
+    const SPECIAL: [u8; 8] = [0x00, 0x01, 0x7f, 0x80, 0xef, 0xf0, 0xf7, 0xf8];
+    const FILLER: [u8; 5] = [0x00, 0x41, 0x80, 0xf0, 0xff];
+
+    let single = FILLER.into_iter().flat_map(|filler| {
+        SPECIAL.into_iter().flat_map(move |special| {
+            (0..USIZE_SIZE).map(move |position| {
+                let mut bytes = [filler; USIZE_SIZE];
+                bytes[position] = special;
+                bytes
+            })
+        })
+    });
+
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let random = (0..4096).map(move |_| {
+        // NOTE: This is xorshift64, which is plenty for spreading bits around.
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let mut bytes = [0; USIZE_SIZE];
+        bytes.copy_from_slice(&state.to_le_bytes()[..USIZE_SIZE]);
+        bytes
+    });
+
+    single.chain(random)
+}
+
+fn assert_byte_order_independent(test: fn(usize) -> bool, reference: fn(&[u8]) -> bool) {
+    for bytes in words() {
+        let expected = reference(&bytes);
+        assert_eq!(test(usize::from_le_bytes(bytes)), expected, "{bytes:02x?}");
+        assert_eq!(test(usize::from_be_bytes(bytes)), expected, "{bytes:02x?}");
+    }
+}
+
+#[test]
+fn usize_repeat_u8_is_byte_order_independent() {
+    for byte in 0..=u8::MAX {
+        let word = word::usize_repeat_u8(byte);
+        assert_eq!(word, usize::from_le_bytes([byte; USIZE_SIZE]));
+        assert_eq!(word, usize::from_be_bytes([byte; USIZE_SIZE]));
+    }
+}
+
+#[test]
+fn word_contains_null_byte_matches_bytes() {
+    assert_byte_order_independent(word::word_contains_null_byte, |bytes| bytes.contains(&0x00));
+}
+
+#[test]
+fn word_is_ascii_matches_bytes() {
+    assert_byte_order_independent(word::word_is_ascii, <[u8]>::is_ascii);
+}
+
+#[test]
+fn word_is_non_null_ascii_matches_bytes() {
+    assert_byte_order_independent(word::word_is_non_null_ascii, |bytes| {
+        bytes.iter().all(|&byte| (0x01..=0x7f).contains(&byte))
+    });
+}
+
+#[test]
+fn word_contains_utf8_4_byte_char_header_matches_bytes() {
+    assert_byte_order_independent(word::word_contains_utf8_4_byte_char_header, |bytes| {
+        bytes.iter().any(|&byte| byte & 0b1111_1000 == 0b1111_0000)
+    });
+}
+
+#[test]
+fn word_contains_null_or_utf8_4_byte_char_header_matches_bytes() {
+    assert_byte_order_independent(
+        word::word_contains_null_or_utf8_4_byte_char_header,
+        |bytes| {
+            bytes
+                .iter()
+                .any(|&byte| byte == 0x00 || byte & 0b1111_1000 == 0b1111_0000)
+        },
+    );
+}
+
+/// Returns slices of every length up to a few words, at every offset from an
+/// aligned address, where one byte that only differs from the filler in a
+/// single bit is placed at each position.
+///
+/// These catch a mask that's off by a bit, as the special byte and the filler
+/// can only be told apart by exactly the right mask.
+fn slices() -> impl Iterator<Item = Vec<u8>> {
+    const PAIRS: [(u8, u8); 8] = [
+        (0x00, 0x01),
+        (0x00, 0x40),
+        (0x80, 0x00),
+        (0x80, 0x7f),
+        (0xf0, 0xf8),
+        (0xf7, 0xff),
+        (0xf0, 0xe0),
+        (0xf4, 0x74),
+    ];
+
+    PAIRS.into_iter().flat_map(|(special, filler)| {
+        (0..=USIZE_SIZE * 4).flat_map(move |len| {
+            (0..=len).map(move |position| {
+                let mut bytes = vec![filler; len];
+
+                if let Some(byte) = bytes.get_mut(position) {
+                    *byte = special;
+                }
+
+                bytes
+            })
+        })
+    })
+}
+
+fn assert_matches_fallback<T: PartialEq + core::fmt::Debug>(
+    test: fn(&[u8]) -> T,
+    reference: fn(&[u8]) -> T,
+) {
+    // NOTE: We copy each slice into a buffer that's a word longer than it at
+    // every offset, so the word-at-a-time path starts both aligned and not.
+    let mut buffer = [0; USIZE_SIZE * 6];
+
+    for bytes in slices() {
+        for offset in 0..USIZE_SIZE {
+            let slice = &mut buffer[offset..offset + bytes.len()];
+            slice.copy_from_slice(&bytes);
+            assert_eq!(test(slice), reference(slice), "{bytes:02x?} {offset}");
+        }
+    }
+}
+
+#[test]
+fn contains_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        word::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+}
+
+#[test]
+fn contains_null_or_utf8_4_byte_char_header_matches_fallback() {
+    assert_matches_fallback(
+        word::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+}
+
+#[test]
+fn count_leading_ascii_matches_fallback() {
+    assert_matches_fallback(word::count_leading_ascii, fallback::count_leading_ascii);
+}
+
+#[test]
+fn count_leading_non_null_ascii_matches_fallback() {
+    assert_matches_fallback(
+        word::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[test]
+fn scanners_never_look_outside_the_slice() {
+    const MAX_LEN: usize = USIZE_SIZE * 4;
+
+    // NOTE: Every byte around the slice is one that every scanner stops at, so
+    // reading even one byte past either end of the slice changes the answer.
+    for special in [0x00, 0x80, 0xf0] {
+        let mut buffer = [special; MAX_LEN + USIZE_SIZE * 4];
+        let aligned = buffer.as_ptr().align_offset(USIZE_SIZE) + USIZE_SIZE;
+
+        for misalignment in 0..USIZE_SIZE {
+            let start = aligned + misalignment;
+
+            for len in 0..=MAX_LEN {
+                buffer[start..start + len].fill(b'a');
+
+                let slice = &buffer[start..start + len];
+                assert_eq!(slice.as_ptr() as usize % USIZE_SIZE, misalignment);
+
+                let context = (special, misalignment, len);
+                assert!(
+                    !word::contains_utf8_4_byte_char_header(slice),
+                    "{context:?}"
+                );
+                assert!(
+                    !word::contains_null_or_utf8_4_byte_char_header(slice),
+                    "{context:?}"
+                );
+                assert_eq!(word::count_leading_ascii(slice), len, "{context:?}");
+                assert_eq!(
+                    word::count_leading_non_null_ascii(slice),
+                    len,
+                    "{context:?}"
+                );
+
+                buffer[start..start + len].fill(special);
+            }
+        }
+    }
+}
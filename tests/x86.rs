@@ -0,0 +1,116 @@
+//! Tests that the `std::arch` scanners give the same answers as the fallback,
+//! for every instruction set the CPU supports.
+#![cfg(all(feature = "bench", feature = "std", target_arch = "x86_64"))]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use simd_cesu8::implementation::{fallback, x86};
+
+/// Returns slices of every length up to a few vectors, where each special byte
+/// is placed at each position of a filler that's one bit away from it.
+fn slices() -> impl Iterator<Item = Vec<u8>> {
+    const PAIRS: [(u8, u8); 6] = [
+        (0x00, 0x01),
+        (0x00, 0x80),
+        (0xf0, 0xf8),
+        (0xf7, 0xff),
+        (0xf0, 0xe0),
+        (0xf4, 0x74),
+    ];
+
+    PAIRS.into_iter().flat_map(|(special, filler)| {
+        (0..=100).flat_map(move |len| {
+            (0..=len).map(move |position| {
+                let mut bytes = vec![filler; len];
+
+                if let Some(byte) = bytes.get_mut(position) {
+                    *byte = special;
+                }
+
+                bytes
+            })
+        })
+    })
+}
+
+fn assert_matches_fallback<T: Debug + PartialEq>(
+    test: impl Fn(&[u8]) -> T,
+    reference: fn(&[u8]) -> T,
+) {
+    for bytes in slices() {
+        assert_eq!(test(&bytes), reference(&bytes), "{bytes:02x?}");
+    }
+}
+
+#[test]
+fn sse2_matches_fallback() {
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::sse2::contains_utf8_4_byte_char_header(bytes) },
+        fallback::contains_utf8_4_byte_char_header,
+    );
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::sse2::contains_null_or_utf8_4_byte_char_header(bytes) },
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::sse2::count_leading_ascii(bytes) },
+        fallback::count_leading_ascii,
+    );
+    // SAFETY: SSE2 is part of the x86_64 baseline.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::sse2::count_leading_non_null_ascii(bytes) },
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[test]
+fn avx2_matches_fallback() {
+    if !is_x86_feature_detected!("avx2") {
+        return;
+    }
+
+    // SAFETY: We just checked that the CPU supports AVX2.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::avx2::contains_utf8_4_byte_char_header(bytes) },
+        fallback::contains_utf8_4_byte_char_header,
+    );
+    // SAFETY: We just checked that the CPU supports AVX2.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::avx2::contains_null_or_utf8_4_byte_char_header(bytes) },
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+    // SAFETY: We just checked that the CPU supports AVX2.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::avx2::count_leading_ascii(bytes) },
+        fallback::count_leading_ascii,
+    );
+    // SAFETY: We just checked that the CPU supports AVX2.
+    assert_matches_fallback(
+        |bytes| unsafe { x86::avx2::count_leading_non_null_ascii(bytes) },
+        fallback::count_leading_non_null_ascii,
+    );
+}
+
+#[test]
+fn dispatch_matches_fallback() {
+    assert_matches_fallback(
+        x86::contains_utf8_4_byte_char_header,
+        fallback::contains_utf8_4_byte_char_header,
+    );
+    assert_matches_fallback(
+        x86::contains_null_or_utf8_4_byte_char_header,
+        fallback::contains_null_or_utf8_4_byte_char_header,
+    );
+    assert_matches_fallback(x86::count_leading_ascii, fallback::count_leading_ascii);
+    assert_matches_fallback(
+        x86::count_leading_non_null_ascii,
+        fallback::count_leading_non_null_ascii,
+    );
+}